@@ -12,17 +12,21 @@ mod transaction;
 
 use self::access::gen_state_access_trace;
 use crate::error::Error;
-use crate::evm::opcodes::{gen_associated_ops, gen_begin_tx_ops, gen_end_tx_ops};
+use crate::evm::opcodes::{
+    gen_associated_ops, gen_begin_tx_ops, gen_end_tx_ops, gen_invalid_tx_ops,
+};
 use crate::operation::{CallContextField, Operation, RWCounter, StartOp, RW};
 use crate::rpc::GethClient;
 use crate::state_db::{self, CodeDB, StateDB};
 pub use access::{Access, AccessSet, AccessValue, CodeSource};
-pub use block::{Block, BlockContext};
+pub use block::{Block, BlockContext, NUM_PREV_BLOCK_ALLOWED};
 pub use call::{Call, CallContext, CallKind};
 use core::fmt::Debug;
 use eth_types::sign_types::{pk_bytes_le, pk_bytes_swap_endianness, SignData};
 use eth_types::ToWord;
-use eth_types::{self, geth_types, Address, GethExecStep, GethExecTrace, Word};
+use eth_types::{
+    self, evm_types::HardFork, geth_types, Address, GethExecStep, GethExecTrace, Word,
+};
 use ethers_providers::JsonRpcClient;
 pub use execution::{
     CopyDataType, CopyEvent, CopyStep, ExecState, ExecStep, ExpEvent, ExpStep, NumberOrHash,
@@ -250,9 +254,18 @@ impl<'a> CircuitInputBuilder {
         geth_trace: &GethExecTrace,
         is_last_tx: bool,
     ) -> Result<(), Error> {
-        let mut tx = self.new_tx(eth_tx, !geth_trace.failed)?;
+        let mut tx = self.new_tx(eth_tx, !geth_trace.failed && !geth_trace.invalid)?;
         let mut tx_ctx = TransactionContext::new(eth_tx, geth_trace, is_last_tx)?;
 
+        // A tx rejected before execution doesn't touch the state, so it's
+        // handled by a single InvalidTx step instead of BeginTx/EndTx.
+        if geth_trace.invalid {
+            let invalid_tx_step = gen_invalid_tx_ops(&mut self.state_ref(&mut tx, &mut tx_ctx))?;
+            tx.steps_mut().push(invalid_tx_step);
+            self.block.txs.push(tx);
+            return Ok(());
+        }
+
         // TODO: Move into gen_associated_steps with
         // - execution_state: BeginTx
         // - op: None
@@ -370,6 +383,125 @@ pub fn get_call_memory_offset_length(step: &GethExecStep, nth: usize) -> Result<
 
 type EthBlock = eth_types::Block<eth_types::Transaction>;
 
+/// Override of the state of an account, like an entry of the
+/// `stateOverride` of `eth_call`. The fields left to `None` keep their value.
+#[derive(Debug, Clone, Default)]
+pub struct AccountOverride {
+    /// Nonce
+    pub nonce: Option<Word>,
+    /// Balance
+    pub balance: Option<Word>,
+    /// EVM code
+    pub code: Option<Vec<u8>>,
+    /// Storage replacing the whole account storage.
+    pub storage: Option<HashMap<Word, Word>>,
+    /// Storage slots replacing the ones of the account storage.
+    pub storage_diff: HashMap<Word, Word>,
+}
+
+/// Overrides of the state a block is executed on, by account address.
+pub type StateOverride = HashMap<Address, AccountOverride>;
+
+/// Build a partial StateDB and a CodeDB from the `eth_getProof` responses and
+/// the codes of the accounts accessed by a block.
+pub fn build_state_code_db(
+    proofs: Vec<eth_types::EIP1186ProofResponse>,
+    codes: HashMap<Address, Vec<u8>>,
+) -> (StateDB, CodeDB) {
+    build_state_code_db_with_override(proofs, codes, &StateOverride::new())
+}
+
+/// Build a partial StateDB and a CodeDB like [`build_state_code_db`], then
+/// apply `state_override` on top, for what-if analysis of a block executed on
+/// a modified state. The traces of the block must have been generated with
+/// the same overrides.
+pub fn build_state_code_db_with_override(
+    proofs: Vec<eth_types::EIP1186ProofResponse>,
+    codes: HashMap<Address, Vec<u8>>,
+    state_override: &StateOverride,
+) -> (StateDB, CodeDB) {
+    let mut sdb = StateDB::new();
+    for proof in proofs {
+        let mut storage = HashMap::new();
+        for storage_proof in proof.storage_proof {
+            storage.insert(storage_proof.key, storage_proof.value);
+        }
+        sdb.set_account(
+            &proof.address,
+            state_db::Account {
+                nonce: proof.nonce,
+                balance: proof.balance,
+                storage,
+                code_hash: proof.code_hash,
+            },
+        )
+    }
+
+    let mut code_db = CodeDB::new();
+    for (_address, code) in codes {
+        code_db.insert(code.clone());
+    }
+
+    for (address, account_override) in state_override {
+        let (_, account) = sdb.get_account_mut(address);
+        if let Some(nonce) = account_override.nonce {
+            account.nonce = nonce;
+        }
+        if let Some(balance) = account_override.balance {
+            account.balance = balance;
+        }
+        if let Some(code) = &account_override.code {
+            account.code_hash = code_db.insert(code.clone());
+        }
+        if let Some(storage) = &account_override.storage {
+            account.storage = storage.clone();
+        }
+        account.storage.extend(&account_override.storage_diff);
+    }
+    (sdb, code_db)
+}
+
+/// Parse the raw JSON response of a `debug_traceBlockByNumber` or
+/// `debug_traceBlockByHash` call into the execution trace of each tx.
+pub fn geth_traces_from_json(json: &str) -> Result<Vec<GethExecTrace>, Error> {
+    let traces: eth_types::ResultGethExecTraces =
+        serde_json::from_str(json).map_err(Error::SerdeError)?;
+    Ok(traces.0.into_iter().map(|trace| trace.result).collect())
+}
+
+/// Generate the circuit inputs of a block from the raw JSON response of a
+/// `debug_traceBlock*` call, the block and the state proofs of the accessed
+/// accounts, without querying geth. `state_override` is applied on the state
+/// from the proofs, see [`build_state_code_db_with_override`], and the block is
+/// executed with the EVM rules of `hardfork`.
+#[allow(clippy::too_many_arguments)]
+pub fn gen_inputs_from_geth_json(
+    chain_id: Word,
+    circuits_params: CircuitsParams,
+    hardfork: HardFork,
+    eth_block: &EthBlock,
+    geth_traces_json: &str,
+    proofs: Vec<eth_types::EIP1186ProofResponse>,
+    codes: HashMap<Address, Vec<u8>>,
+    state_override: &StateOverride,
+    history_hashes: Vec<Word>,
+    prev_state_root: Word,
+) -> Result<CircuitInputBuilder, Error> {
+    let geth_traces = geth_traces_from_json(geth_traces_json)?;
+    let (sdb, code_db) = build_state_code_db_with_override(proofs, codes, state_override);
+    let block = Block::new(
+        chain_id,
+        history_hashes,
+        prev_state_root,
+        eth_block,
+        circuits_params,
+        hardfork,
+    )?;
+    let mut builder = CircuitInputBuilder::new(sdb, code_db, block);
+    builder.handle_block(eth_block, &geth_traces)?;
+    Ok(builder)
+}
+
 /// Struct that wraps a GethClient and contains methods to perform all the steps
 /// necessary to generate the circuit inputs for a block by querying geth for
 /// the necessary information and using the CircuitInputBuilder.
@@ -377,13 +509,16 @@ pub struct BuilderClient<P: JsonRpcClient> {
     cli: GethClient<P>,
     chain_id: Word,
     circuits_params: CircuitsParams,
+    hardfork: HardFork,
 }
 
 impl<P: JsonRpcClient> BuilderClient<P> {
-    /// Create a new BuilderClient
+    /// Create a new BuilderClient generating the circuit inputs of blocks
+    /// executed with the EVM rules of `hardfork`
     pub async fn new(
         client: GethClient<P>,
         circuits_params: CircuitsParams,
+        hardfork: HardFork,
     ) -> Result<Self, Error> {
         let chain_id = client.get_chain_id().await?;
 
@@ -391,6 +526,7 @@ impl<P: JsonRpcClient> BuilderClient<P> {
             cli: client,
             chain_id: chain_id.into(),
             circuits_params,
+            hardfork,
         })
     }
 
@@ -403,8 +539,9 @@ impl<P: JsonRpcClient> BuilderClient<P> {
         let eth_block = self.cli.get_block_by_number(block_num.into()).await?;
         let geth_traces = self.cli.trace_block_by_number(block_num.into()).await?;
 
-        // fetch up to 256 blocks
-        let mut n_blocks = std::cmp::min(256, block_num as usize);
+        // fetch up to NUM_PREV_BLOCK_ALLOWED blocks, fewer for the blocks right
+        // after genesis
+        let mut n_blocks = std::cmp::min(NUM_PREV_BLOCK_ALLOWED, block_num) as usize;
         let mut next_hash = eth_block.parent_hash;
         let mut prev_state_root: Option<Word> = None;
         let mut history_hashes = vec![Word::default(); n_blocks];
@@ -504,28 +641,7 @@ impl<P: JsonRpcClient> BuilderClient<P> {
         proofs: Vec<eth_types::EIP1186ProofResponse>,
         codes: HashMap<Address, Vec<u8>>,
     ) -> (StateDB, CodeDB) {
-        let mut sdb = StateDB::new();
-        for proof in proofs {
-            let mut storage = HashMap::new();
-            for storage_proof in proof.storage_proof {
-                storage.insert(storage_proof.key, storage_proof.value);
-            }
-            sdb.set_account(
-                &proof.address,
-                state_db::Account {
-                    nonce: proof.nonce,
-                    balance: proof.balance,
-                    storage,
-                    code_hash: proof.code_hash,
-                },
-            )
-        }
-
-        let mut code_db = CodeDB::new();
-        for (_address, code) in codes {
-            code_db.insert(code.clone());
-        }
-        (sdb, code_db)
+        build_state_code_db(proofs, codes)
     }
 
     /// Step 5. For each step in TxExecTraces, gen the associated ops and state
@@ -545,6 +661,7 @@ impl<P: JsonRpcClient> BuilderClient<P> {
             prev_state_root,
             eth_block,
             self.circuits_params,
+            self.hardfork,
         )?;
         let mut builder = CircuitInputBuilder::new(sdb, code_db, block);
         builder.handle_block(eth_block, geth_traces)?;
@@ -7,9 +7,16 @@ use crate::{
     operation::{OperationContainer, RWCounter},
     Error,
 };
-use eth_types::{evm_unimplemented, Address, Hash, Word};
+use eth_types::{
+    evm_types::HardFork, evm_unimplemented, geth_types::difficulty_or_prevrandao, Address, Hash,
+    Word,
+};
 use std::collections::HashMap;
 
+/// Number of most recent ancestors whose hash is reachable by BLOCKHASH, and
+/// so the maximum length of [`Block::history_hashes`].
+pub const NUM_PREV_BLOCK_ALLOWED: u64 = 256;
+
 /// Context of a [`Block`] which can mutate in a [`Transaction`].
 #[derive(Debug)]
 pub struct BlockContext {
@@ -68,7 +75,7 @@ pub struct Block {
     pub number: Word,
     /// difficulty
     pub timestamp: Word,
-    /// gas limit
+    /// value of DIFFICULTY, i.e. PREVRANDAO after the Merge
     pub difficulty: Word,
     /// base fee
     pub base_fee: Word,
@@ -89,6 +96,8 @@ pub struct Block {
     code: HashMap<Hash, Vec<u8>>,
     /// Circuits Setup Paramteres
     pub circuits_params: CircuitsParams,
+    /// Hard fork whose EVM rules the block is executed with
+    pub hardfork: HardFork,
     /// Original block from geth
     pub eth_block: eth_types::Block<eth_types::Transaction>,
 }
@@ -101,6 +110,7 @@ impl Block {
         prev_state_root: Word,
         eth_block: &eth_types::Block<eth_types::Transaction>,
         circuits_params: CircuitsParams,
+        hardfork: HardFork,
     ) -> Result<Self, Error> {
         if eth_block.base_fee_per_gas.is_none() {
             // FIXME: resolve this once we have proper EIP-1559 support
@@ -109,6 +119,18 @@ impl Block {
             );
         }
 
+        let number = eth_block
+            .number
+            .ok_or(Error::EthTypeError(eth_types::Error::IncompleteBlock))?
+            .low_u64();
+        // Blocks before NUM_PREV_BLOCK_ALLOWED have fewer ancestors, the block
+        // table would otherwise assign hashes to numbers below genesis.
+        if history_hashes.len() as u64 > std::cmp::min(NUM_PREV_BLOCK_ALLOWED, number) {
+            return Err(Error::InternalError(
+                "history hashes exceed the ancestors of the block",
+            ));
+        }
+
         Ok(Self {
             chain_id,
             history_hashes,
@@ -116,13 +138,9 @@ impl Block {
                 .author
                 .ok_or(Error::EthTypeError(eth_types::Error::IncompleteBlock))?,
             gas_limit: eth_block.gas_limit.low_u64(),
-            number: eth_block
-                .number
-                .ok_or(Error::EthTypeError(eth_types::Error::IncompleteBlock))?
-                .low_u64()
-                .into(),
+            number: number.into(),
             timestamp: eth_block.timestamp,
-            difficulty: eth_block.difficulty,
+            difficulty: difficulty_or_prevrandao(eth_block, hardfork),
             base_fee: eth_block.base_fee_per_gas.unwrap_or_default(),
             prev_state_root,
             container: OperationContainer::new(),
@@ -142,6 +160,7 @@ impl Block {
             code: HashMap::new(),
             sha3_inputs: Vec::new(),
             circuits_params,
+            hardfork,
             eth_block: eth_block.clone(),
         })
     }
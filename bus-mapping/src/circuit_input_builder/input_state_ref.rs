@@ -13,12 +13,14 @@ use crate::{
         StackOp, Target, TxAccessListAccountOp, TxLogField, TxLogOp, TxReceiptField, TxReceiptOp,
         RW,
     },
+    precompile,
     state_db::{CodeDB, StateDB},
     Error,
 };
 use eth_types::{
     evm_types::{
         gas_utils::memory_expansion_gas_cost, Gas, GasCost, MemoryAddress, OpcodeId, StackAddress,
+        INVALID_CODE_FIRST_BYTE, MAX_CODE_SIZE,
     },
     evm_unimplemented, Address, GethExecStep, ToAddress, ToBigEndian, ToWord, Word, H256,
 };
@@ -66,6 +68,16 @@ impl<'a> CircuitInputStateRef<'a> {
         }
     }
 
+    /// Create a new InvalidTx step
+    pub fn new_invalid_tx_step(&self) -> ExecStep {
+        ExecStep {
+            exec_state: ExecState::InvalidTx,
+            gas_left: Gas(self.tx.gas),
+            rwc: self.block_ctx.rwc,
+            ..Default::default()
+        }
+    }
+
     /// Create a new EndTx step
     pub fn new_end_tx_step(&self) -> ExecStep {
         let prev_step = self
@@ -627,7 +639,7 @@ impl<'a> CircuitInputStateRef<'a> {
 
     /// Check if address is a precompiled or not.
     pub fn is_precompiled(&self, address: &Address) -> bool {
-        address.0[0..19] == [0u8; 19] && (1..=9).contains(&address.0[19])
+        precompile::is_precompiled(address)
     }
 
     // TODO: Remove unwrap() and add err handling.
@@ -863,8 +875,10 @@ impl<'a> CircuitInputStateRef<'a> {
             if !self.call()?.is_root {
                 let (offset, length) = match step.op {
                     OpcodeId::RETURN | OpcodeId::REVERT => {
-                        let offset = step.stack.nth_last(0)?.as_usize();
-                        let length = step.stack.nth_last(1)?.as_usize();
+                        // A zero length region doesn't access memory, so its offset is
+                        // ignored and can be any word.
+                        let (offset, length) = get_call_memory_offset_length(step, 0)?;
+                        let (offset, length) = (offset as usize, length as usize);
                         // TODO: Try to get rid of clone.
                         // At the moment it conflicts with `call_ctx` and `caller_ctx`.
                         let callee_memory = self.call_ctx()?.memory.clone();
@@ -1043,16 +1057,22 @@ impl<'a> CircuitInputStateRef<'a> {
             return Ok(Some(get_step_reported_error(&step.op, error)));
         }
 
-        if matches!(step.op, OpcodeId::INVALID(_)) {
+        // geth doesn't report an error for undefined opcodes.
+        if !step.op.is_enabled_in(self.block.hardfork) {
             return Ok(Some(ExecError::InvalidOpcode));
         }
 
-        // When last step has opcodes that halt, there's no error.
+        let call = self.call()?;
+
+        // When last step has opcodes that halt, there's no error, unless it's
+        // the RETURN of a failed creation tx, whose returned code couldn't be
+        // deployed.
         if matches!(next_step, None)
             && matches!(
                 step.op,
                 OpcodeId::STOP | OpcodeId::RETURN | OpcodeId::REVERT | OpcodeId::SELFDESTRUCT
             )
+            && !(step.op == OpcodeId::RETURN && call.is_create() && !call.is_success)
         {
             return Ok(None);
         }
@@ -1062,7 +1082,6 @@ impl<'a> CircuitInputStateRef<'a> {
             .map(|s| s.stack.last().unwrap_or_else(|_| Word::zero()))
             .unwrap_or_else(Word::zero);
 
-        let call = self.call()?;
         let call_ctx = self.call_ctx()?;
         // get value first if call/create
         let value = match step.op {
@@ -1105,15 +1124,17 @@ impl<'a> CircuitInputStateRef<'a> {
                     }
                 });
             } else {
-                // Return from a {CREATE, CREATE2} with a failure, via RETURN
-                if !call.is_root && call.is_create() {
+                // Return from a {CREATE, CREATE2} or a creation tx with a
+                // failure, via RETURN
+                if call.is_create() {
                     let offset = step.stack.nth_last(0)?;
                     let length = step.stack.nth_last(1)?;
-                    if length > Word::from(0x6000u64) {
+                    if length > Word::from(MAX_CODE_SIZE) {
                         return Ok(Some(ExecError::MaxCodeSizeExceeded));
                     } else if length > Word::zero()
                         && !call_ctx.memory.is_empty()
-                        && call_ctx.memory.0.get(offset.low_u64() as usize) == Some(&0xef)
+                        && call_ctx.memory.0.get(offset.low_u64() as usize)
+                            == Some(&INVALID_CODE_FIRST_BYTE)
                     {
                         return Ok(Some(ExecError::InvalidCreationCode));
                     } else if Word::from(200u64) * length > Word::from(step.gas.0) {
@@ -1177,6 +1198,13 @@ impl<'a> CircuitInputStateRef<'a> {
                 return Ok(Some(ExecError::InsufficientBalance));
             }
 
+            // Nonce overflow
+            if matches!(step.op, OpcodeId::CREATE | OpcodeId::CREATE2)
+                && account.nonce == Word::from(u64::MAX)
+            {
+                return Ok(Some(ExecError::NonceUintOverflow));
+            }
+
             // Address collision
             if matches!(step.op, OpcodeId::CREATE | OpcodeId::CREATE2) {
                 let address = match step.op {
@@ -7,7 +7,7 @@ use crate::geth_errors::{
 };
 use crate::operation::RWCounter;
 use crate::state_db::Account;
-use eth_types::evm_types::{stack::Stack, Gas, OpcodeId};
+use eth_types::evm_types::{stack::Stack, Gas, HardFork, OpcodeId};
 use eth_types::{
     address, bytecode, geth_types::GethData, word, Bytecode, Hash, ToAddress, ToWord, Word,
 };
@@ -40,6 +40,7 @@ impl CircuitInputBuilderTx {
             &GethExecTrace {
                 gas: Gas(0),
                 failed: false,
+                invalid: false,
                 return_value: "".to_owned(),
                 struct_logs: vec![geth_step.clone()],
             },
@@ -261,6 +262,57 @@ fn tracer_err_insufficient_balance() {
     );
 }
 
+#[test]
+fn tracer_err_nonce_uint_overflow() {
+    // The contract creating another one has its nonce at the maximum, which
+    // can't be increased (EIP-2681).
+    let code_a = bytecode! {
+        PUSH1(0x0) // length
+        PUSH1(0x0) // offset
+        PUSH1(0x0) // value
+        CREATE
+
+        PUSH2(0xaa)
+    };
+
+    // Get the execution steps from the external tracer
+    let block: GethData = TestContext::<2, 1>::new(
+        None,
+        |accs| {
+            accs[0]
+                .address(address!("0x0000000000000000000000000000000000000000"))
+                .nonce(Word::from(u64::MAX))
+                .code(code_a);
+            accs[1]
+                .address(address!("0x000000000000000000000000000000000cafe002"))
+                .balance(Word::from(1u64 << 30));
+        },
+        |mut txs, accs| {
+            txs[0].to(accs[0].address).from(accs[1].address);
+        },
+        |block, _tx| block.number(0xcafeu64),
+    )
+    .unwrap()
+    .into();
+
+    let (index, step) = block.geth_traces[0]
+        .struct_logs
+        .iter()
+        .enumerate()
+        .find(|(_, s)| s.op == OpcodeId::CREATE)
+        .unwrap();
+    let next_step = block.geth_traces[0].struct_logs.get(index + 1);
+    assert_eq!(step.error, None);
+    assert_eq!(next_step.unwrap().op, OpcodeId::PUSH2);
+    assert_eq!(next_step.unwrap().stack, Stack(vec![Word::zero()])); // failure = 0
+
+    let mut builder = CircuitInputBuilderTx::new(&block, step);
+    assert_eq!(
+        builder.state_ref().get_step_err(step, next_step).unwrap(),
+        Some(ExecError::NonceUintOverflow)
+    );
+}
+
 #[test]
 fn tracer_call_success() {
     let code_a = bytecode! {
@@ -1317,6 +1369,35 @@ fn tracer_err_invalid_opcode() {
     );
 }
 
+#[test]
+fn tracer_err_invalid_opcode_basefee_pre_london() {
+    let code = bytecode! {
+        BASEFEE
+        STOP
+    };
+    let block: GethData = TestContext::<2, 1>::new_with_hardfork(
+        None,
+        account_0_code_account_1_no_code(code),
+        tx_from_1_to_0,
+        |block, _tx| block.number(0xcafeu64),
+        HardFork::Berlin,
+    )
+    .unwrap()
+    .into();
+
+    // The execution halts at BASEFEE
+    assert_eq!(block.geth_traces[0].struct_logs.len(), 1);
+    let step = &block.geth_traces[0].struct_logs[0];
+    let next_step = block.geth_traces[0].struct_logs.get(1);
+    assert_eq!(step.op, OpcodeId::BASEFEE);
+
+    let mut builder = CircuitInputBuilderTx::new(&block, step);
+    assert_eq!(
+        builder.state_ref().get_step_err(step, next_step).unwrap(),
+        Some(ExecError::InvalidOpcode)
+    );
+}
+
 #[test]
 fn test_tracer_err_write_protection() {
     // test write_protection error happens in sstore
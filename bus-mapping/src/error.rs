@@ -1,7 +1,10 @@
 //! Error module for the bus-mapping crate
 
 use core::fmt::{Display, Formatter, Result as FmtResult};
-use eth_types::{evm_types::OpcodeId, Address, GethExecStep, Word, H256};
+use eth_types::{
+    evm_types::{HardFork, OpcodeId},
+    Address, GethExecStep, Word, H256,
+};
 use ethers_providers::ProviderError;
 use std::error::Error as StdError;
 
@@ -40,6 +43,16 @@ pub enum Error {
     ExecutionError(ExecError),
     /// Internal Code error
     InternalError(&'static str),
+    /// Opcodes in the trace that the EVM circuit has no gadget for
+    UnsupportedOpcodes(Vec<OpcodeId>),
+    /// Precompiled contracts called in the trace that the EVM circuit can't
+    /// prove
+    UnsupportedPrecompiles(Vec<Address>),
+    /// Hard fork whose EVM rules the EVM circuit doesn't constrain
+    UnsupportedHardFork(HardFork),
+    /// Stack operation inconsistent with the stack of the step it belongs to,
+    /// with the rw counter of the operation.
+    InconsistentStackOp(&'static str, usize),
 }
 
 impl From<eth_types::Error> for Error {
@@ -128,6 +141,9 @@ pub enum ExecError {
     InsufficientBalance,
     /// For CREATE, CREATE2
     ContractAddressCollision,
+    /// For CREATE, CREATE2 when the caller's nonce can't be increased
+    /// (EIP-2681)
+    NonceUintOverflow,
     /// contract must not begin with 0xef due to EIP #3541 EVM Object Format
     /// (EOF)
     InvalidCreationCode,
@@ -6,6 +6,7 @@ use crate::{
     operation::{
         AccountField, CallContextField, TxAccessListAccountOp, TxReceiptField, TxRefundOp, RW,
     },
+    precompile::PrecompileCall,
     Error,
 };
 use core::fmt::Debug;
@@ -54,6 +55,9 @@ mod swap;
 
 mod error_invalid_jump;
 mod error_oog_call;
+mod error_oog_static_memory;
+mod error_return_data_outofbound;
+mod error_write_protection;
 
 #[cfg(test)]
 mod memory_expansion_test;
@@ -73,6 +77,9 @@ use create::DummyCreate;
 use dup::Dup;
 use error_invalid_jump::ErrorInvalidJump;
 use error_oog_call::OOGCall;
+use error_oog_static_memory::ErrorOOGStaticMemory;
+use error_return_data_outofbound::ErrorReturnDataOutOfBound;
+use error_write_protection::ErrorWriteProtection;
 use exp::Exponentiation;
 use extcodecopy::Extcodecopy;
 use extcodehash::Extcodehash;
@@ -257,8 +264,13 @@ fn fn_gen_error_state_associated_ops(error: &ExecError) -> Option<FnGenAssociate
     match error {
         ExecError::InvalidJump => Some(ErrorInvalidJump::gen_associated_ops),
         ExecError::OutOfGas(OogError::Call) => Some(OOGCall::gen_associated_ops),
+        ExecError::OutOfGas(OogError::StaticMemoryExpansion) => {
+            Some(ErrorOOGStaticMemory::gen_associated_ops)
+        }
         // call & callcode can encounter InsufficientBalance error, Use pop-7 generic CallOpcode
         ExecError::InsufficientBalance => Some(CallOpcode::<7>::gen_associated_ops),
+        ExecError::ReturnDataOutOfBounds => Some(ErrorReturnDataOutOfBound::gen_associated_ops),
+        ExecError::WriteProtection => Some(ErrorWriteProtection::gen_associated_ops),
         // more future errors place here
         _ => {
             evm_unimplemented!("TODO: error state {:?} not implemented", error);
@@ -438,9 +450,30 @@ pub fn gen_begin_tx_ops(state: &mut CircuitInputStateRef) -> Result<ExecStep, Er
             }
             Ok(exec_step)
         }
-        // 2. Call to precompiled.
+        // 2. Call to precompiled, which runs without entering a call frame,
+        // like a call to an account with empty code.
         (_, true, _) => {
-            evm_unimplemented!("Call to precompiled is left unimplemented");
+            state.account_read(
+                &mut exec_step,
+                call.address,
+                AccountField::CodeHash,
+                callee_code_hash_word,
+                callee_code_hash_word,
+            )?;
+
+            match PrecompileCall::from_address(&call.address) {
+                Some(precompile) if call.is_success => {
+                    exec_step.gas_cost =
+                        GasCost(intrinsic_gas_cost + precompile.gas_cost(&state.tx.input));
+                }
+                _ => {
+                    evm_unimplemented!(
+                        "Failed tx to precompiled {:?} is left unimplemented",
+                        call.address
+                    );
+                    exec_step.gas_cost = GasCost(state.tx.gas);
+                }
+            }
             Ok(exec_step)
         }
         (_, _, is_empty_code_hash) => {
@@ -597,6 +630,74 @@ pub fn gen_end_tx_ops(state: &mut CircuitInputStateRef) -> Result<ExecStep, Erro
     Ok(exec_step)
 }
 
+/// Generate the [`ExecStep`] of a tx which is rejected before execution
+/// (nonce mismatch, insufficient balance for the up-front cost or gas limit
+/// below the intrinsic gas). Such tx only reads the caller's account to prove
+/// the rejection reason, charges nothing and leaves an empty receipt.
+pub fn gen_invalid_tx_ops(state: &mut CircuitInputStateRef) -> Result<ExecStep, Error> {
+    let mut exec_step = state.new_invalid_tx_step();
+    let call = state.call()?.clone();
+
+    state.call_context_write(
+        &mut exec_step,
+        call.call_id,
+        CallContextField::TxId,
+        state.tx_ctx.id().into(),
+    );
+
+    let (found, caller_account) = state.sdb.get_account(&call.caller_address);
+    if !found {
+        return Err(Error::AccountNotFound(call.caller_address));
+    }
+    let (nonce, balance) = (caller_account.nonce, caller_account.balance);
+    state.account_read(
+        &mut exec_step,
+        call.caller_address,
+        AccountField::Nonce,
+        nonce,
+        nonce,
+    )?;
+    state.account_read(
+        &mut exec_step,
+        call.caller_address,
+        AccountField::Balance,
+        balance,
+        balance,
+    )?;
+
+    for (field, value) in [
+        (TxReceiptField::PostStateOrStatus, 0),
+        (TxReceiptField::LogLength, 0),
+    ] {
+        state.tx_receipt_write(&mut exec_step, state.tx_ctx.id(), field, value)?;
+    }
+    if state.tx_ctx.id() > 1 {
+        state.tx_receipt_read(
+            &mut exec_step,
+            state.tx_ctx.id() - 1,
+            TxReceiptField::CumulativeGasUsed,
+            state.block_ctx.cumulative_gas_used,
+        )?;
+    }
+    state.tx_receipt_write(
+        &mut exec_step,
+        state.tx_ctx.id(),
+        TxReceiptField::CumulativeGasUsed,
+        state.block_ctx.cumulative_gas_used,
+    )?;
+
+    if !state.tx_ctx.is_last_tx() {
+        state.call_context_write(
+            &mut exec_step,
+            state.block_ctx.rwc.0 + 1,
+            CallContextField::TxId,
+            (state.tx_ctx.id() + 1).into(),
+        );
+    }
+
+    Ok(exec_step)
+}
+
 #[derive(Debug, Copy, Clone)]
 struct DummySelfDestruct;
 
@@ -1,6 +1,9 @@
 use super::Opcode;
-use crate::circuit_input_builder::{CallKind, CircuitInputStateRef, CodeSource, ExecStep};
-use crate::operation::{AccountField, CallContextField, TxAccessListAccountOp, RW};
+use crate::circuit_input_builder::{
+    CallKind, CircuitInputStateRef, CodeSource, CopyDataType, CopyEvent, ExecStep, NumberOrHash,
+};
+use crate::operation::{AccountField, CallContextField, MemoryOp, TxAccessListAccountOp, RW};
+use crate::precompile::PrecompileCall;
 use crate::Error;
 use eth_types::evm_types::gas_utils::{eip150_gas, memory_expansion_gas_cost};
 use eth_types::evm_types::GasCost;
@@ -207,12 +210,85 @@ impl<const N_ARGS: usize> Opcode for CallOpcode<N_ARGS> {
         // add failure case for insufficient balance or error depth in the future.
         match (
             insufficient_balance,
-            state.is_precompiled(&call.address),
+            state.is_precompiled(&callee_address),
             is_empty_code_hash,
         ) {
-            // 1. Call to precompiled.
+            // 1. Call to precompiled, which runs without entering a new call
+            // frame.
             (false, true, _) => {
-                evm_unimplemented!("Call to precompiled is left unimplemented");
+                let precompile = PrecompileCall::from_address(&callee_address);
+                if precompile.is_none() {
+                    evm_unimplemented!(
+                        "Call to precompiled {:?} is left unimplemented",
+                        callee_address
+                    );
+                }
+                let input = state.call_ctx()?.call_data.clone();
+                let output = if call.is_success {
+                    precompile
+                        .and_then(|precompile| precompile.execute(&input))
+                        .unwrap_or_default()
+                } else {
+                    vec![]
+                };
+
+                // The output is the return data of the call, which is read
+                // from the memory of the precompiled call frame.
+                for (field, value) in [
+                    (CallContextField::LastCalleeId, call.call_id.into()),
+                    (CallContextField::LastCalleeReturnDataOffset, 0.into()),
+                    (
+                        CallContextField::LastCalleeReturnDataLength,
+                        output.len().into(),
+                    ),
+                ] {
+                    state.call_context_write(&mut exec_step, current_call.call_id, field, value);
+                }
+
+                // The identity precompiled copies its input from the caller
+                // memory to its own memory, then the output is copied to the
+                // return data region of the caller. The other precompiled are
+                // not proven by the circuit, so their output is written right
+                // away.
+                if !output.is_empty() {
+                    if precompile == Some(PrecompileCall::Identity) {
+                        let copy_event = gen_copy_event(
+                            state,
+                            &mut exec_step,
+                            (current_call.call_id, args_offset),
+                            (call.call_id, 0),
+                            &output,
+                        );
+                        state.push_copy(copy_event);
+                    } else {
+                        for (idx, byte) in output.iter().enumerate() {
+                            state.push_op(
+                                &mut exec_step,
+                                RW::WRITE,
+                                MemoryOp::new(call.call_id, idx.into(), *byte),
+                            );
+                        }
+                    }
+                }
+                let length = output.len().min(ret_length);
+                if length != 0 {
+                    let copy_event = gen_copy_event(
+                        state,
+                        &mut exec_step,
+                        (call.call_id, 0),
+                        (current_call.call_id, ret_offset),
+                        &output[..length],
+                    );
+                    state.push_copy(copy_event);
+                }
+                state.handle_return(geth_step)?;
+
+                if length != 0 {
+                    state.call_ctx_mut()?.memory.0[ret_offset..ret_offset + length]
+                        .copy_from_slice(&output[..length]);
+                }
+                state.call_mut()?.last_callee_return_data_length = output.len() as u64;
+                state.call_ctx_mut()?.return_data = output;
                 Ok(vec![exec_step])
             }
             // 2. Call to account with empty code.
@@ -307,3 +383,41 @@ impl<const N_ARGS: usize> Opcode for CallOpcode<N_ARGS> {
         }
     }
 }
+
+/// Generates the memory reads and writes of the copy of `bytes` from the
+/// memory of the call `src` to the memory of the call `dst`, both given as a
+/// call id and an address.
+fn gen_copy_event(
+    state: &mut CircuitInputStateRef,
+    exec_step: &mut ExecStep,
+    src: (usize, usize),
+    dst: (usize, usize),
+    bytes: &[u8],
+) -> CopyEvent {
+    let rw_counter_start = state.block_ctx.rwc;
+    for (idx, byte) in bytes.iter().enumerate() {
+        state.push_op(
+            exec_step,
+            RW::READ,
+            MemoryOp::new(src.0, (src.1 + idx).into(), *byte),
+        );
+        state.push_op(
+            exec_step,
+            RW::WRITE,
+            MemoryOp::new(dst.0, (dst.1 + idx).into(), *byte),
+        );
+    }
+
+    CopyEvent {
+        src_type: CopyDataType::Memory,
+        src_id: NumberOrHash::Number(src.0),
+        src_addr: src.1 as u64,
+        src_addr_end: (src.1 + bytes.len()) as u64,
+        dst_type: CopyDataType::Memory,
+        dst_id: NumberOrHash::Number(dst.0),
+        dst_addr: dst.1 as u64,
+        log_id: None,
+        rw_counter_start,
+        bytes: bytes.iter().map(|byte| (*byte, false)).collect(),
+    }
+}
@@ -0,0 +1,42 @@
+use crate::circuit_input_builder::{CircuitInputStateRef, ExecStep};
+use crate::evm::{Opcode, OpcodeId};
+use crate::Error;
+use eth_types::GethExecStep;
+
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct ErrorOOGStaticMemory;
+
+impl Opcode for ErrorOOGStaticMemory {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let geth_step = &geth_steps[0];
+        let mut exec_step = state.new_step(geth_step)?;
+        let next_step = if geth_steps.len() > 1 {
+            Some(&geth_steps[1])
+        } else {
+            None
+        };
+        exec_step.error = state.get_step_err(geth_step, next_step).unwrap();
+        // assert op code can only be MLOAD, MSTORE or MSTORE8
+        assert!(matches!(
+            geth_step.op,
+            OpcodeId::MLOAD | OpcodeId::MSTORE | OpcodeId::MSTORE8
+        ));
+
+        // Only the offset is needed to compute the memory expansion, which
+        // can be arbitrarily large and overflow the gas.
+        state.stack_read(
+            &mut exec_step,
+            geth_step.stack.last_filled(),
+            geth_step.stack.last()?,
+        )?;
+
+        // `IsSuccess` call context operation is added in gen_restore_context_ops
+
+        state.gen_restore_context_ops(&mut exec_step, geth_steps)?;
+        state.handle_return(geth_step)?;
+        Ok(vec![exec_step])
+    }
+}
@@ -1,5 +1,7 @@
 use super::Opcode;
-use crate::circuit_input_builder::{CopyDataType, CopyEvent, NumberOrHash};
+use crate::circuit_input_builder::{
+    get_call_memory_offset_length, CopyDataType, CopyEvent, NumberOrHash,
+};
 use crate::operation::AccountOp;
 use crate::operation::MemoryOp;
 use crate::{
@@ -44,8 +46,10 @@ impl Opcode for ReturnRevert {
             call.is_success.to_word(),
         );
 
-        let offset = offset.as_usize();
-        let length = length.as_usize();
+        // A zero length region doesn't access memory, so its offset is ignored
+        // and can be any word.
+        let (offset, length) = get_call_memory_offset_length(step, 0)?;
+        let (offset, length) = (offset as usize, length as usize);
 
         // Case A in the spec.
         if call.is_create() && call.is_success && length > 0 {
@@ -141,6 +141,7 @@
 //!     return_value: "".to_string(),
 //!     gas: Gas(block.eth_block.transactions[0].gas.as_u64()),
 //!     failed: false,
+//!     invalid: false,
 //!     struct_logs: geth_steps,
 //! };
 //!
@@ -234,6 +235,7 @@ pub mod exec_trace;
 pub(crate) mod geth_errors;
 pub mod mock;
 pub mod operation;
+pub mod precompile;
 pub mod rpc;
 pub mod state_db;
 pub use error::Error;
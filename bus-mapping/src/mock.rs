@@ -4,7 +4,19 @@ use crate::{
     circuit_input_builder::{Block, CircuitInputBuilder, CircuitsParams},
     state_db::{self, CodeDB, StateDB},
 };
-use eth_types::{geth_types::GethData, Word};
+use eth_types::{evm_types::HardFork, geth_types::GethData, Word};
+
+#[cfg(feature = "test")]
+mod geth_client;
+#[cfg(feature = "test")]
+pub use geth_client::{MemoryGethClient, MemoryGethClientError};
+
+/// Response of geth to `debug_traceBlockByNumber` for the block of a single
+/// transfer from an account without code to another one, as built by the
+/// `TestContext` with `account_0_code_account_1_no_code(bytecode! {})` and
+/// `tx_from_1_to_0`.
+#[cfg(feature = "test")]
+pub const TRANSFER_TRACE_JSON: &str = include_str!("mock/testdata/transfer_trace.json");
 
 /// BlockData is a type that contains all the information from a block required
 /// to build the circuit inputs.
@@ -25,6 +37,8 @@ pub struct BlockData {
     pub geth_traces: Vec<eth_types::GethExecTrace>,
     /// Circuits setup parameters
     pub circuits_params: CircuitsParams,
+    /// Hard fork whose EVM rules the block is executed with
+    pub hardfork: HardFork,
 }
 
 impl BlockData {
@@ -40,6 +54,7 @@ impl BlockData {
                 Word::default(),
                 &self.eth_block,
                 self.circuits_params,
+                self.hardfork,
             )
             .unwrap(),
         )
@@ -84,6 +99,7 @@ impl BlockData {
             eth_block: geth_data.eth_block,
             geth_traces: geth_data.geth_traces,
             circuits_params,
+            hardfork: geth_data.hardfork,
         }
     }
 
@@ -0,0 +1,276 @@
+//! In-memory replacement of a geth node, answering the JSON-RPC calls done by
+//! the [`BuilderClient`](crate::circuit_input_builder::BuilderClient) from
+//! fixture data.
+
+use async_trait::async_trait;
+use core::fmt::{Debug, Display, Formatter, Result as FmtResult};
+use eth_types::{geth_types::Account, Address, Block, Hash, Transaction, Word, H256, U64};
+use ethers_core::utils::keccak256;
+use ethers_providers::{JsonRpcClient, ProviderError};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{json, Value};
+use std::{collections::HashMap, error::Error as StdError};
+
+/// Error returned by the [`MemoryGethClient`].
+#[derive(Debug)]
+pub enum MemoryGethClientError {
+    /// Serde de/serialization error.
+    SerdeError(serde_json::Error),
+    /// The requested method is not served by the client.
+    UnsupportedMethod(String),
+    /// The requested block or traces are missing from the fixture data.
+    NotFound(String),
+}
+
+impl Display for MemoryGethClientError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl StdError for MemoryGethClientError {}
+
+impl From<serde_json::Error> for MemoryGethClientError {
+    fn from(err: serde_json::Error) -> Self {
+        MemoryGethClientError::SerdeError(err)
+    }
+}
+
+impl From<MemoryGethClientError> for ProviderError {
+    fn from(err: MemoryGethClientError) -> Self {
+        ProviderError::JsonRpcClientError(Box::new(err))
+    }
+}
+
+/// [`JsonRpcClient`] backed by in-memory blocks, traces and accounts, to be
+/// wrapped in a [`GethClient`](crate::rpc::GethClient) in tests that need the
+/// whole input generation pipeline but no running node.
+///
+/// Only the methods used by the `BuilderClient` are served. Accounts that
+/// were not inserted are treated as empty, and every state query is answered
+/// with the inserted state regardless of the queried block.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryGethClient {
+    chain_id: Word,
+    blocks: Vec<Block<Transaction>>,
+    traces: HashMap<U64, Value>,
+    accounts: HashMap<Address, Account>,
+}
+
+impl MemoryGethClient {
+    /// Create a new empty client for the chain `chain_id`.
+    pub fn new(chain_id: Word) -> Self {
+        Self {
+            chain_id,
+            ..Default::default()
+        }
+    }
+
+    /// Insert a block, served by both hash and number.
+    pub fn insert_block(&mut self, block: Block<Transaction>) {
+        self.blocks.push(block);
+    }
+
+    /// Insert the raw JSON response of `debug_traceBlockByNumber` for the
+    /// block `block_num`.
+    pub fn insert_traces(&mut self, block_num: u64, json: &str) -> Result<(), serde_json::Error> {
+        self.traces
+            .insert(U64::from(block_num), serde_json::from_str(json)?);
+        Ok(())
+    }
+
+    /// Insert an account of the state the block is executed on.
+    pub fn insert_account(&mut self, account: Account) {
+        self.accounts.insert(account.address, account);
+    }
+
+    fn block_by(
+        &self,
+        pred: impl Fn(&Block<Transaction>) -> bool,
+        desc: String,
+    ) -> Result<Value, MemoryGethClientError> {
+        let block = self
+            .blocks
+            .iter()
+            .find(|block| pred(block))
+            .ok_or(MemoryGethClientError::NotFound(desc))?;
+        Ok(serde_json::to_value(block)?)
+    }
+
+    fn proof(&self, address: Address, keys: Vec<Word>) -> Value {
+        let account = self.accounts.get(&address).cloned().unwrap_or(Account {
+            address,
+            ..Default::default()
+        });
+        let storage_proof: Vec<Value> = keys
+            .iter()
+            .map(|key| {
+                json!({
+                    "key": key,
+                    "value": account.storage.get(key).cloned().unwrap_or_default(),
+                    "proof": [],
+                })
+            })
+            .collect();
+        json!({
+            "address": address,
+            "balance": account.balance,
+            "codeHash": H256(keccak256(&account.code)),
+            "nonce": account.nonce,
+            "storageHash": H256::zero(),
+            "accountProof": [],
+            "storageProof": storage_proof,
+        })
+    }
+
+    fn response(&self, method: &str, params: Value) -> Result<Value, MemoryGethClientError> {
+        let param = |idx: usize| params.get(idx).cloned().unwrap_or_default();
+        match method {
+            "eth_chainId" => Ok(json!(U64::from(self.chain_id.low_u64()))),
+            "eth_getBlockByHash" => {
+                let hash: Hash = serde_json::from_value(param(0))?;
+                self.block_by(
+                    |block| block.hash == Some(hash),
+                    format!("block {:?}", hash),
+                )
+            }
+            "eth_getBlockByNumber" => {
+                let num = block_number(param(0))?;
+                self.block_by(|block| block.number == Some(num), format!("block {}", num))
+            }
+            "debug_traceBlockByNumber" => {
+                let num = block_number(param(0))?;
+                self.traces
+                    .get(&num)
+                    .cloned()
+                    .ok_or_else(|| MemoryGethClientError::NotFound(format!("traces {}", num)))
+            }
+            "eth_getProof" => {
+                let address: Address = serde_json::from_value(param(0))?;
+                let keys: Vec<Word> = serde_json::from_value(param(1))?;
+                Ok(self.proof(address, keys))
+            }
+            "eth_getCode" => {
+                let address: Address = serde_json::from_value(param(0))?;
+                let code = self
+                    .accounts
+                    .get(&address)
+                    .map(|account| account.code.clone())
+                    .unwrap_or_default();
+                Ok(json!(code))
+            }
+            _ => Err(MemoryGethClientError::UnsupportedMethod(method.to_string())),
+        }
+    }
+}
+
+/// Parse a block number param, only numbers are supported and not tags like
+/// `latest`.
+fn block_number(value: Value) -> Result<U64, MemoryGethClientError> {
+    Ok(serde_json::from_value(value)?)
+}
+
+#[async_trait]
+impl JsonRpcClient for MemoryGethClient {
+    type Error = MemoryGethClientError;
+
+    async fn request<T, R>(&self, method: &str, params: T) -> Result<R, Self::Error>
+    where
+        T: Debug + Serialize + Send + Sync,
+        R: DeserializeOwned,
+    {
+        let response = self.response(method, serde_json::to_value(params)?)?;
+        Ok(serde_json::from_value(response)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        circuit_input_builder::{BuilderClient, CircuitsParams, ExecState},
+        mock::{BlockData, TRANSFER_TRACE_JSON},
+        rpc::GethClient,
+    };
+    use eth_types::{bytecode, evm_types::HardFork, geth_types::GethData};
+    use mock::test_ctx::{
+        helpers::{account_0_code_account_1_no_code, tx_from_1_to_0},
+        TestContext,
+    };
+
+    #[tokio::test]
+    async fn memory_geth_client_transfer_block() {
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(bytecode! {}),
+            tx_from_1_to_0,
+            |block, _tx| block.number(1),
+        )
+        .unwrap()
+        .into();
+
+        let mut client = MemoryGethClient::new(block.chain_id);
+        client.insert_block(Block {
+            hash: Some(block.eth_block.parent_hash),
+            number: Some(U64::zero()),
+            ..Default::default()
+        });
+        client.insert_block(block.eth_block.clone());
+        client.insert_traces(1, TRANSFER_TRACE_JSON).unwrap();
+        for account in block.accounts.iter() {
+            client.insert_account(account.clone());
+        }
+
+        let cli = BuilderClient::new(
+            GethClient::new(client),
+            CircuitsParams::default(),
+            HardFork::default(),
+        )
+        .await
+        .unwrap();
+
+        // The state is queried for the accounts the transfer accesses: the
+        // coinbase, which wasn't inserted and is served empty, and both
+        // parties of the transfer, served with their inserted state.
+        let (eth_block, geth_traces, _, _) = cli.get_block(1).await.unwrap();
+        let access_set = cli.get_state_accesses(&eth_block, &geth_traces).unwrap();
+        let mut accessed: Vec<_> = access_set.state.keys().cloned().collect();
+        accessed.sort();
+        let mut expected_accessed = vec![
+            eth_block.author.unwrap(),
+            block.accounts[0].address,
+            block.accounts[1].address,
+        ];
+        expected_accessed.sort();
+        assert_eq!(accessed, expected_accessed);
+
+        let (proofs, _) = cli.get_state(1, access_set).await.unwrap();
+        assert_eq!(proofs.len(), 3);
+        for proof in proofs {
+            let account = block
+                .accounts
+                .iter()
+                .find(|account| account.address == proof.address)
+                .cloned()
+                .unwrap_or_default();
+            assert_eq!(proof.balance, account.balance);
+            assert_eq!(proof.nonce, account.nonce);
+            assert_eq!(proof.code_hash, H256(keccak256(&account.code)));
+        }
+
+        let (builder, _) = cli.gen_inputs(1).await.unwrap();
+
+        let mut expected = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        expected
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let tx_states: Vec<_> = builder.block.txs()[0]
+            .steps()
+            .iter()
+            .map(|step| step.exec_state.clone())
+            .collect();
+        assert_eq!(tx_states, vec![ExecState::BeginTx, ExecState::EndTx]);
+        assert_eq!(builder.block.container, expected.block.container);
+    }
+}
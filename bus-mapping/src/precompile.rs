@@ -0,0 +1,494 @@
+//! Execution of the precompiled contracts known to the circuit input builder.
+//!
+//! Geth doesn't trace the execution of precompiled contracts, so their output
+//! is computed here to fill the return data region of the caller, as well as
+//! their gas cost for the txs sent to them. The EVM circuit only proves the
+//! calls to the identity precompiled, the other ones are run here to keep the
+//! caller memory in sync with geth, and `block_convert` rejects the blocks
+//! calling them.
+
+use eth_types::{
+    evm_types::{
+        GasCost, PRECOMPILE_BLAKE2F, PRECOMPILE_BN256_ADD, PRECOMPILE_BN256_PAIRING,
+        PRECOMPILE_BN256_SCALAR_MUL, PRECOMPILE_COUNT, PRECOMPILE_IDENTITY,
+    },
+    Address,
+};
+use halo2_proofs::{
+    arithmetic::{CurveAffine, FieldExt},
+    halo2curves::{
+        bn256::{Bn256, Fq, Fq2, Fr, G1Affine, G2Affine, G2Prepared},
+        group::{prime::PrimeCurveAffine, Curve, Group},
+        pairing::{MillerLoopResult, MultiMillerLoop},
+        Coordinates,
+    },
+};
+
+/// Returns whether `address` is the address of a precompiled contract.
+pub fn is_precompiled(address: &Address) -> bool {
+    address.0[0..19] == [0u8; 19] && (1..=PRECOMPILE_COUNT as u8).contains(&address.0[19])
+}
+
+/// Precompiled contracts known to the circuit input builder.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PrecompileCall {
+    /// Identity, which returns its input.
+    Identity,
+    /// ecAdd, the addition of two BN254 points. Not proven by the EVM
+    /// circuit.
+    Bn256Add,
+    /// ecMul, the multiplication of a BN254 point by a scalar. Not proven by
+    /// the EVM circuit.
+    Bn256ScalarMul,
+    /// ecPairing, the check of a product of BN254 pairings. Not proven by the
+    /// EVM circuit.
+    Bn256Pairing,
+    /// Blake2F, the compression function F of BLAKE2b (EIP-152). Not proven
+    /// by the EVM circuit.
+    Blake2F,
+}
+
+impl PrecompileCall {
+    /// Returns the supported precompiled contract deployed at `address`, if
+    /// any.
+    pub fn from_address(address: &Address) -> Option<Self> {
+        if address.0[0..19] != [0u8; 19] {
+            return None;
+        }
+        match address.0[19] as u64 {
+            PRECOMPILE_IDENTITY => Some(Self::Identity),
+            PRECOMPILE_BN256_ADD => Some(Self::Bn256Add),
+            PRECOMPILE_BN256_SCALAR_MUL => Some(Self::Bn256ScalarMul),
+            PRECOMPILE_BN256_PAIRING => Some(Self::Bn256Pairing),
+            PRECOMPILE_BLAKE2F => Some(Self::Blake2F),
+            _ => None,
+        }
+    }
+
+    /// Runs the precompiled contract on `input`. Returns `None` when the input
+    /// is malformed, in which case the call fails with an empty output.
+    pub fn execute(&self, input: &[u8]) -> Option<Vec<u8>> {
+        match self {
+            Self::Identity => Some(input.to_vec()),
+            Self::Bn256Add => bn256_add(input).map(|output| output.to_vec()),
+            Self::Bn256ScalarMul => bn256_scalar_mul(input).map(|output| output.to_vec()),
+            Self::Bn256Pairing => bn256_pairing(input).map(|output| output.to_vec()),
+            Self::Blake2F => blake2f(input).map(|output| output.to_vec()),
+        }
+    }
+
+    /// Returns the gas cost of running the precompiled contract on `input`.
+    pub fn gas_cost(&self, input: &[u8]) -> u64 {
+        match self {
+            Self::Identity => {
+                GasCost::PRECOMPILE_IDENTITY_BASE.as_u64()
+                    + (input.len() as u64 + 31) / 32
+                        * GasCost::PRECOMPILE_IDENTITY_PER_WORD.as_u64()
+            }
+            Self::Bn256Add => GasCost::PRECOMPILE_BN256_ADD.as_u64(),
+            Self::Bn256ScalarMul => GasCost::PRECOMPILE_BN256_SCALAR_MUL.as_u64(),
+            Self::Bn256Pairing => {
+                GasCost::PRECOMPILE_BN256_PAIRING_BASE.as_u64()
+                    + (input.len() / BN256_PAIRING_PAIR_LENGTH) as u64
+                        * GasCost::PRECOMPILE_BN256_PAIRING_PER_PAIR.as_u64()
+            }
+            Self::Blake2F if input.len() == BLAKE2F_INPUT_LENGTH => {
+                blake2f_rounds(input).unwrap() as u64
+                    * GasCost::PRECOMPILE_BLAKE2F_PER_ROUND.as_u64()
+            }
+            Self::Blake2F => 0,
+        }
+    }
+}
+
+/// Returns `input` truncated or right padded with zeros to `length` bytes, as
+/// the precompiled contracts with a fixed size input read it.
+fn padded_input(input: &[u8], length: usize) -> Vec<u8> {
+    let mut input = input[..input.len().min(length)].to_vec();
+    input.resize(length, 0);
+    input
+}
+
+/// Decodes a BN254 point from its 64 bytes big-endian coordinates, where
+/// `(0, 0)` is the point at infinity. Returns `None` when a coordinate is not
+/// in the base field or the point is not on the curve.
+fn bn256_point(bytes: &[u8]) -> Option<G1Affine> {
+    if bytes.iter().all(|byte| *byte == 0) {
+        return Some(G1Affine::identity());
+    }
+    let coordinate = |bytes: &[u8]| {
+        let mut bytes: [u8; 32] = bytes.try_into().unwrap();
+        bytes.reverse();
+        Option::<Fq>::from(Fq::from_bytes(&bytes))
+    };
+    let (x, y) = (coordinate(&bytes[..32])?, coordinate(&bytes[32..64])?);
+    Option::from(G1Affine::from_xy(x, y))
+}
+
+/// Encodes a BN254 point into its 64 bytes big-endian coordinates.
+fn bn256_point_bytes(point: G1Affine) -> [u8; 64] {
+    let mut bytes = [0u8; 64];
+    if let Some(coordinates) = Option::<Coordinates<_>>::from(point.coordinates()) {
+        for (chunk, coordinate) in bytes
+            .chunks_exact_mut(32)
+            .zip([coordinates.x(), coordinates.y()])
+        {
+            chunk.copy_from_slice(&coordinate.to_bytes());
+            chunk.reverse();
+        }
+    }
+    bytes
+}
+
+/// Addition of the two BN254 points of the 128 bytes input, as specified in
+/// EIP-196. Returns `None` when a point is invalid.
+pub fn bn256_add(input: &[u8]) -> Option<[u8; 64]> {
+    let input = padded_input(input, 128);
+    let (a, b) = (bn256_point(&input[..64])?, bn256_point(&input[64..])?);
+    Some(bn256_point_bytes((a.to_curve() + b).to_affine()))
+}
+
+/// Multiplication of the BN254 point of the 96 bytes input by the scalar that
+/// follows it, as specified in EIP-196. Returns `None` when the point is
+/// invalid.
+pub fn bn256_scalar_mul(input: &[u8]) -> Option<[u8; 64]> {
+    let input = padded_input(input, 96);
+    let point = bn256_point(&input[..64])?;
+    // The scalar is reduced modulo the group order, which doesn't change the
+    // result.
+    let mut scalar = [0u8; 64];
+    scalar[..32].copy_from_slice(&input[64..]);
+    scalar[..32].reverse();
+    Some(bn256_point_bytes(
+        (point * Fr::from_bytes_wide(&scalar)).to_affine(),
+    ))
+}
+
+/// Length of a pair of points in the input of the ecPairing precompiled
+/// contract.
+pub const BN256_PAIRING_PAIR_LENGTH: usize = 192;
+
+/// Decodes a point of the BN254 twist from its 128 bytes big-endian
+/// coordinates, each one encoded as its imaginary part followed by its real
+/// part. Returns `None` when a coordinate is not in the base field or the
+/// point is not in the subgroup of the curve.
+fn bn256_g2_point(bytes: &[u8]) -> Option<G2Affine> {
+    if bytes.iter().all(|byte| *byte == 0) {
+        return Some(G2Affine::identity());
+    }
+    let coordinate = |bytes: &[u8]| {
+        let mut c1: [u8; 32] = bytes[..32].try_into().unwrap();
+        let mut c0: [u8; 32] = bytes[32..64].try_into().unwrap();
+        c1.reverse();
+        c0.reverse();
+        Some(Fq2 {
+            c0: Option::from(Fq::from_bytes(&c0))?,
+            c1: Option::from(Fq::from_bytes(&c1))?,
+        })
+    };
+    let (x, y) = (coordinate(&bytes[..64])?, coordinate(&bytes[64..])?);
+    let point = Option::<G2Affine>::from(G2Affine::from_xy(x, y))?;
+    // Unlike the curve of the first group, the twist has points out of the
+    // subgroup of order r, for which r * point isn't the identity.
+    let is_in_subgroup: bool = (point * -Fr::one() + point).is_identity().into();
+    is_in_subgroup.then_some(point)
+}
+
+/// Pairing check of the input made of 192 bytes pairs of a BN254 point and a
+/// point of its twist, as specified in EIP-197. Returns a word set to `1` when
+/// the product of the pairings is the identity and `0` otherwise, or `None`
+/// when the input length is not a multiple of 192 bytes or a point is
+/// invalid. An empty input passes the check.
+pub fn bn256_pairing(input: &[u8]) -> Option<[u8; 32]> {
+    if input.len() % BN256_PAIRING_PAIR_LENGTH != 0 {
+        return None;
+    }
+    let pairs = input
+        .chunks_exact(BN256_PAIRING_PAIR_LENGTH)
+        .map(|pair| {
+            Some((
+                bn256_point(&pair[..64])?,
+                G2Prepared::from(bn256_g2_point(&pair[64..])?),
+            ))
+        })
+        .collect::<Option<Vec<_>>>()?;
+    let terms = pairs.iter().map(|(a, b)| (a, b)).collect::<Vec<_>>();
+    let is_identity: bool = Bn256::multi_miller_loop(&terms)
+        .final_exponentiation()
+        .is_identity()
+        .into();
+    let mut output = [0u8; 32];
+    output[31] = is_identity as u8;
+    Some(output)
+}
+
+/// Length of the input of the blake2f precompiled contract.
+pub const BLAKE2F_INPUT_LENGTH: usize = 213;
+
+const BLAKE2B_IV: [u64; 8] = [
+    0x6a09e667f3bcc908,
+    0xbb67ae8584caa73b,
+    0x3c6ef372fe94f82b,
+    0xa54ff53a5f1d36f1,
+    0x510e527fade682d1,
+    0x9b05688c2b3e6c1f,
+    0x1f83d9abfb41bd6b,
+    0x5be0cd19137e2179,
+];
+
+const BLAKE2B_SIGMA: [[usize; 16]; 10] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+    [11, 8, 12, 0, 5, 2, 15, 13, 10, 14, 3, 6, 7, 1, 9, 4],
+    [7, 9, 3, 1, 13, 12, 11, 14, 2, 6, 5, 10, 4, 0, 15, 8],
+    [9, 0, 5, 7, 2, 4, 10, 15, 14, 1, 11, 12, 6, 8, 3, 13],
+    [2, 12, 6, 10, 0, 11, 8, 3, 4, 13, 7, 5, 15, 14, 1, 9],
+    [12, 5, 1, 15, 14, 13, 4, 10, 0, 7, 6, 3, 9, 2, 8, 11],
+    [13, 11, 7, 14, 12, 1, 3, 9, 5, 0, 15, 4, 8, 6, 2, 10],
+    [6, 15, 14, 9, 11, 3, 0, 8, 12, 2, 13, 7, 1, 4, 10, 5],
+    [10, 2, 8, 4, 7, 6, 1, 5, 15, 11, 9, 14, 3, 12, 13, 0],
+];
+
+/// Mixing function G of BLAKE2b.
+#[allow(clippy::too_many_arguments)]
+fn blake2b_g(v: &mut [u64; 16], a: usize, b: usize, c: usize, d: usize, x: u64, y: u64) {
+    v[a] = v[a].wrapping_add(v[b]).wrapping_add(x);
+    v[d] = (v[d] ^ v[a]).rotate_right(32);
+    v[c] = v[c].wrapping_add(v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(24);
+    v[a] = v[a].wrapping_add(v[b]).wrapping_add(y);
+    v[d] = (v[d] ^ v[a]).rotate_right(16);
+    v[c] = v[c].wrapping_add(v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(63);
+}
+
+/// Returns the number of rounds of a blake2f input, encoded in its first 4
+/// bytes in big-endian.
+pub fn blake2f_rounds(input: &[u8]) -> Option<u32> {
+    Some(u32::from_be_bytes(input.get(0..4)?.try_into().unwrap()))
+}
+
+/// Compression function F of BLAKE2b as specified in EIP-152, where the
+/// 213 bytes input is `rounds || h || m || t || f` with `rounds` in
+/// big-endian and `h`, `m` and `t` as little-endian 64-bit words. Returns
+/// `None` when the input length is not 213 bytes or the final block flag `f`
+/// is not 0 or 1.
+pub fn blake2f(input: &[u8]) -> Option<[u8; 64]> {
+    if input.len() != BLAKE2F_INPUT_LENGTH {
+        return None;
+    }
+    let is_final_block = match input[212] {
+        0 => false,
+        1 => true,
+        _ => return None,
+    };
+    let rounds = blake2f_rounds(input)?;
+    let word = |offset: usize| u64::from_le_bytes(input[offset..offset + 8].try_into().unwrap());
+    let mut h = [0u64; 8];
+    for (idx, h) in h.iter_mut().enumerate() {
+        *h = word(4 + 8 * idx);
+    }
+    let mut m = [0u64; 16];
+    for (idx, m) in m.iter_mut().enumerate() {
+        *m = word(68 + 8 * idx);
+    }
+    let t = [word(196), word(204)];
+
+    let mut v = [0u64; 16];
+    v[..8].copy_from_slice(&h);
+    v[8..].copy_from_slice(&BLAKE2B_IV);
+    v[12] ^= t[0];
+    v[13] ^= t[1];
+    if is_final_block {
+        v[14] = !v[14];
+    }
+    for round in 0..rounds as usize {
+        let s = &BLAKE2B_SIGMA[round % 10];
+        blake2b_g(&mut v, 0, 4, 8, 12, m[s[0]], m[s[1]]);
+        blake2b_g(&mut v, 1, 5, 9, 13, m[s[2]], m[s[3]]);
+        blake2b_g(&mut v, 2, 6, 10, 14, m[s[4]], m[s[5]]);
+        blake2b_g(&mut v, 3, 7, 11, 15, m[s[6]], m[s[7]]);
+        blake2b_g(&mut v, 0, 5, 10, 15, m[s[8]], m[s[9]]);
+        blake2b_g(&mut v, 1, 6, 11, 12, m[s[10]], m[s[11]]);
+        blake2b_g(&mut v, 2, 7, 8, 13, m[s[12]], m[s[13]]);
+        blake2b_g(&mut v, 3, 4, 9, 14, m[s[14]], m[s[15]]);
+    }
+
+    let mut output = [0u8; 64];
+    for (idx, chunk) in output.chunks_exact_mut(8).enumerate() {
+        chunk.copy_from_slice(&(h[idx] ^ v[idx] ^ v[idx + 8]).to_le_bytes());
+    }
+    Some(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Test vectors 0 to 7 from EIP-152, vector 8 with 2^32 - 1 rounds is
+    // skipped since it's too slow.
+    const MALFORMED_INPUTS: [&str; 4] = [
+        "",
+        "00000c48c9bdf267e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f3af54fa5d182e6ad7f520e511f6c3e2b8c68059b6bbd41fbabd9831f79217e1319cde05b61626300000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000300000000000000000000000000000001",
+        "000000000c48c9bdf267e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f3af54fa5d182e6ad7f520e511f6c3e2b8c68059b6bbd41fbabd9831f79217e1319cde05b61626300000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000300000000000000000000000000000001",
+        "0000000c48c9bdf267e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f3af54fa5d182e6ad7f520e511f6c3e2b8c68059b6bbd41fbabd9831f79217e1319cde05b61626300000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000300000000000000000000000000000002",
+    ];
+
+    const VECTORS: [(&str, &str); 4] = [
+        (
+            "0000000048c9bdf267e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f3af54fa5d182e6ad7f520e511f6c3e2b8c68059b6bbd41fbabd9831f79217e1319cde05b61626300000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000300000000000000000000000000000001",
+            "08c9bcf367e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f3af54fa5d282e6ad7f520e511f6c3e2b8c68059b9442be0454267ce079217e1319cde05b",
+        ),
+        (
+            "0000000c48c9bdf267e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f3af54fa5d182e6ad7f520e511f6c3e2b8c68059b6bbd41fbabd9831f79217e1319cde05b61626300000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000300000000000000000000000000000001",
+            "ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d17d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923",
+        ),
+        (
+            "0000000c48c9bdf267e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f3af54fa5d182e6ad7f520e511f6c3e2b8c68059b6bbd41fbabd9831f79217e1319cde05b61626300000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000300000000000000000000000000000000",
+            "75ab69d3190a562c51aef8d88f1c2775876944407270c42c9844252c26d2875298743e7f6d5ea2f2d3e8d226039cd31b4e426ac4f2d3d666a610c2116fde4735",
+        ),
+        (
+            "0000000148c9bdf267e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f3af54fa5d182e6ad7f520e511f6c3e2b8c68059b6bbd41fbabd9831f79217e1319cde05b61626300000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000300000000000000000000000000000001",
+            "b63a380cb2897d521994a85234ee2c181b5f844d2c624c002677e9703449d2fba551b3a8333bcdf5f2f7e08993d53923de3d64fcc68c034e717b9293fed7a421",
+        ),
+    ];
+
+    const G1: &str = "00000000000000000000000000000000000000000000000000000000000000010000000000000000000000000000000000000000000000000000000000000002";
+    const G1_DOUBLE: &str = "030644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd315ed738c0e0a7c92e7845f96b2ae9c0a68a6a449e3538fc7ff3ebf7a5a18a2c4";
+    const G1_TRIPLE: &str = "0769bf9ac56bea3ff40232bcb1b6bd159315d84715b8e679f2d355961915abf02ab799bee0489429554fdb7c8d086475319e63b40b9c5b57cdf1ff3dd9fe2261";
+    const G1_NEG: &str = "000000000000000000000000000000000000000000000000000000000000000130644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd45";
+    const NOT_ON_CURVE: &str = "00000000000000000000000000000000000000000000000000000000000000010000000000000000000000000000000000000000000000000000000000000003";
+    const INFINITY: &str = "00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000";
+    const G2: &str = "198e9393920d483a7260bfb731fb5d25f1aa493335a9e71297e485b7aef312c21800deef121f1e76426a00665e5c4479674322d4f75edadd46debd5cd992f6ed090689d0585ff075ec9e99ad690c3395bc4b313370b38ef355acdadcd122975b12c85ea5db8c6deb4aab71808dcb408fe3d1e7690c43d37b4ce6cc0166fa7daa";
+    // Order of the BN254 group.
+    const ORDER: &str = "30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000001";
+
+    fn decode(input: &str) -> Vec<u8> {
+        hex::decode(input).unwrap()
+    }
+
+    #[test]
+    fn blake2f_malformed_input() {
+        for input in MALFORMED_INPUTS {
+            assert_eq!(blake2f(&decode(input)), None);
+            assert_eq!(PrecompileCall::Blake2F.execute(&decode(input)), None);
+        }
+    }
+
+    #[test]
+    fn blake2f_eip152_vectors() {
+        for (input, output) in VECTORS {
+            assert_eq!(blake2f(&decode(input)).unwrap().to_vec(), decode(output));
+        }
+    }
+
+    #[test]
+    fn bn256_add_points() {
+        for (a, b, sum) in [
+            (G1, G1, G1_DOUBLE),
+            (G1_DOUBLE, G1, G1_TRIPLE),
+            (G1, INFINITY, G1),
+            (G1, G1_NEG, INFINITY),
+        ] {
+            assert_eq!(
+                bn256_add(&decode(&(a.to_owned() + b))).unwrap().to_vec(),
+                decode(sum)
+            );
+        }
+        // The input is padded with zeros.
+        assert_eq!(bn256_add(&decode(G1)).unwrap().to_vec(), decode(G1));
+        assert_eq!(bn256_add(&[]).unwrap().to_vec(), decode(INFINITY));
+    }
+
+    #[test]
+    fn bn256_scalar_mul_points() {
+        for (point, scalar, product) in [
+            (G1, "02", G1_DOUBLE),
+            (G1, "03", G1_TRIPLE),
+            (G1, "00", INFINITY),
+            (INFINITY, "03", INFINITY),
+        ] {
+            let input = point.to_owned() + &"0".repeat(64 - scalar.len()) + scalar;
+            assert_eq!(
+                bn256_scalar_mul(&decode(&input)).unwrap().to_vec(),
+                decode(product)
+            );
+        }
+        assert_eq!(
+            bn256_scalar_mul(&decode(&(G1.to_owned() + ORDER)))
+                .unwrap()
+                .to_vec(),
+            decode(INFINITY)
+        );
+    }
+
+    #[test]
+    fn bn256_invalid_points() {
+        assert_eq!(bn256_add(&decode(&(G1.to_owned() + NOT_ON_CURVE))), None);
+        assert_eq!(
+            bn256_scalar_mul(&decode(&(NOT_ON_CURVE.to_owned() + ORDER))),
+            None
+        );
+        // A coordinate out of the base field.
+        let out_of_field = "30644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd48"
+            .to_owned()
+            + &G1[64..];
+        assert_eq!(bn256_add(&decode(&out_of_field)), None);
+        assert_eq!(
+            PrecompileCall::Bn256Add.execute(&decode(NOT_ON_CURVE)),
+            None
+        );
+    }
+
+    #[test]
+    fn bn256_pairing_check() {
+        let pass = decode(&format!("{:064x}", 1));
+        let fail = decode(&format!("{:064x}", 0));
+        // An empty input passes the check.
+        assert_eq!(bn256_pairing(&[]).unwrap().to_vec(), pass);
+        // e(G1, G2) is not the identity, but e(G1, G2) * e(-G1, G2) is.
+        assert_eq!(
+            bn256_pairing(&decode(&(G1.to_owned() + G2)))
+                .unwrap()
+                .to_vec(),
+            fail
+        );
+        assert_eq!(
+            bn256_pairing(&decode(&(G1.to_owned() + G2 + G1_NEG + G2)))
+                .unwrap()
+                .to_vec(),
+            pass
+        );
+        // e(2 * G1, G2) * e(-G1, G2) is not either.
+        assert_eq!(
+            bn256_pairing(&decode(&(G1_DOUBLE.to_owned() + G2 + G1_NEG + G2)))
+                .unwrap()
+                .to_vec(),
+            fail
+        );
+        // A pair with the point at infinity doesn't change the result.
+        assert_eq!(
+            bn256_pairing(&decode(&(INFINITY.to_owned() + G2)))
+                .unwrap()
+                .to_vec(),
+            pass
+        );
+    }
+
+    #[test]
+    fn bn256_pairing_invalid_input() {
+        // The input length is not a multiple of 192 bytes.
+        assert_eq!(bn256_pairing(&decode(G1)), None);
+        assert_eq!(bn256_pairing(&decode(&(G1.to_owned() + G2 + "00"))), None);
+        assert_eq!(
+            bn256_pairing(&decode(&(NOT_ON_CURVE.to_owned() + G2))),
+            None
+        );
+        // A point of the twist not on the curve.
+        let not_on_twist = G2[..255].to_owned() + "b";
+        assert_eq!(
+            bn256_pairing(&decode(&(G1.to_owned() + &not_on_twist))),
+            None
+        );
+    }
+}
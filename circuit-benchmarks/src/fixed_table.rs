@@ -0,0 +1,36 @@
+//! Fixed table benchmarks
+
+#[cfg(test)]
+mod fixed_table_benches {
+    use ark_std::{end_timer, start_timer};
+    use halo2_proofs::halo2curves::bn256::Fr;
+    use strum::IntoEnumIterator;
+    use zkevm_circuits::evm_circuit::table::{build_fixed_table_rows, FixedTableTag};
+
+    #[cfg_attr(not(feature = "benches"), ignore)]
+    #[test]
+    fn bench_fixed_table_fill() {
+        // The bitwise rows are by far the largest part of the fixed table of
+        // the EVM circuit.
+        let fixed_table_tags: Vec<_> = FixedTableTag::iter().collect();
+
+        let single_threaded = rayon::ThreadPoolBuilder::new()
+            .num_threads(1)
+            .build()
+            .unwrap();
+        let start1 = start_timer!(|| "Single-threaded fixed table fill");
+        let rows_single =
+            single_threaded.install(|| build_fixed_table_rows::<Fr>(&fixed_table_tags));
+        end_timer!(start1);
+
+        let multi_message = format!(
+            "Multi-threaded fixed table fill with {} threads",
+            rayon::current_num_threads()
+        );
+        let start2 = start_timer!(|| multi_message);
+        let rows_multi = build_fixed_table_rows::<Fr>(&fixed_table_tags);
+        end_timer!(start2);
+
+        assert_eq!(rows_single, rows_multi);
+    }
+}
@@ -35,3 +35,7 @@ pub mod copy_circuit;
 #[cfg(test)]
 #[cfg(feature = "benches")]
 pub mod exp_circuit;
+
+#[cfg(test)]
+#[cfg(feature = "benches")]
+pub mod fixed_table;
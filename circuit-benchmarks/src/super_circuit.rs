@@ -82,7 +82,7 @@ mod tests {
             max_bytecode: 512,
             keccak_padding: None,
         };
-        let (_, circuit, instance, _) =
+        let (k, circuit, instance, _) =
             SuperCircuit::<_, MAX_TXS, MAX_CALLDATA, 0x100>::build(block, circuits_params).unwrap();
         let instance_refs: Vec<&[Fr]> = instance.iter().map(|v| &v[..]).collect();
 
@@ -94,6 +94,7 @@ mod tests {
         end_timer!(start1);
 
         // Initialize the proving key
+        SuperCircuit::<Fr, MAX_TXS, MAX_CALLDATA, 0x100>::assert_params_k(&general_params, k);
         let vk = keygen_vk(&general_params, &circuit).expect("keygen_vk should not fail");
         let pk = keygen_pk(&general_params, vk, &circuit).expect("keygen_pk should not fail");
         // Create a proof
@@ -33,6 +33,8 @@ pub enum Error {
     WordToMemAddr,
     /// Signature parsing error.
     Signature(libsecp256k1::Error),
+    /// RLP decoding error.
+    RlpDecoding(ethers_core::utils::rlp::DecoderError),
 }
 
 impl From<libsecp256k1::Error> for Error {
@@ -41,6 +43,12 @@ impl From<libsecp256k1::Error> for Error {
     }
 }
 
+impl From<ethers_core::utils::rlp::DecoderError> for Error {
+    fn from(err: ethers_core::utils::rlp::DecoderError) -> Self {
+        Error::RlpDecoding(err)
+    }
+}
+
 impl Display for Error {
     fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
         write!(f, "{:?}", self)
@@ -62,10 +62,45 @@ impl fmt::Debug for Gas {
     }
 }
 
+/// Hard fork whose EVM rules a block is executed with.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HardFork {
+    /// Berlin hard fork
+    Berlin,
+    /// London hard fork, which introduces the `BASEFEE` opcode (EIP-3198)
+    #[default]
+    London,
+    /// The Merge, after which the opcode `0x44` returns PREVRANDAO instead of
+    /// the block difficulty (EIP-4399)
+    Merge,
+}
+
 /// Quotient for max refund of gas used
 pub const MAX_REFUND_QUOTIENT_OF_GAS_USED: usize = 5;
 /// Gas stipend when CALL or CALLCODE is attached with value.
 pub const GAS_STIPEND_CALL_WITH_VALUE: u64 = 2300;
+/// Maximum size of the code deployed by a contract creation (EIP-170).
+pub const MAX_CODE_SIZE: u64 = 0x6000;
+/// First byte that the code deployed by a contract creation can't start with
+/// (EIP-3541).
+pub const INVALID_CODE_FIRST_BYTE: u8 = 0xef;
+/// Number of precompiled contracts, deployed at the addresses `0x01..=0x09`
+/// up to London, which is the hardfork traced by geth. `0x0a` is an ordinary
+/// account there.
+pub const PRECOMPILE_COUNT: u64 = 9;
+/// Address of the identity precompiled contract, which returns its input.
+pub const PRECOMPILE_IDENTITY: u64 = 0x04;
+/// Address of the ecAdd precompiled contract, which adds two BN254 points.
+pub const PRECOMPILE_BN256_ADD: u64 = 0x06;
+/// Address of the ecMul precompiled contract, which multiplies a BN254 point
+/// by a scalar.
+pub const PRECOMPILE_BN256_SCALAR_MUL: u64 = 0x07;
+/// Address of the ecPairing precompiled contract, which checks BN254 pairings.
+pub const PRECOMPILE_BN256_PAIRING: u64 = 0x08;
+/// Address of the blake2f precompiled contract, which runs the compression
+/// function F of BLAKE2b (EIP-152).
+pub const PRECOMPILE_BLAKE2F: u64 = 0x09;
 
 /// Defines the gas consumption.
 #[derive(Clone, Copy, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
@@ -134,6 +169,20 @@ impl GasCost {
     pub const MEMORY_EXPANSION_LINEAR_COEFF: Self = Self(3);
     /// Constant gas for LOG[0-4] op codes
     pub const LOG: Self = Self(375);
+    /// Constant gas for calling the identity precompiled contract
+    pub const PRECOMPILE_IDENTITY_BASE: Self = Self(15);
+    /// Gas for every word of input of the identity precompiled contract
+    pub const PRECOMPILE_IDENTITY_PER_WORD: Self = Self(3);
+    /// Gas for every round of the blake2f precompiled contract
+    pub const PRECOMPILE_BLAKE2F_PER_ROUND: Self = Self(1);
+    /// Constant gas for calling the ecAdd precompiled contract
+    pub const PRECOMPILE_BN256_ADD: Self = Self(150);
+    /// Constant gas for calling the ecMul precompiled contract
+    pub const PRECOMPILE_BN256_SCALAR_MUL: Self = Self(6000);
+    /// Constant gas for calling the ecPairing precompiled contract
+    pub const PRECOMPILE_BN256_PAIRING_BASE: Self = Self(45000);
+    /// Gas for every pair of points of the ecPairing precompiled contract
+    pub const PRECOMPILE_BN256_PAIRING_PER_PAIR: Self = Self(34000);
 }
 
 impl GasCost {
@@ -1,5 +1,8 @@
 //! Doc this
-use crate::{error::Error, evm_types::GasCost};
+use crate::{
+    error::Error,
+    evm_types::{GasCost, HardFork},
+};
 use core::fmt::Debug;
 use lazy_static::lazy_static;
 use regex::Regex;
@@ -316,6 +319,16 @@ pub enum OpcodeId {
 }
 
 impl OpcodeId {
+    /// Returns `true` if the `OpcodeId` is defined by the EVM rules of
+    /// `hardfork`, executing it halts with an invalid opcode error otherwise.
+    pub fn is_enabled_in(&self, hardfork: HardFork) -> bool {
+        match self {
+            OpcodeId::BASEFEE => hardfork >= HardFork::London,
+            OpcodeId::INVALID(_) => false,
+            _ => true,
+        }
+    }
+
     /// Returns `true` if the `OpcodeId` is a `PUSHn`.
     pub fn is_push(&self) -> bool {
         self.as_u8() >= Self::PUSH1.as_u8() && self.as_u8() <= Self::PUSH32.as_u8()
@@ -857,6 +870,19 @@ impl OpcodeId {
         }
     }
 
+    /// Returns the number of stack items reached by `DUPn` and `SWAPn`, i.e.
+    /// `n` and `n + 1`, otherwise None. Executing them on a shallower stack
+    /// halts with a stack underflow.
+    pub fn dup_swap_depth(&self) -> Option<u8> {
+        if self.is_dup() {
+            self.postfix()
+        } else if self.is_swap() {
+            self.postfix().map(|n| n + 1)
+        } else {
+            None
+        }
+    }
+
     /// Returns number of bytes used by immediate data. This is > 0 only for
     /// push opcodes.
     pub fn data_len(&self) -> usize {
@@ -1229,6 +1255,27 @@ mod opcode_ids_tests {
         assert_eq!(OpcodeId::CALLCODE.postfix(), None);
     }
 
+    #[test]
+    fn dup_swap_depth() {
+        assert_eq!(OpcodeId::DUP1.dup_swap_depth(), Some(1));
+        assert_eq!(OpcodeId::DUP16.dup_swap_depth(), Some(16));
+        assert_eq!(OpcodeId::SWAP1.dup_swap_depth(), Some(2));
+        assert_eq!(OpcodeId::SWAP16.dup_swap_depth(), Some(17));
+        assert_eq!(OpcodeId::PUSH1.dup_swap_depth(), None);
+
+        // The stack pointer of a stack of `depth` items is `1024 - depth`.
+        for opcode in (0..=u8::MAX).map(OpcodeId::from) {
+            if let Some(depth) = opcode.dup_swap_depth() {
+                assert_eq!(
+                    opcode.valid_stack_ptr_range().1,
+                    1024 - depth as u32,
+                    "{:?}",
+                    opcode
+                );
+            }
+        }
+    }
+
     #[test]
     fn data_len() {
         assert_eq!(OpcodeId::PUSH1.data_len(), 1);
@@ -1,11 +1,18 @@
 //! Types needed for generating Ethereum traces
 
 use crate::{
+    evm_types::HardFork,
     sign_types::{biguint_to_32bytes_le, ct_option_ok_or, recover_pk, SignData, SECP256K1_Q},
     AccessList, Address, Block, Bytes, Error, GethExecTrace, Hash, ToBigEndian, ToLittleEndian,
-    Word, U64,
+    ToWord, Word, U64,
+};
+use ethers_core::{
+    types::{OtherFields, TransactionRequest},
+    utils::{
+        keccak256,
+        rlp::{DecoderError, Rlp},
+    },
 };
-use ethers_core::types::TransactionRequest;
 use ethers_signers::{LocalWallet, Signer};
 use halo2_proofs::halo2curves::{group::ff::PrimeField, secp256k1};
 use num::Integer;
@@ -63,7 +70,8 @@ pub struct BlockConstants {
     pub timestamp: Word,
     /// number
     pub number: U64,
-    /// difficulty
+    /// value of DIFFICULTY, i.e. PREVRANDAO after the Merge (see
+    /// [`difficulty_or_prevrandao`])
     pub difficulty: Word,
     /// gas limit
     pub gas_limit: Word,
@@ -71,22 +79,40 @@ pub struct BlockConstants {
     pub base_fee: Word,
 }
 
+/// Returns the value pushed by the opcode `0x44` in `block` executed with the
+/// EVM rules of `hardfork`: its difficulty before the Merge, and its
+/// `mix_hash`, i.e. the beacon randomness returned by PREVRANDAO (EIP-4399),
+/// after the Merge.
+pub fn difficulty_or_prevrandao<TX>(block: &Block<TX>, hardfork: HardFork) -> Word {
+    if hardfork >= HardFork::Merge {
+        block.mix_hash.unwrap_or_default().to_word()
+    } else {
+        block.difficulty
+    }
+}
+
 impl<TX> TryFrom<&Block<TX>> for BlockConstants {
     type Error = Error;
 
     fn try_from(block: &Block<TX>) -> Result<Self, Self::Error> {
+        Self::try_from_block(block, HardFork::default())
+    }
+}
+
+impl BlockConstants {
+    /// Generates the `BlockConstants` of `block` executed with the EVM rules
+    /// of `hardfork`.
+    pub fn try_from_block<TX>(block: &Block<TX>, hardfork: HardFork) -> Result<Self, Error> {
         Ok(Self {
             coinbase: block.author.ok_or(Error::IncompleteBlock)?,
             timestamp: block.timestamp,
             number: block.number.ok_or(Error::IncompleteBlock)?,
-            difficulty: block.difficulty,
+            difficulty: difficulty_or_prevrandao(block, hardfork),
             gas_limit: block.gas_limit,
             base_fee: block.base_fee_per_gas.ok_or(Error::IncompleteBlock)?,
         })
     }
-}
 
-impl BlockConstants {
     /// Generates a new `BlockConstants` instance from it's fields.
     pub fn new(
         coinbase: Address,
@@ -251,6 +277,8 @@ pub struct GethData {
     pub geth_traces: Vec<GethExecTrace>,
     /// Accounts
     pub accounts: Vec<Account>,
+    /// Hard fork whose EVM rules the block is executed with
+    pub hardfork: HardFork,
 }
 
 impl GethData {
@@ -268,3 +296,88 @@ impl GethData {
         }
     }
 }
+
+/// Decode a raw RLP encoded block `[header, transactions, uncles]` into the
+/// block type returned by the geth JSON-RPC API, for users who have the block
+/// bytes but no node to query.
+///
+/// The block and transaction hashes and the transaction senders are computed
+/// since they aren't part of the encoding. Fields which only a node knows,
+/// like the total difficulty or the size, are left empty.
+pub fn decode_rlp_block(bytes: &[u8]) -> Result<Block<crate::Transaction>, Error> {
+    let rlp = Rlp::new(bytes);
+    let header = rlp.at(0)?;
+    if header.item_count()? < 15 {
+        return Err(DecoderError::RlpIncorrectListLen.into());
+    }
+    let hash = Hash::from(keccak256(header.as_raw()));
+    let number = U64::from(header.val_at::<u64>(8)?);
+    let base_fee_per_gas = if header.item_count()? > 15 {
+        Some(header.val_at::<Word>(15)?)
+    } else {
+        None
+    };
+
+    let transactions = rlp
+        .at(1)?
+        .iter()
+        .enumerate()
+        .map(|(idx, item)| -> Result<crate::Transaction, Error> {
+            let mut tx: crate::Transaction = item.as_val()?;
+            // Typed transactions are wrapped in a byte string whose hash
+            // doesn't count.
+            tx.hash = Hash::from(keccak256(if item.is_list() {
+                item.as_raw()
+            } else {
+                item.data()?
+            }));
+            tx.from = tx
+                .recover_from()
+                .map_err(|_| Error::Signature(libsecp256k1::Error::InvalidSignature))?;
+            tx.block_hash = Some(hash);
+            tx.block_number = Some(number);
+            tx.transaction_index = Some(U64::from(idx));
+            // Like the JSON-RPC API, report the effective gas price of
+            // EIP-1559 transactions.
+            if let (None, Some(max_fee), Some(max_priority_fee), Some(base_fee)) = (
+                tx.gas_price,
+                tx.max_fee_per_gas,
+                tx.max_priority_fee_per_gas,
+                base_fee_per_gas,
+            ) {
+                tx.gas_price = Some(std::cmp::min(max_fee, base_fee + max_priority_fee));
+            }
+            Ok(tx)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(Block {
+        hash: Some(hash),
+        parent_hash: header.val_at(0)?,
+        uncles_hash: header.val_at(1)?,
+        author: Some(header.val_at(2)?),
+        state_root: header.val_at(3)?,
+        transactions_root: header.val_at(4)?,
+        receipts_root: header.val_at(5)?,
+        logs_bloom: Some(header.val_at(6)?),
+        difficulty: header.val_at(7)?,
+        number: Some(number),
+        gas_limit: header.val_at(9)?,
+        gas_used: header.val_at(10)?,
+        timestamp: header.val_at(11)?,
+        extra_data: Bytes::from(header.val_at::<Vec<u8>>(12)?),
+        mix_hash: Some(header.val_at(13)?),
+        nonce: Some(header.val_at(14)?),
+        base_fee_per_gas,
+        total_difficulty: None,
+        seal_fields: Vec::new(),
+        uncles: rlp
+            .at(2)?
+            .iter()
+            .map(|uncle| Hash::from(keccak256(uncle.as_raw())))
+            .collect(),
+        transactions,
+        size: None,
+        other: OtherFields::default(),
+    })
+}
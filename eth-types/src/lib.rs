@@ -402,6 +402,11 @@ pub struct GethExecTrace {
     pub gas: Gas,
     /// True when the transaction has failed.
     pub failed: bool,
+    /// True when the transaction was rejected before execution (nonce
+    /// mismatch, insufficient balance or gas below intrinsic), so it has no
+    /// effect on the state.
+    #[serde(default)]
+    pub invalid: bool,
     /// Return value of execution which is a hex encoded byte array
     #[serde(rename = "returnValue")]
     pub return_value: String,
@@ -518,6 +523,7 @@ mod tests {
             GethExecTrace {
                 gas: Gas(26809),
                 failed: false,
+                invalid: false,
                 return_value: "".to_owned(),
                 struct_logs: vec![
                     GethExecStep {
@@ -1,6 +1,7 @@
 //! This module generates traces by connecting to an external tracer
 
 use eth_types::{
+    evm_types::HardFork,
     geth_types::{Account, BlockConstants, Transaction},
     Address, Error, GethExecTrace, Word,
 };
@@ -23,6 +24,8 @@ pub struct TraceConfig {
     pub transactions: Vec<Transaction>,
     /// logger
     pub logger_config: LoggerConfig,
+    /// hard fork whose rules the transactions are executed with
+    pub hardfork: HardFork,
 }
 
 /// Configuration structure for `logger.Config`
@@ -1,6 +1,7 @@
 package gethutil
 
 import (
+	"errors"
 	"fmt"
 	"math/big"
 
@@ -24,6 +25,27 @@ type ExecutionResult struct {
 	Failed      bool           `json:"failed"`
 	ReturnValue string         `json:"returnValue"`
 	StructLogs  []StructLogRes `json:"structLogs"`
+	// Invalid is set when the transaction is rejected before execution
+	// (nonce mismatch or at max, insufficient balance, gas below intrinsic),
+	// in which case it doesn't modify the state.
+	Invalid bool `json:"invalid,omitempty"`
+}
+
+// isInvalidTxError returns whether err is returned by the pre-execution checks
+// of a transaction, which clients handle by skipping the transaction.
+func isInvalidTxError(err error) bool {
+	for _, target := range []error{
+		core.ErrNonceTooLow,
+		core.ErrNonceTooHigh,
+		core.ErrNonceMax,
+		core.ErrInsufficientFunds,
+		core.ErrIntrinsicGas,
+	} {
+		if errors.Is(err, target) {
+			return true
+		}
+	}
+	return false
 }
 
 // StructLogRes stores a structured log emitted by the EVM while replaying a
@@ -122,6 +144,10 @@ type TraceConfig struct {
 	Accounts      map[common.Address]Account `json:"accounts"`
 	Transactions  []Transaction              `json:"transactions"`
 	LoggerConfig  *logger.Config             `json:"logger_config"`
+	// Hardfork is the hard fork whose rules the transactions are executed
+	// with, one of "berlin", "london" or "merge". After the Merge, the block
+	// difficulty is the PREVRANDAO value returned by the opcode 0x44.
+	Hardfork string `json:"hardfork"`
 }
 
 func Trace(config TraceConfig) ([]*ExecutionResult, error) {
@@ -142,6 +168,17 @@ func Trace(config TraceConfig) ([]*ExecutionResult, error) {
 		BerlinBlock:         big.NewInt(0),
 		LondonBlock:         big.NewInt(0),
 	}
+	var random *common.Hash
+	switch config.Hardfork {
+	case "", "london":
+	case "berlin":
+		chainConfig.LondonBlock = nil
+	case "merge":
+		prevRandao := common.BigToHash(toBigInt(config.Block.Difficulty))
+		random = &prevRandao
+	default:
+		return nil, fmt.Errorf("Unsupported hardfork: %s", config.Hardfork)
+	}
 
 	var txsGasLimit uint64
 	blockGasLimit := toBigInt(config.Block.GasLimit).Uint64()
@@ -195,6 +232,7 @@ func Trace(config TraceConfig) ([]*ExecutionResult, error) {
 		Difficulty:  toBigInt(config.Block.Difficulty),
 		BaseFee:     toBigInt(config.Block.BaseFee),
 		GasLimit:    blockGasLimit,
+		Random:      random,
 	}
 
 	// Setup state db with accounts from argument
@@ -217,7 +255,17 @@ func Trace(config TraceConfig) ([]*ExecutionResult, error) {
 		tracer := logger.NewStructLogger(config.LoggerConfig)
 		evm := vm.NewEVM(blockCtx, core.NewEVMTxContext(message), stateDB, &chainConfig, vm.Config{Debug: true, Tracer: tracer, NoBaseFee: true})
 
+		snapshot := stateDB.Snapshot()
 		result, err := core.ApplyMessage(evm, message, new(core.GasPool).AddGas(message.Gas()))
+		if err != nil && isInvalidTxError(err) {
+			stateDB.RevertToSnapshot(snapshot)
+			executionResults[i] = &ExecutionResult{
+				ReturnValue: "",
+				StructLogs:  []StructLogRes{},
+				Invalid:     true,
+			}
+			continue
+		}
 		if err != nil {
 			return nil, fmt.Errorf("Failed to apply config.Transactions[%d]: %w", i, err)
 		}
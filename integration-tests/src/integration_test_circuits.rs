@@ -1,7 +1,7 @@
 use crate::{get_client, GenDataOutput};
 use bus_mapping::circuit_input_builder::{BuilderClient, CircuitInputBuilder, CircuitsParams};
 use bus_mapping::mock::BlockData;
-use eth_types::geth_types::GethData;
+use eth_types::{evm_types::HardFork, geth_types::GethData};
 use halo2_proofs::plonk::{
     create_proof, keygen_pk, keygen_vk, verify_proof, Circuit, ProvingKey, VerifyingKey,
 };
@@ -28,7 +28,7 @@ use zkevm_circuits::copy_circuit::CopyCircuit;
 use zkevm_circuits::evm_circuit::witness::block_convert;
 use zkevm_circuits::state_circuit::StateCircuit;
 use zkevm_circuits::tx_circuit::TxCircuit;
-use zkevm_circuits::util::SubCircuit;
+use zkevm_circuits::util::{log2_ceil, SubCircuit};
 use zkevm_circuits::witness::Block;
 
 /// TEST_MOCK_RANDOMNESS
@@ -135,6 +135,19 @@ fn new_empty_block() -> Block<Fr> {
     block_convert(&builder.block, &builder.code_db).unwrap()
 }
 
+/// Number of rows reserved by halo2 for the blinding factors.
+const NUM_BLINDING_ROWS: usize = 64;
+
+/// Returns the minimum degree `k` such that the circuit fits the rows required
+/// by `block`, with padding, and the blinding rows, like
+/// `SuperCircuit::min_k`.
+pub fn min_degree<C: SubCircuit<Fr>>(block: &Block<Fr>) -> u32 {
+    let (_, num_rows) = C::min_num_rows_block(block);
+    log2_ceil(NUM_BLINDING_ROWS + num_rows)
+}
+
+/// Returns the params for `degree`, setting them up on the fly the first time
+/// they are requested.
 fn get_general_params(degree: u32) -> ParamsKZG<Bn256> {
     let mut map = GEN_PARAMS.lock().unwrap();
     match map.get(&degree) {
@@ -154,7 +167,9 @@ async fn gen_inputs(
     eth_types::Block<eth_types::Transaction>,
 ) {
     let cli = get_client();
-    let cli = BuilderClient::new(cli, CIRCUITS_PARAMS).await.unwrap();
+    let cli = BuilderClient::new(cli, CIRCUITS_PARAMS, HardFork::default())
+        .await
+        .unwrap();
 
     cli.gen_inputs(block_num).await.unwrap()
 }
@@ -231,6 +246,8 @@ fn test_actual<C: Circuit<Fr>>(
         }
     };
 
+    // The transcript already writes every G1 point in its compressed 32 byte
+    // encoding, and `Blake2bRead` decodes the same format.
     let transcript = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
 
     // change instace to slice
@@ -274,6 +291,15 @@ pub async fn test_circuit_at_block<C: SubCircuit<Fr> + Circuit<Fr>>(
     let (builder, _) = gen_inputs(block_num).await;
     let mut block = block_convert(&builder.block, &builder.code_db).unwrap();
     block.randomness = Fr::from(TEST_MOCK_RANDOMNESS);
+    let min_degree = min_degree::<C>(&block);
+    assert!(
+        degree >= min_degree,
+        "{} circuit degree {} is too small for block {}, it requires at least {}",
+        circuit_name,
+        degree,
+        block_num,
+        min_degree
+    );
     let circuit = C::new_from_block(&block);
     let instance = circuit.instance();
 
@@ -283,3 +309,23 @@ pub async fn test_circuit_at_block<C: SubCircuit<Fr> + Circuit<Fr>>(
         test_mock(degree, &circuit, instance);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zkevm_circuits::super_circuit::SuperCircuit;
+
+    #[test]
+    fn min_degree_counts_blinding_rows() {
+        let block = new_empty_block();
+        let (_, num_rows) = StateCircuit::<Fr>::min_num_rows_block(&block);
+        let degree = min_degree::<StateCircuit<Fr>>(&block);
+        assert!(1 << degree >= num_rows + NUM_BLINDING_ROWS);
+        assert!(1 << (degree - 1) < num_rows + NUM_BLINDING_ROWS);
+
+        assert_eq!(
+            min_degree::<SuperCircuit<Fr, MAX_TXS, MAX_CALLDATA, TEST_MOCK_RANDOMNESS>>(&block),
+            SuperCircuit::<Fr, MAX_TXS, MAX_CALLDATA, TEST_MOCK_RANDOMNESS>::min_k(&block)
+        );
+    }
+}
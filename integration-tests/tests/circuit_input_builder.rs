@@ -1,6 +1,7 @@
 #![cfg(feature = "circuit_input_builder")]
 
 use bus_mapping::circuit_input_builder::{BuilderClient, CircuitsParams};
+use eth_types::evm_types::HardFork;
 use integration_tests::{get_client, log_init, GenDataOutput};
 use lazy_static::lazy_static;
 use log::trace;
@@ -21,6 +22,7 @@ async fn test_circuit_input_builder_block(block_num: u64) {
             max_copy_rows: 16384,
             keccak_padding: None,
         },
+        HardFork::default(),
     )
     .await
     .unwrap();
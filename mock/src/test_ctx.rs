@@ -2,6 +2,7 @@
 
 use crate::{eth, MockAccount, MockBlock, MockTransaction};
 use eth_types::{
+    evm_types::HardFork,
     geth_types::{Account, BlockConstants, GethData},
     Block, Bytecode, Error, GethExecTrace, Transaction, Word,
 };
@@ -89,6 +90,8 @@ pub struct TestContext<const NACC: usize, const NTX: usize> {
     pub eth_block: eth_types::Block<eth_types::Transaction>,
     /// Execution Trace from geth
     pub geth_traces: [eth_types::GethExecTrace; NTX],
+    /// Hard fork whose EVM rules the transactions are traced with
+    pub hardfork: HardFork,
 }
 
 impl<const NACC: usize, const NTX: usize> From<TestContext<NACC, NTX>> for GethData {
@@ -99,6 +102,7 @@ impl<const NACC: usize, const NTX: usize> From<TestContext<NACC, NTX>> for GethD
             eth_block: ctx.eth_block,
             geth_traces: ctx.geth_traces.to_vec(),
             accounts: ctx.accounts.into(),
+            hardfork: ctx.hardfork,
         }
     }
 }
@@ -111,6 +115,53 @@ impl<const NACC: usize, const NTX: usize> TestContext<NACC, NTX> {
         func_block: Fb,
         logger_config: LoggerConfig,
     ) -> Result<Self, Error>
+    where
+        FTx: FnOnce(Vec<&mut MockTransaction>, [MockAccount; NACC]),
+        Fb: FnOnce(&mut MockBlock, Vec<MockTransaction>) -> &mut MockBlock,
+        FAcc: FnOnce([&mut MockAccount; NACC]),
+    {
+        Self::new_with_config(
+            history_hashes,
+            acc_fns,
+            func_tx,
+            func_block,
+            logger_config,
+            HardFork::default(),
+        )
+    }
+
+    /// Create a new TestContext like [`TestContext::new`], but tracing the
+    /// transactions with the EVM rules of `hardfork`.
+    pub fn new_with_hardfork<FAcc, FTx, Fb>(
+        history_hashes: Option<Vec<Word>>,
+        acc_fns: FAcc,
+        func_tx: FTx,
+        func_block: Fb,
+        hardfork: HardFork,
+    ) -> Result<Self, Error>
+    where
+        FTx: FnOnce(Vec<&mut MockTransaction>, [MockAccount; NACC]),
+        Fb: FnOnce(&mut MockBlock, Vec<MockTransaction>) -> &mut MockBlock,
+        FAcc: FnOnce([&mut MockAccount; NACC]),
+    {
+        Self::new_with_config(
+            history_hashes,
+            acc_fns,
+            func_tx,
+            func_block,
+            LoggerConfig::default(),
+            hardfork,
+        )
+    }
+
+    fn new_with_config<FAcc, FTx, Fb>(
+        history_hashes: Option<Vec<Word>>,
+        acc_fns: FAcc,
+        func_tx: FTx,
+        func_block: Fb,
+        logger_config: LoggerConfig,
+        hardfork: HardFork,
+    ) -> Result<Self, Error>
     where
         FTx: FnOnce(Vec<&mut MockTransaction>, [MockAccount; NACC]),
         Fb: FnOnce(&mut MockBlock, Vec<MockTransaction>) -> &mut MockBlock,
@@ -174,6 +225,7 @@ impl<const NACC: usize, const NTX: usize> TestContext<NACC, NTX> {
             accounts.clone(),
             history_hashes.clone(),
             logger_config,
+            hardfork,
         )?;
 
         Ok(Self {
@@ -182,6 +234,7 @@ impl<const NACC: usize, const NTX: usize> TestContext<NACC, NTX> {
             history_hashes: history_hashes.unwrap_or_default(),
             eth_block: block,
             geth_traces,
+            hardfork,
         })
     }
 
@@ -234,11 +287,12 @@ fn gen_geth_traces<const NACC: usize, const NTX: usize>(
     accounts: [Account; NACC],
     history_hashes: Option<Vec<Word>>,
     logger_config: LoggerConfig,
+    hardfork: HardFork,
 ) -> Result<[GethExecTrace; NTX], Error> {
     let trace_config = TraceConfig {
         chain_id,
         history_hashes: history_hashes.unwrap_or_default(),
-        block_constants: BlockConstants::try_from(&block)?,
+        block_constants: BlockConstants::try_from_block(&block, hardfork)?,
         accounts: accounts
             .iter()
             .map(|account| (account.address, account.clone()))
@@ -249,6 +303,7 @@ fn gen_geth_traces<const NACC: usize, const NTX: usize>(
             .map(eth_types::geth_types::Transaction::from)
             .collect(),
         logger_config,
+        hardfork,
     };
     let traces = trace(&trace_config)?;
     let result: [GethExecTrace; NTX] = traces.try_into().expect("Unexpected len mismatch");
@@ -245,6 +245,7 @@ pub fn run_test(
         geth_traces: geth_traces.clone(),
         accounts: trace_config.accounts.values().cloned().collect(),
         eth_block: eth_block.clone(),
+        hardfork: trace_config.hardfork,
     };
 
     let mut builder;
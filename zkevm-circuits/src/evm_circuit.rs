@@ -20,6 +20,7 @@ use bus_mapping::evm::OpcodeId;
 use eth_types::Field;
 use execution::ExecutionConfig;
 use itertools::Itertools;
+use std::collections::HashSet;
 use strum::IntoEnumIterator;
 use table::FixedTableTag;
 use witness::Block;
@@ -116,11 +117,12 @@ impl<F: Field> EvmCircuitConfig<F> {
         layouter: &mut impl Layouter<F>,
         fixed_table_tags: Vec<FixedTableTag>,
     ) -> Result<(), Error> {
+        let rows = table::build_fixed_table_rows(&fixed_table_tags);
         layouter.assign_region(
             || "fixed table",
             |mut region| {
                 for (offset, row) in std::iter::once([F::zero(); 4])
-                    .chain(fixed_table_tags.iter().flat_map(|tag| tag.build()))
+                    .chain(rows.iter().copied())
                     .enumerate()
                 {
                     for (column, value) in self.fixed_table.iter().zip_eq(row) {
@@ -170,6 +172,9 @@ impl<F: Field> EvmCircuit<F> {
         }
     }
 
+    /// Return a new EvmCircuit which only loads `fixed_table_tags` into the
+    /// fixed table, shrinking the fixed region when the block only needs a
+    /// subset of them.  Synthesis fails if the block needs an excluded tag.
     pub fn new_dev(block: Block<F>, fixed_table_tags: Vec<FixedTableTag>) -> Self {
         Self {
             block: Some(block),
@@ -177,6 +182,17 @@ impl<F: Field> EvmCircuit<F> {
         }
     }
 
+    /// Return a new EvmCircuit without the bitwise rows of the fixed table,
+    /// for blocks without AND, OR, XOR and NOT.
+    pub fn new_without_bitwise_table(block: Block<F>) -> Self {
+        Self::new_dev(
+            block,
+            FixedTableTag::iter()
+                .filter(|tag| !tag.is_bitwise())
+                .collect(),
+        )
+    }
+
     /// Calculate which rows are "actually" used in the circuit
     pub fn get_active_rows(block: &Block<F>) -> (Vec<usize>, Vec<usize>) {
         let max_offset = Self::get_num_rows_required(block);
@@ -237,6 +253,22 @@ impl<F: Field> SubCircuit<F> for EvmCircuit<F> {
     ) -> Result<(), Error> {
         let block = self.block.as_ref().unwrap();
 
+        // The zero row is always loaded, so the Zero tag can be excluded.
+        let missing_tags = detect_fixed_table_tags(block)
+            .into_iter()
+            .filter(|tag| *tag != FixedTableTag::Zero && !self.fixed_table_tags.contains(tag))
+            .collect_vec();
+        if !missing_tags.is_empty() {
+            log::error!(
+                "fixed table tags {:?} are required by the block but not loaded",
+                missing_tags
+            );
+            if missing_tags.iter().any(FixedTableTag::is_bitwise) {
+                log::error!("the bitwise fixed table is needed by AND, OR, XOR or NOT");
+            }
+            return Err(Error::Synthesis);
+        }
+
         config.load_fixed_table(layouter, self.fixed_table_tags.clone())?;
         config.load_byte_table(layouter)?;
         config.execution.assign_block(layouter, block, challenges)
@@ -245,23 +277,31 @@ impl<F: Field> SubCircuit<F> for EvmCircuit<F> {
 
 /// create fixed_table_tags needed given witness block
 pub(crate) fn detect_fixed_table_tags<F: Field>(block: &Block<F>) -> Vec<FixedTableTag> {
-    let need_bitwise_lookup = block.txs.iter().any(|tx| {
-        tx.steps.iter().any(|step| {
-            matches!(
-                step.opcode,
-                Some(OpcodeId::AND)
-                    | Some(OpcodeId::OR)
-                    | Some(OpcodeId::XOR)
-                    | Some(OpcodeId::NOT)
-            )
-        })
-    });
+    let opcodes: HashSet<OpcodeId> = block
+        .txs
+        .iter()
+        .flat_map(|tx| tx.steps.iter().filter_map(|step| step.opcode))
+        .collect();
+    let uses_any = |ops: &[OpcodeId]| ops.iter().any(|op| opcodes.contains(op));
     FixedTableTag::iter()
-        .filter(|t| {
-            !matches!(
-                t,
-                FixedTableTag::BitwiseAnd | FixedTableTag::BitwiseOr | FixedTableTag::BitwiseXor
-            ) || need_bitwise_lookup
+        .filter(|tag| match tag {
+            FixedTableTag::Zero
+            | FixedTableTag::Range5
+            | FixedTableTag::Range16
+            | FixedTableTag::Range32
+            | FixedTableTag::Range64
+            | FixedTableTag::Range256
+            | FixedTableTag::Range512
+            | FixedTableTag::Range1024 => true,
+            FixedTableTag::SignByte => uses_any(&[OpcodeId::SIGNEXTEND, OpcodeId::SAR]),
+            FixedTableTag::BitwiseAnd | FixedTableTag::BitwiseOr | FixedTableTag::BitwiseXor => {
+                uses_any(&[OpcodeId::AND, OpcodeId::OR, OpcodeId::XOR, OpcodeId::NOT])
+            }
+            FixedTableTag::Pow2 => uses_any(&[OpcodeId::SHL, OpcodeId::SHR, OpcodeId::SAR]),
+            // Looked up by every opcode step
+            FixedTableTag::ResponsibleOpcode
+            | FixedTableTag::ConstantGasCost
+            | FixedTableTag::OpcodeStack => !opcodes.is_empty(),
         })
         .collect()
 }
@@ -270,7 +310,10 @@ pub(crate) fn detect_fixed_table_tags<F: Field>(block: &Block<F>) -> Vec<FixedTa
 pub mod test {
     use super::*;
     use crate::{
-        evm_circuit::{witness::Block, EvmCircuitConfig},
+        evm_circuit::{
+            witness::{Block, Transaction},
+            EvmCircuitConfig,
+        },
         table::{BlockTable, BytecodeTable, CopyTable, ExpTable, KeccakTable, RwTable, TxTable},
         util::Challenges,
     };
@@ -347,7 +390,21 @@ pub mod test {
         fn synthesize(
             &self,
             config: Self::Config,
+            layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let block = self.block.as_ref().unwrap();
+            self.synthesize_with_txs(config, layouter, &block.txs)
+        }
+    }
+
+    impl<F: Field> EvmCircuit<F> {
+        /// Loads the tables looked up by the EVM circuit, with the tx table
+        /// filled from `txs`, and assigns the block.
+        fn synthesize_with_txs(
+            &self,
+            config: <Self as Circuit<F>>::Config,
             mut layouter: impl Layouter<F>,
+            txs: &[Transaction],
         ) -> Result<(), Error> {
             let block = self.block.as_ref().unwrap();
 
@@ -356,7 +413,7 @@ pub mod test {
 
             config.tx_table.load(
                 &mut layouter,
-                &block.txs,
+                txs,
                 block.circuits_params.max_txs,
                 &challenges,
             )?;
@@ -389,18 +446,80 @@ pub mod test {
             EvmCircuit::<F>::new_dev(block, fixed_table_tags)
         }
     }
+
+    /// EvmCircuit whose tx table is filled from `txs` instead of the txs of
+    /// its block, to check that the tx fields the gadgets are assigned are
+    /// looked up in the tx table.
+    #[derive(Clone, Default)]
+    pub(crate) struct EvmCircuitWithTxTable<F: Field> {
+        pub(crate) circuit: EvmCircuit<F>,
+        pub(crate) txs: Vec<Transaction>,
+    }
+
+    impl<F: Field> Circuit<F> for EvmCircuitWithTxTable<F> {
+        type Config = <EvmCircuit<F> as Circuit<F>>::Config;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            EvmCircuit::<F>::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            self.circuit
+                .synthesize_with_txs(config, layouter, &self.txs)
+        }
+    }
 }
 
 #[cfg(test)]
 mod evm_circuit_stats {
-    use crate::evm_circuit::step::ExecutionState;
-    use crate::test_util::CircuitTestBuilder;
+    use super::{detect_fixed_table_tags, EvmCircuit, FixedTableTag};
+    use crate::evm_circuit::{
+        step::ExecutionState, table::build_fixed_table_rows,
+        util::constraint_builder::ConstraintCounts,
+    };
+    use crate::test_util::{block_from_test_ctx, CircuitTestBuilder};
 
-    use eth_types::{bytecode, evm_types::OpcodeId, geth_types::GethData};
+    use eth_types::{
+        bytecode,
+        evm_types::{GasCost, OpcodeId},
+        geth_types::GethData,
+        Word,
+    };
+    use halo2_proofs::{
+        dev::MockProver,
+        halo2curves::bn256::Fr,
+        plonk::{Circuit, ConstraintSystem, Error},
+    };
 
     use mock::test_ctx::{helpers::*, TestContext};
     use strum::IntoEnumIterator;
 
+    fn range_fixed_table_tags() -> Vec<FixedTableTag> {
+        FixedTableTag::iter()
+            .filter(|tag| {
+                matches!(
+                    tag,
+                    FixedTableTag::Range5
+                        | FixedTableTag::Range16
+                        | FixedTableTag::Range32
+                        | FixedTableTag::Range64
+                        | FixedTableTag::Range256
+                        | FixedTableTag::Range512
+                        | FixedTableTag::Range1024
+                )
+            })
+            .collect()
+    }
+
     #[test]
     pub fn empty_evm_circuit_no_padding() {
         CircuitTestBuilder::new_from_test_ctx(
@@ -418,6 +537,318 @@ mod evm_circuit_stats {
         .run();
     }
 
+    #[test]
+    pub fn short_evm_circuit_with_padding() {
+        // The rows after the last real step are filled with non-last EndBlock
+        // steps that keep the rw_counter and call_id unchanged.
+        let code = bytecode! {
+            PUSH1(0x01)
+            PUSH1(0x02)
+            ADD
+            STOP
+        };
+        CircuitTestBuilder::new_from_test_ctx(
+            TestContext::<2, 1>::new(
+                None,
+                account_0_code_account_1_no_code(code),
+                tx_from_1_to_0,
+                |block, _tx| block.number(0xcafeu64),
+            )
+            .unwrap(),
+        )
+        .block_modifier(Box::new(|block| block.evm_circuit_pad_to = 1 << 12))
+        .run();
+    }
+
+    #[test]
+    pub fn empty_evm_circuit_range_fixed_table_tags() {
+        let block = block_from_test_ctx(
+            TestContext::<0, 0>::new(None, |_| {}, |_, _| {}, |b, _| b).unwrap(),
+        );
+        let k = block.get_test_degree();
+        let circuit = EvmCircuit::<Fr>::new_dev(block, range_fixed_table_tags());
+        let prover = MockProver::<Fr>::run(k, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+
+    #[test]
+    pub fn evm_circuit_missing_fixed_table_tags() {
+        let block = block_from_test_ctx(
+            TestContext::<2, 1>::simple_ctx_with_bytecode(bytecode! {
+                PUSH1(0x01)
+                PUSH1(0x02)
+                ADD
+                STOP
+            })
+            .unwrap(),
+        );
+        let k = block.get_test_degree();
+        // ADD needs the ResponsibleOpcode and ConstantGasCost tags.
+        let circuit = EvmCircuit::<Fr>::new_dev(block, range_fixed_table_tags());
+        assert!(matches!(
+            MockProver::<Fr>::run(k, &circuit, vec![]),
+            Err(Error::Synthesis)
+        ));
+    }
+
+    #[test]
+    pub fn evm_circuit_without_bitwise_table() {
+        let block = block_from_test_ctx(
+            TestContext::<2, 1>::simple_ctx_with_bytecode(bytecode! {
+                PUSH1(0x01)
+                PUSH1(0x02)
+                ADD
+                STOP
+            })
+            .unwrap(),
+        );
+        let k = block.get_test_degree();
+        let circuit = EvmCircuit::<Fr>::new_without_bitwise_table(block);
+        assert!(!circuit
+            .fixed_table_tags
+            .iter()
+            .any(FixedTableTag::is_bitwise));
+        let prover = MockProver::<Fr>::run(k, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+
+    #[test]
+    pub fn evm_circuit_without_bitwise_table_needed() {
+        let block = block_from_test_ctx(
+            TestContext::<2, 1>::simple_ctx_with_bytecode(bytecode! {
+                PUSH1(0x0f)
+                PUSH1(0xf0)
+                AND
+                STOP
+            })
+            .unwrap(),
+        );
+        let k = block.get_test_degree();
+        let circuit = EvmCircuit::<Fr>::new_without_bitwise_table(block);
+        assert!(matches!(
+            MockProver::<Fr>::run(k, &circuit, vec![]),
+            Err(Error::Synthesis)
+        ));
+    }
+
+    #[test]
+    pub fn evm_circuit_bitwise_ops_share_fixed_table() {
+        let block = block_from_test_ctx(
+            TestContext::<2, 1>::simple_ctx_with_bytecode(bytecode! {
+                PUSH1(0x0f)
+                PUSH1(0xf0)
+                AND
+                PUSH1(0x0f)
+                OR
+                PUSH1(0xff)
+                XOR
+                NOT
+                STOP
+            })
+            .unwrap(),
+        );
+        // A single set of rows per bitwise tag, 2^16 for every pair of bytes,
+        // is loaded for all four ops, NOT looking up the BitwiseXor rows.
+        let fixed_table_tags = detect_fixed_table_tags(&block);
+        let bitwise_tags = [
+            FixedTableTag::BitwiseAnd,
+            FixedTableTag::BitwiseOr,
+            FixedTableTag::BitwiseXor,
+        ];
+        for tag in bitwise_tags {
+            assert_eq!(
+                fixed_table_tags.iter().filter(|t| **t == tag).count(),
+                1,
+                "{:?} is not loaded once",
+                tag
+            );
+        }
+        let other_tags: Vec<_> = fixed_table_tags
+            .iter()
+            .filter(|tag| !tag.is_bitwise())
+            .copied()
+            .collect();
+        assert_eq!(
+            build_fixed_table_rows::<Fr>(&fixed_table_tags).len(),
+            build_fixed_table_rows::<Fr>(&other_tags).len() + bitwise_tags.len() * (1 << 16)
+        );
+
+        let k = block.get_test_degree();
+        let circuit = EvmCircuit::<Fr>::new_dev(block, fixed_table_tags);
+        let prover = MockProver::<Fr>::run(k, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+
+    #[test]
+    pub fn fixed_table_rows_built_in_parallel_keep_order() {
+        let fixed_table_tags: Vec<_> = FixedTableTag::iter().collect();
+        let rows: Vec<[Fr; 4]> = fixed_table_tags
+            .iter()
+            .flat_map(|tag| tag.build())
+            .collect();
+        assert_eq!(build_fixed_table_rows(&fixed_table_tags), rows);
+    }
+
+    #[test]
+    pub fn scmp_constraint_counts() {
+        let mut cs = ConstraintSystem::<Fr>::default();
+        let (config, _) = EvmCircuit::<Fr>::configure(&mut cs);
+        let counts = config.execution.constraint_counts_map[&ExecutionState::SCMP];
+        println!("SCMP: {:?}", counts);
+
+        // 13 constraints from the math gadgets and the copy of a_lt_b, 12 from
+        // SameContextGadget (gas range check and state transition) and 1 for
+        // the step height. The lookups are the opcode, responsible opcode and
+        // the 3 stack lookups.
+        assert_eq!(
+            counts,
+            ConstraintCounts {
+                constraints: 26,
+                lookups: 5,
+            }
+        );
+    }
+
+    #[test]
+    pub fn execution_states_have_one_gadget() {
+        // Configuring two gadgets with the same execution state panics, so it's
+        // enough to check that every state has been configured.
+        let mut cs = ConstraintSystem::<Fr>::default();
+        let (config, _) = EvmCircuit::<Fr>::configure(&mut cs);
+        let missing_states = ExecutionState::iter()
+            .filter(|state| !config.execution.height_map.contains_key(state))
+            .collect::<Vec<_>>();
+        assert!(
+            missing_states.is_empty(),
+            "execution states without gadget: {:?}",
+            missing_states
+        );
+        assert_eq!(
+            config.execution.height_map.len(),
+            ExecutionState::iter().count()
+        );
+    }
+
+    /// Execution states whose gadget only charges the constant gas of the
+    /// executed opcode.
+    const CONSTANT_GAS_STATES: &[ExecutionState] = &[
+        ExecutionState::ADD_SUB,
+        ExecutionState::ADDMOD,
+        ExecutionState::ADDRESS,
+        ExecutionState::BITWISE,
+        ExecutionState::BLOCKCTXU64,
+        ExecutionState::BLOCKCTXU160,
+        ExecutionState::BLOCKCTXU256,
+        ExecutionState::BLOCKHASH,
+        ExecutionState::BYTE,
+        ExecutionState::CALLDATACOPY,
+        ExecutionState::CALLDATALOAD,
+        ExecutionState::CALLDATASIZE,
+        ExecutionState::CALLER,
+        ExecutionState::CALLVALUE,
+        ExecutionState::CHAINID,
+        ExecutionState::CODECOPY,
+        ExecutionState::CODESIZE,
+        ExecutionState::CMP,
+        ExecutionState::DUP,
+        ExecutionState::EXP,
+        ExecutionState::GAS,
+        ExecutionState::GASPRICE,
+        ExecutionState::ISZERO,
+        ExecutionState::JUMP,
+        ExecutionState::JUMPDEST,
+        ExecutionState::JUMPI,
+        ExecutionState::MEMORY,
+        ExecutionState::MSIZE,
+        ExecutionState::MUL_DIV_MOD,
+        ExecutionState::MULMOD,
+        ExecutionState::NOT,
+        ExecutionState::ORIGIN,
+        ExecutionState::PC,
+        ExecutionState::POP,
+        ExecutionState::PUSH,
+        ExecutionState::RETURNDATACOPY,
+        ExecutionState::RETURNDATASIZE,
+        ExecutionState::SAR,
+        ExecutionState::SDIV_SMOD,
+        ExecutionState::SELFBALANCE,
+        ExecutionState::SHA3,
+        ExecutionState::SHL_SHR,
+        ExecutionState::SCMP,
+        ExecutionState::SIGNEXTEND,
+        ExecutionState::SWAP,
+    ];
+
+    #[test]
+    pub fn constant_gas_matches_responsible_opcodes() {
+        for state in CONSTANT_GAS_STATES {
+            // Expand the memory to a word first, so that the memory opcodes
+            // don't pay for an expansion.
+            let mut code = bytecode! {
+                PUSH1(0)
+                PUSH1(0)
+                MSTORE
+            };
+            let mut opcode_pcs = Vec::new();
+            for opcode in state.responsible_opcodes() {
+                // Zero operands, deep enough for SWAP16, don't take the
+                // JUMPI nor copy or hash any byte.
+                for _ in 0..17 {
+                    code.push(1, Word::zero());
+                }
+                if opcode == OpcodeId::JUMP {
+                    let dest = code.code().len() + 4;
+                    code.push(2, Word::from(dest));
+                }
+                opcode_pcs.push((code.code().len() as u64, opcode));
+                if opcode.is_push() {
+                    code.push(opcode.data_len() as u8, Word::zero());
+                } else {
+                    code.write_op(opcode);
+                }
+                if opcode == OpcodeId::JUMP {
+                    code.write_op(OpcodeId::JUMPDEST);
+                }
+            }
+            code.write_op(OpcodeId::STOP);
+
+            let block = block_from_test_ctx(
+                TestContext::<2, 1>::new(
+                    None,
+                    account_0_code_account_1_no_code(code),
+                    tx_from_1_to_0,
+                    |block, _tx| block.number(0xcafeu64),
+                )
+                .unwrap(),
+            );
+            let steps = &block.txs[0].steps;
+            for (pc, opcode) in opcode_pcs {
+                let idx = steps
+                    .iter()
+                    .position(|step| step.program_counter == pc)
+                    .unwrap_or_else(|| panic!("{:?} is not executed", opcode));
+                assert_eq!(steps[idx].opcode, Some(opcode));
+                assert_eq!(steps[idx].execution_state, *state);
+                assert_eq!(
+                    steps[idx].gas_left - steps[idx + 1].gas_left,
+                    opcode.constant_gas_cost().as_u64(),
+                    "{:?} doesn't charge the constant gas of {:?}",
+                    state,
+                    opcode
+                );
+            }
+            // The gadget constrains the gas_left delta of every step to the
+            // gas it charges, so the circuit only verifies if it's the
+            // constant gas checked above.
+            CircuitTestBuilder::<2, 1>::new_from_block(block).run();
+        }
+        // Spot check some canonical values.
+        assert_eq!(OpcodeId::SLT.constant_gas_cost(), GasCost::FASTEST);
+        assert_eq!(OpcodeId::SGT.constant_gas_cost().as_u64(), 3);
+        assert_eq!(OpcodeId::MUL.constant_gas_cost(), GasCost::FAST);
+        assert_eq!(OpcodeId::ADDMOD.constant_gas_cost(), GasCost::MID);
+    }
+
     /// This function prints to stdout a table with all the implemented states
     /// and their responsible opcodes with the following stats:
     /// - height: number of rows in the EVM circuit used by the execution state
@@ -5,7 +5,7 @@ use crate::{
         step::{ExecutionState, Step},
         table::Table,
         util::{
-            constraint_builder::{BaseConstraintBuilder, ConstraintBuilder},
+            constraint_builder::{BaseConstraintBuilder, ConstraintBuilder, ConstraintCounts},
             rlc, CellType,
         },
         witness::{Block, Call, ExecStep, Transaction},
@@ -13,7 +13,7 @@ use crate::{
     table::LookupTable,
     util::{query_expression, Challenges, Expr},
 };
-use eth_types::{evm_unimplemented, Field};
+use eth_types::Field;
 use gadgets::util::not;
 use halo2_proofs::{
     arithmetic::FieldExt,
@@ -57,13 +57,16 @@ mod error_invalid_jump;
 mod error_oog_call;
 mod error_oog_constant;
 mod error_oog_static_memory;
+mod error_return_data_oo_bound;
 mod error_stack;
+mod error_write_protection;
 mod exp;
 mod extcodecopy;
 mod extcodehash;
 mod extcodesize;
 mod gas;
 mod gasprice;
+mod invalid_tx;
 mod is_zero;
 mod jump;
 mod jumpdest;
@@ -121,13 +124,17 @@ use end_tx::EndTxGadget;
 use error_invalid_jump::ErrorInvalidJumpGadget;
 use error_oog_call::ErrorOOGCallGadget;
 use error_oog_constant::ErrorOOGConstantGadget;
+use error_oog_static_memory::ErrorOOGStaticMemoryGadget;
+use error_return_data_oo_bound::ErrorReturnDataOutOfBoundGadget;
 use error_stack::ErrorStackGadget;
+use error_write_protection::ErrorWriteProtectionGadget;
 use exp::ExponentiationGadget;
 use extcodecopy::ExtcodecopyGadget;
 use extcodehash::ExtcodehashGadget;
 use extcodesize::ExtcodesizeGadget;
 use gas::GasGadget;
 use gasprice::GasPriceGadget;
+use invalid_tx::InvalidTxGadget;
 use is_zero::IsZeroGadget;
 use jump::JumpGadget;
 use jumpdest::JumpdestGadget;
@@ -194,11 +201,13 @@ pub(crate) struct ExecutionConfig<F> {
     advices: [Column<Advice>; STEP_WIDTH],
     step: Step<F>,
     pub(crate) height_map: HashMap<ExecutionState, usize>,
+    pub(crate) constraint_counts_map: HashMap<ExecutionState, ConstraintCounts>,
     stored_expressions_map: HashMap<ExecutionState, Vec<StoredExpression<F>>>,
     // internal state gadgets
     begin_tx_gadget: BeginTxGadget<F>,
     end_block_gadget: EndBlockGadget<F>,
     end_tx_gadget: EndTxGadget<F>,
+    invalid_tx_gadget: InvalidTxGadget<F>,
     // opcode gadgets
     add_sub_gadget: AddSubGadget<F>,
     addmod_gadget: AddModGadget<F>,
@@ -261,8 +270,7 @@ pub(crate) struct ExecutionConfig<F> {
     // error gadgets
     error_oog_call: ErrorOOGCallGadget<F>,
     error_oog_constant: ErrorOOGConstantGadget<F>,
-    error_oog_static_memory_gadget:
-        DummyGadget<F, 0, 0, { ExecutionState::ErrorOutOfGasStaticMemoryExpansion }>,
+    error_oog_static_memory_gadget: ErrorOOGStaticMemoryGadget<F>,
     error_stack: ErrorStackGadget<F>,
     error_oog_dynamic_memory_gadget:
         DummyGadget<F, 0, 0, { ExecutionState::ErrorOutOfGasDynamicMemoryExpansion }>,
@@ -283,12 +291,14 @@ pub(crate) struct ExecutionConfig<F> {
     error_insufficient_balance: DummyGadget<F, 0, 0, { ExecutionState::ErrorInsufficientBalance }>,
     error_invalid_jump: ErrorInvalidJumpGadget<F>,
     error_depth: DummyGadget<F, 0, 0, { ExecutionState::ErrorDepth }>,
-    error_write_protection: DummyGadget<F, 0, 0, { ExecutionState::ErrorWriteProtection }>,
+    error_write_protection: ErrorWriteProtectionGadget<F>,
     error_contract_address_collision:
         DummyGadget<F, 0, 0, { ExecutionState::ErrorContractAddressCollision }>,
+    error_nonce_uint_overflow: DummyGadget<F, 0, 0, { ExecutionState::ErrorNonceUintOverflow }>,
     error_invalid_creation_code: DummyGadget<F, 0, 0, { ExecutionState::ErrorInvalidCreationCode }>,
-    error_return_data_out_of_bound:
-        DummyGadget<F, 0, 0, { ExecutionState::ErrorReturnDataOutOfBound }>,
+    error_max_code_size_exceeded:
+        DummyGadget<F, 0, 0, { ExecutionState::ErrorMaxCodeSizeExceeded }>,
+    error_return_data_out_of_bound: ErrorReturnDataOutOfBoundGadget<F>,
     invalid_opcode_gadget: DummyGadget<F, 0, 0, { ExecutionState::ErrorInvalidOpcode }>,
 }
 
@@ -347,10 +357,13 @@ impl<F: Field> ExecutionConfig<F> {
 
             // NEW: Enabled, this will break hand crafted tests, maybe we can remove them?
             let first_step_check = {
-                let begin_tx_end_block_selector = step_curr
-                    .execution_state_selector([ExecutionState::BeginTx, ExecutionState::EndBlock]);
+                let begin_tx_end_block_selector = step_curr.execution_state_selector([
+                    ExecutionState::BeginTx,
+                    ExecutionState::InvalidTx,
+                    ExecutionState::EndBlock,
+                ]);
                 iter::once((
-                    "First step should be BeginTx or EndBlock",
+                    "First step should be BeginTx, InvalidTx or EndBlock",
                     q_step_first * (1.expr() - begin_tx_end_block_selector),
                 ))
             };
@@ -419,6 +432,7 @@ impl<F: Field> ExecutionConfig<F> {
         });
 
         let mut stored_expressions_map = HashMap::new();
+        let mut constraint_counts_map = HashMap::new();
 
         let step_next = Step::new(meta, advices, MAX_STEP_HEIGHT, true);
         macro_rules! configure_gadget {
@@ -436,6 +450,7 @@ impl<F: Field> ExecutionConfig<F> {
                     &step_next,
                     &mut height_map,
                     &mut stored_expressions_map,
+                    &mut constraint_counts_map,
                 )
             };
         }
@@ -455,6 +470,7 @@ impl<F: Field> ExecutionConfig<F> {
             begin_tx_gadget: configure_gadget!(),
             end_block_gadget: configure_gadget!(),
             end_tx_gadget: configure_gadget!(),
+            invalid_tx_gadget: configure_gadget!(),
             // opcode gadgets
             add_sub_gadget: configure_gadget!(),
             addmod_gadget: configure_gadget!(),
@@ -539,12 +555,15 @@ impl<F: Field> ExecutionConfig<F> {
             error_write_protection: configure_gadget!(),
             error_depth: configure_gadget!(),
             error_contract_address_collision: configure_gadget!(),
+            error_nonce_uint_overflow: configure_gadget!(),
             error_invalid_creation_code: configure_gadget!(),
+            error_max_code_size_exceeded: configure_gadget!(),
             error_return_data_out_of_bound: configure_gadget!(),
             invalid_opcode_gadget: configure_gadget!(),
             // step and presets
             step: step_curr,
             height_map,
+            constraint_counts_map,
             stored_expressions_map,
         };
 
@@ -579,6 +598,7 @@ impl<F: Field> ExecutionConfig<F> {
         step_next: &Step<F>,
         height_map: &mut HashMap<ExecutionState, usize>,
         stored_expressions_map: &mut HashMap<ExecutionState, Vec<StoredExpression<F>>>,
+        constraint_counts_map: &mut HashMap<ExecutionState, ConstraintCounts>,
     ) -> G {
         // Configure the gadget with the max height first so we can find out the actual
         // height
@@ -616,15 +636,16 @@ impl<F: Field> ExecutionConfig<F> {
         );
 
         let (constraints, stored_expressions, _) = cb.build();
-        debug_assert!(
+        assert!(
             !height_map.contains_key(&G::EXECUTION_STATE),
-            "execution state already configured"
+            "execution state {:?} already configured",
+            G::EXECUTION_STATE
         );
 
         height_map.insert(G::EXECUTION_STATE, height);
-        debug_assert!(
-            !stored_expressions_map.contains_key(&G::EXECUTION_STATE),
-            "execution state already configured"
+        constraint_counts_map.insert(
+            G::EXECUTION_STATE,
+            ConstraintCounts::new(&constraints, &stored_expressions),
         );
         stored_expressions_map.insert(G::EXECUTION_STATE, stored_expressions);
 
@@ -667,9 +688,22 @@ impl<F: Field> ExecutionConfig<F> {
                 .chain(
                     IntoIterator::into_iter([
                         (
-                            "EndTx can only transit to BeginTx or EndBlock",
+                            "EndTx can only transit to BeginTx, InvalidTx or EndBlock",
                             ExecutionState::EndTx,
-                            vec![ExecutionState::BeginTx, ExecutionState::EndBlock],
+                            vec![
+                                ExecutionState::BeginTx,
+                                ExecutionState::InvalidTx,
+                                ExecutionState::EndBlock,
+                            ],
+                        ),
+                        (
+                            "InvalidTx can only transit to BeginTx, InvalidTx or EndBlock",
+                            ExecutionState::InvalidTx,
+                            vec![
+                                ExecutionState::BeginTx,
+                                ExecutionState::InvalidTx,
+                                ExecutionState::EndBlock,
+                            ],
                         ),
                         (
                             "EndBlock can only transit to EndBlock",
@@ -683,9 +717,14 @@ impl<F: Field> ExecutionConfig<F> {
                 .chain(
                     IntoIterator::into_iter([
                         (
-                            "Only EndTx can transit to BeginTx",
+                            "Only EndTx or InvalidTx can transit to BeginTx",
                             ExecutionState::BeginTx,
-                            vec![ExecutionState::EndTx],
+                            vec![ExecutionState::EndTx, ExecutionState::InvalidTx],
+                        ),
+                        (
+                            "Only EndTx or InvalidTx can transit to InvalidTx",
+                            ExecutionState::InvalidTx,
+                            vec![ExecutionState::EndTx, ExecutionState::InvalidTx],
                         ),
                         (
                             "Only ExecutionState which halts or BeginTx can transit to EndTx",
@@ -696,9 +735,13 @@ impl<F: Field> ExecutionConfig<F> {
                                 .collect(),
                         ),
                         (
-                            "Only EndTx or EndBlock can transit to EndBlock",
+                            "Only EndTx, InvalidTx or EndBlock can transit to EndBlock",
                             ExecutionState::EndBlock,
-                            vec![ExecutionState::EndTx, ExecutionState::EndBlock],
+                            vec![
+                                ExecutionState::EndTx,
+                                ExecutionState::InvalidTx,
+                                ExecutionState::EndBlock,
+                            ],
                         ),
                     ])
                     .filter(move |(_, _, from)| !from.contains(&G::EXECUTION_STATE))
@@ -1058,6 +1101,7 @@ impl<F: Field> ExecutionConfig<F> {
             // internal states
             ExecutionState::BeginTx => assign_exec_step!(self.begin_tx_gadget),
             ExecutionState::EndTx => assign_exec_step!(self.end_tx_gadget),
+            ExecutionState::InvalidTx => assign_exec_step!(self.invalid_tx_gadget),
             ExecutionState::EndBlock => assign_exec_step!(self.end_block_gadget),
             // opcode
             ExecutionState::ADD_SUB => assign_exec_step!(self.add_sub_gadget),
@@ -1195,9 +1239,15 @@ impl<F: Field> ExecutionConfig<F> {
             ExecutionState::ErrorContractAddressCollision => {
                 assign_exec_step!(self.error_contract_address_collision)
             }
+            ExecutionState::ErrorNonceUintOverflow => {
+                assign_exec_step!(self.error_nonce_uint_overflow)
+            }
             ExecutionState::ErrorInvalidCreationCode => {
                 assign_exec_step!(self.error_invalid_creation_code)
             }
+            ExecutionState::ErrorMaxCodeSizeExceeded => {
+                assign_exec_step!(self.error_max_code_size_exceeded)
+            }
             ExecutionState::ErrorReturnDataOutOfBound => {
                 assign_exec_step!(self.error_return_data_out_of_bound)
             }
@@ -1205,8 +1255,6 @@ impl<F: Field> ExecutionConfig<F> {
             ExecutionState::ErrorInvalidOpcode => {
                 assign_exec_step!(self.invalid_opcode_gadget)
             }
-
-            _ => evm_unimplemented!("unimplemented ExecutionState: {:?}", step.execution_state),
         }
 
         // Fill in the witness values for stored expressions
@@ -1,7 +1,7 @@
 use crate::{
     evm_circuit::{
         execution::ExecutionGadget,
-        param::N_BYTES_GAS,
+        param::{N_BYTES_ACCOUNT_ADDRESS, N_BYTES_GAS},
         step::ExecutionState,
         util::{
             common_gadget::TransferWithGasFeeGadget,
@@ -9,15 +9,18 @@ use crate::{
                 ConstraintBuilder, ReversionInfo, StepStateTransition,
                 Transition::{Delta, To},
             },
+            from_bytes,
             math_gadget::{IsEqualGadget, IsZeroGadget, MulWordByU64Gadget, RangeCheckGadget},
-            not, select, CachedRegion, Cell, Word,
+            not, or,
+            precompile_gadget::PrecompileGadget,
+            select, CachedRegion, Cell, Word,
         },
         witness::{Block, Call, ExecStep, Transaction},
     },
     table::{AccountFieldTag, CallContextFieldTag, TxFieldTag as TxContextFieldTag},
     util::Expr,
 };
-use eth_types::{evm_types::GasCost, Field, ToLittleEndian, ToScalar};
+use eth_types::{evm_types::GasCost, Field, ToLittleEndian, ToScalar, ToWord};
 use halo2_proofs::circuit::Value;
 use halo2_proofs::plonk::Error;
 
@@ -25,12 +28,14 @@ use halo2_proofs::plonk::Error;
 pub(crate) struct BeginTxGadget<F> {
     tx_id: Cell<F>,
     tx_nonce: Cell<F>,
+    is_nonce_max: IsEqualGadget<F>,
     tx_gas: Cell<F>,
     tx_gas_price: Word<F>,
     mul_gas_fee_by_gas: MulWordByU64Gadget<F>,
     tx_caller_address: Cell<F>,
     tx_caller_address_is_zero: IsZeroGadget<F>,
     tx_callee_address: Cell<F>,
+    tx_callee_address_bytes: [Cell<F>; N_BYTES_ACCOUNT_ADDRESS],
     tx_is_create: Cell<F>,
     tx_value: Word<F>,
     tx_call_data_length: Cell<F>,
@@ -40,6 +45,7 @@ pub(crate) struct BeginTxGadget<F> {
     transfer_with_gas_fee: TransferWithGasFeeGadget<F>,
     phase2_code_hash: Cell<F>,
     is_empty_code_hash: IsEqualGadget<F>,
+    precompile: PrecompileGadget<F>,
 }
 
 impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
@@ -100,6 +106,10 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
             tx_nonce.expr(),
             None,
         );
+        // The nonce can't be increased past 2^64 - 1 (EIP-2681), such a tx is
+        // rejected and handled by InvalidTx instead.
+        let is_nonce_max = IsEqualGadget::construct(cb, tx_nonce.expr(), u64::MAX.expr());
+        cb.require_zero("tx nonce < 2^64 - 1", is_nonce_max.expr());
 
         // TODO: Implement EIP 1559 (currently it only supports legacy
         // transaction format)
@@ -147,7 +157,6 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
         );
 
         // TODO: Handle creation transaction
-        // TODO: Handle precompiled
 
         // Read code_hash of callee
         let phase2_code_hash = cb.query_cell_phase2();
@@ -162,6 +171,22 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
         let is_empty_code_hash =
             IsEqualGadget::construct(cb, phase2_code_hash.expr(), cb.empty_hash_rlc());
 
+        // Precompiled contracts run without entering a call frame, like
+        // accounts with empty code, reading their input from the tx call data.
+        let tx_callee_address_bytes = cb.query_bytes();
+        cb.require_equal(
+            "tx_callee_address_bytes are the bytes of tx_callee_address",
+            from_bytes::expr(&tx_callee_address_bytes),
+            tx_callee_address.expr(),
+        );
+        let precompile = PrecompileGadget::construct(
+            cb,
+            &tx_callee_address_bytes,
+            tx_call_data_length.expr(),
+            gas_left.clone(),
+        );
+        let is_precompile = precompile.is_precompile() * not::expr(tx_is_create.expr());
+
         cb.condition(is_empty_code_hash.expr(), |cb| {
             cb.require_equal(
                 "Tx to account with empty code should be persistent",
@@ -192,7 +217,38 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
             });
         });
 
-        cb.condition(1.expr() - is_empty_code_hash.expr(), |cb| {
+        cb.condition(is_precompile.clone(), |cb| {
+            // TODO: Handle failed tx to precompiled, which consumes all the gas
+            // and reverts the value transfer.
+            cb.require_equal(
+                "Tx to precompiled succeeds",
+                precompile.is_success(),
+                1.expr(),
+            );
+            cb.require_equal(
+                "Tx to precompiled should be persistent",
+                reversion_info.is_persistent(),
+                1.expr(),
+            );
+            cb.require_equal(
+                "Go to EndTx when Tx to precompiled",
+                cb.next.execution_state_selector([ExecutionState::EndTx]),
+                1.expr(),
+            );
+
+            cb.require_step_state_transition(StepStateTransition {
+                // 10 reads and writes, the same as a Tx to account with empty
+                // code.
+                rw_counter: Delta(10.expr()),
+                call_id: To(call_id.expr()),
+                gas_left: To(gas_left.clone() - precompile.gas_used()),
+                ..StepStateTransition::any()
+            });
+        });
+
+        // Txs to accounts with empty code and to precompiled end right away.
+        let no_callee_frame = or::expr([is_empty_code_hash.expr(), is_precompile]);
+        cb.condition(not::expr(no_callee_frame), |cb| {
             // Setup first call's context.
             for (field_tag, value) in [
                 (CallContextFieldTag::Depth, 1.expr()),
@@ -255,12 +311,14 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
         Self {
             tx_id,
             tx_nonce,
+            is_nonce_max,
             tx_gas,
             tx_gas_price,
             mul_gas_fee_by_gas,
             tx_caller_address,
             tx_caller_address_is_zero,
             tx_callee_address,
+            tx_callee_address_bytes,
             tx_is_create,
             tx_value,
             tx_call_data_length,
@@ -270,6 +328,7 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
             transfer_with_gas_fee,
             phase2_code_hash,
             is_empty_code_hash,
+            precompile,
         }
     }
 
@@ -295,6 +354,8 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
             .assign(region, offset, Value::known(F::from(tx.id as u64)))?;
         self.tx_nonce
             .assign(region, offset, Value::known(F::from(tx.nonce)))?;
+        self.is_nonce_max
+            .assign(region, offset, F::from(tx.nonce), F::from(u64::MAX))?;
         self.tx_gas
             .assign(region, offset, Value::known(F::from(tx.gas)))?;
         self.tx_gas_price
@@ -336,8 +397,16 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
             call.rw_counter_end_of_reversion,
             call.is_persistent,
         )?;
+        // The step gas cost also includes the cost of the precompiled for a tx
+        // to precompiled.
+        let intrinsic_gas_cost = if tx.is_create {
+            GasCost::CREATION_TX.as_u64()
+        } else {
+            GasCost::TX.as_u64()
+        } + tx.call_data_gas_cost;
+        let gas_left = tx.gas - intrinsic_gas_cost;
         self.sufficient_gas_left
-            .assign(region, offset, F::from(tx.gas - step.gas_cost))?;
+            .assign(region, offset, F::from(gas_left))?;
         self.transfer_with_gas_fee.assign(
             region,
             offset,
@@ -354,15 +423,39 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
             region.word_rlc(callee_code_hash),
             region.empty_hash_rlc(),
         )?;
+
+        for (cell, byte) in self
+            .tx_callee_address_bytes
+            .iter()
+            .zip(tx.callee_address.as_bytes().iter().rev())
+        {
+            cell.assign(region, offset, Value::known(F::from(*byte as u64)))?;
+        }
+        self.precompile.assign(
+            region,
+            offset,
+            tx.callee_address.to_word(),
+            tx.call_data_length as u64,
+            gas_left,
+        )?;
         Ok(())
     }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::{evm_circuit::test::rand_bytes, test_util::CircuitTestBuilder};
-    use bus_mapping::evm::OpcodeId;
-    use eth_types::{self, bytecode, evm_types::GasCost, word, Bytecode, Word};
+    use crate::{
+        evm_circuit::{step::ExecutionState, test::rand_bytes},
+        test_util::{block_from_test_ctx, builder_from_test_ctx, CircuitTestBuilder},
+        witness::block_convert,
+    };
+    use bus_mapping::{circuit_input_builder::CircuitsParams, evm::OpcodeId, Error};
+    use eth_types::{
+        self, bytecode,
+        evm_types::{GasCost, PRECOMPILE_BN256_ADD, PRECOMPILE_COUNT, PRECOMPILE_IDENTITY},
+        word, Address, Bytecode, Word,
+    };
+    use halo2_proofs::halo2curves::bn256::Fr;
 
     use mock::{eth, gwei, TestContext, MOCK_ACCOUNTS};
 
@@ -553,6 +646,116 @@ mod test {
         CircuitTestBuilder::new_from_test_ctx(ctx).run();
     }
 
+    #[test]
+    fn begin_tx_precompile() {
+        let ctx = TestContext::<1, 1>::new(
+            None,
+            |accs| {
+                accs[0].address(MOCK_ACCOUNTS[0]).balance(eth(20));
+            },
+            |mut txs, _accs| {
+                txs[0]
+                    .from(MOCK_ACCOUNTS[0])
+                    .to(Address::from_low_u64_be(PRECOMPILE_IDENTITY))
+                    .gas_price(gwei(2))
+                    .gas(Word::from(0x10000))
+                    .value(eth(2))
+                    .input(vec![1, 2, 3, 4, 0, 0, 0, 0].into());
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap();
+
+        let block = block_from_test_ctx(ctx);
+
+        // The identity precompiled runs right in BeginTx, which goes to EndTx
+        // with the cost of one word deducted on top of the intrinsic gas.
+        let steps = &block.txs[0].steps;
+        assert_eq!(
+            steps
+                .iter()
+                .map(|step| step.execution_state)
+                .collect::<Vec<_>>(),
+            vec![ExecutionState::BeginTx, ExecutionState::EndTx]
+        );
+        assert_eq!(
+            steps[1].gas_left,
+            0x10000
+                - GasCost::TX.as_u64()
+                - 4 * 16
+                - 4 * 4
+                - GasCost::PRECOMPILE_IDENTITY_BASE.as_u64()
+                - GasCost::PRECOMPILE_IDENTITY_PER_WORD.as_u64()
+        );
+
+        CircuitTestBuilder::<1, 1>::new_from_block(block).run();
+    }
+
+    #[test]
+    fn begin_tx_account_after_precompiles() {
+        let ctx = TestContext::<1, 1>::new(
+            None,
+            |accs| {
+                accs[0].address(MOCK_ACCOUNTS[0]).balance(eth(20));
+            },
+            |mut txs, _accs| {
+                txs[0]
+                    .from(MOCK_ACCOUNTS[0])
+                    .to(Address::from_low_u64_be(PRECOMPILE_COUNT + 1))
+                    .gas_price(gwei(2))
+                    .gas(Word::from(0x10000))
+                    .value(eth(2));
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap();
+
+        let block = block_from_test_ctx(ctx);
+
+        // `0x0a` is an account with empty code, which ends right away with
+        // only the intrinsic gas charged.
+        let steps = &block.txs[0].steps;
+        assert_eq!(
+            steps
+                .iter()
+                .map(|step| step.execution_state)
+                .collect::<Vec<_>>(),
+            vec![ExecutionState::BeginTx, ExecutionState::EndTx]
+        );
+        assert_eq!(steps[1].gas_left, 0x10000 - GasCost::TX.as_u64());
+
+        CircuitTestBuilder::<1, 1>::new_from_block(block).run();
+    }
+
+    #[test]
+    fn begin_tx_unsupported_precompile() {
+        let address = Address::from_low_u64_be(PRECOMPILE_BN256_ADD);
+        let ctx = TestContext::<1, 1>::new(
+            None,
+            |accs| {
+                accs[0].address(MOCK_ACCOUNTS[0]).balance(eth(20));
+            },
+            |mut txs, _accs| {
+                txs[0]
+                    .from(MOCK_ACCOUNTS[0])
+                    .to(address)
+                    .gas_price(gwei(2))
+                    .gas(Word::from(0x10000))
+                    .input(vec![0; 128].into());
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap();
+        let builder = builder_from_test_ctx(ctx, CircuitsParams::default());
+
+        // The PrecompileGadget only proves the identity, so the tx is rejected
+        // before reaching the circuit.
+        match block_convert::<Fr>(&builder.block, &builder.code_db).unwrap_err() {
+            Error::UnsupportedPrecompiles(addresses) => assert_eq!(addresses, vec![address]),
+            err => panic!("unexpected error {:?}", err),
+        }
+    }
+
     // TODO: Enable this test once we have support for contract deployment from
     // BeginTx.
     #[ignore]
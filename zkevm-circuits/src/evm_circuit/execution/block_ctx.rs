@@ -15,7 +15,7 @@ use crate::{
 };
 use bus_mapping::evm::OpcodeId;
 use eth_types::Field;
-use eth_types::ToLittleEndian;
+use eth_types::{ToLittleEndian, Word};
 use halo2_proofs::plonk::Error;
 
 #[derive(Clone, Debug)]
@@ -62,6 +62,21 @@ impl<F: Field, const N_BYTES: usize> BlockCtxGadget<F, N_BYTES> {
     }
 }
 
+/// Value of the block field pushed by the opcode of `step`, from the block
+/// context the block table is assigned with.
+fn block_field_value<F: Field>(block: &Block<F>, step: &ExecStep) -> Word {
+    let tag = match step.opcode.unwrap() {
+        OpcodeId::COINBASE => BlockContextFieldTag::Coinbase,
+        OpcodeId::TIMESTAMP => BlockContextFieldTag::Timestamp,
+        OpcodeId::NUMBER => BlockContextFieldTag::Number,
+        OpcodeId::DIFFICULTY => BlockContextFieldTag::Difficulty,
+        OpcodeId::GASLIMIT => BlockContextFieldTag::GasLimit,
+        OpcodeId::BASEFEE => BlockContextFieldTag::BaseFee,
+        opcode => unreachable!("{:?} doesn't push a block field", opcode),
+    };
+    block.context.value(tag).unwrap()
+}
+
 #[derive(Clone, Debug)]
 pub(crate) struct BlockCtxU64Gadget<F> {
     value_u64: BlockCtxGadget<F, N_BYTES_U64>,
@@ -91,7 +106,7 @@ impl<F: Field> ExecutionGadget<F> for BlockCtxU64Gadget<F> {
             .same_context
             .assign_exec_step(region, offset, step)?;
 
-        let value = block.rws[step.rw_indices[0]].stack_value();
+        let value = block_field_value(block, step);
 
         self.value_u64.value.assign(
             region,
@@ -132,7 +147,7 @@ impl<F: Field> ExecutionGadget<F> for BlockCtxU160Gadget<F> {
             .same_context
             .assign_exec_step(region, offset, step)?;
 
-        let value = block.rws[step.rw_indices[0]].stack_value();
+        let value = block_field_value(block, step);
 
         self.value_u160.value.assign(
             region,
@@ -177,7 +192,7 @@ impl<F: Field> ExecutionGadget<F> for BlockCtxU256Gadget<F> {
             .same_context
             .assign_exec_step(region, offset, step)?;
 
-        let value = block.rws[step.rw_indices[0]].stack_value();
+        let value = block_field_value(block, step);
 
         self.value_u256
             .value
@@ -189,9 +204,19 @@ impl<F: Field> ExecutionGadget<F> for BlockCtxU256Gadget<F> {
 
 #[cfg(test)]
 mod test {
-    use crate::test_util::CircuitTestBuilder;
-    use eth_types::bytecode;
-    use mock::TestContext;
+    use crate::{
+        test_util::{builder_from_test_ctx, CircuitTestBuilder},
+        witness::block_convert,
+    };
+    use bus_mapping::{
+        circuit_input_builder::{CircuitInputBuilder, CircuitsParams, ExecState},
+        error::ExecError,
+        evm::OpcodeId,
+        Error,
+    };
+    use eth_types::{bytecode, evm_types::HardFork, Hash, ToWord, Word};
+    use halo2_proofs::halo2curves::bn256::Fr;
+    use mock::test_ctx::{helpers::*, TestContext};
 
     fn test_ok(bytecode: bytecode::Bytecode) {
         CircuitTestBuilder::new_from_test_ctx(
@@ -231,4 +256,101 @@ mod test {
         };
         test_ok(bytecode);
     }
+
+    #[test]
+    fn basefee_pre_london_is_invalid_opcode() {
+        let ctx = TestContext::<2, 1>::new_with_hardfork(
+            None,
+            account_0_code_account_1_no_code(bytecode! {
+                BASEFEE
+                STOP
+            }),
+            tx_from_1_to_0,
+            |block, _tx| block,
+            HardFork::Berlin,
+        )
+        .unwrap();
+
+        // BASEFEE doesn't exist before London (EIP-3198), so it halts the tx.
+        let builder = builder_from_test_ctx(ctx, CircuitsParams::default());
+        let step = builder.block.txs()[0]
+            .steps()
+            .iter()
+            .find(|step| step.exec_state == ExecState::Op(OpcodeId::BASEFEE))
+            .unwrap();
+        assert_eq!(step.error, Some(ExecError::InvalidOpcode));
+
+        assert_unsupported_hardfork(&builder, HardFork::Berlin);
+    }
+
+    /// Checks the witness of `builder` is rejected, the EVM circuit doesn't
+    /// constrain the rules of `hardfork`.
+    fn assert_unsupported_hardfork(builder: &CircuitInputBuilder, hardfork: HardFork) {
+        match block_convert::<Fr>(&builder.block, &builder.code_db).unwrap_err() {
+            Error::UnsupportedHardFork(unsupported) => assert_eq!(unsupported, hardfork),
+            err => panic!("unexpected error {:?}", err),
+        }
+    }
+
+    /// Runs DIFFICULTY in a block of `difficulty` and `mix_hash` executed with
+    /// the EVM rules of `hardfork`, checking the value pushed is `expected`.
+    /// The block only runs through the circuits for London.
+    fn test_difficulty(hardfork: HardFork, difficulty: Word, mix_hash: Hash, expected: Word) {
+        let ctx = TestContext::<2, 1>::new_with_hardfork(
+            None,
+            account_0_code_account_1_no_code(bytecode! {
+                DIFFICULTY
+                STOP
+            }),
+            tx_from_1_to_0,
+            |block, _tx| block.difficulty(difficulty).mix_hash(mix_hash),
+            hardfork,
+        )
+        .unwrap();
+
+        let builder = builder_from_test_ctx(ctx, CircuitsParams::default());
+        assert_eq!(builder.block.difficulty, expected);
+        let step = builder.block.txs()[0]
+            .steps()
+            .iter()
+            .find(|step| step.exec_state == ExecState::Op(OpcodeId::DIFFICULTY))
+            .unwrap();
+        let op = &builder.block.container.stack[step.bus_mapping_instance[0].as_usize()];
+        assert_eq!(op.op().value(), &expected);
+
+        if hardfork == HardFork::London {
+            let block = block_convert(&builder.block, &builder.code_db).unwrap();
+            CircuitTestBuilder::<2, 1>::new_from_block(block).run();
+        } else {
+            assert_unsupported_hardfork(&builder, hardfork);
+        }
+    }
+
+    #[test]
+    fn difficulty_pre_merge() {
+        let difficulty = Word::from(0x1234_5678u64);
+        test_difficulty(
+            HardFork::London,
+            difficulty,
+            Hash::repeat_byte(0xab),
+            difficulty,
+        );
+    }
+
+    #[test]
+    fn difficulty_zero_pre_merge() {
+        // The hard fork, not the difficulty, tells whether PREVRANDAO is used.
+        test_difficulty(
+            HardFork::London,
+            Word::zero(),
+            Hash::repeat_byte(0xab),
+            Word::zero(),
+        );
+    }
+
+    #[test]
+    fn prevrandao_post_merge() {
+        let mix_hash = Hash::repeat_byte(0xab);
+        test_difficulty(HardFork::Merge, Word::zero(), mix_hash, mix_hash.to_word());
+    }
 }
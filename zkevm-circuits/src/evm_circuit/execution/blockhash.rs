@@ -15,7 +15,7 @@ use crate::{
     table::BlockContextFieldTag,
     util::Expr,
 };
-use bus_mapping::evm::OpcodeId;
+use bus_mapping::{circuit_input_builder::NUM_PREV_BLOCK_ALLOWED, evm::OpcodeId};
 use eth_types::{Field, ToLittleEndian, ToScalar};
 use gadgets::util::not;
 use halo2_proofs::{circuit::Value, plonk::Error};
@@ -55,7 +55,7 @@ impl<F: Field> ExecutionGadget<F> for BlockHashGadget<F> {
         let diff_lt = LtGadget::construct(
             cb,
             current_block_number.expr(),
-            257.expr() + from_bytes::expr(&block_number.cells),
+            (NUM_PREV_BLOCK_ALLOWED + 1).expr() + from_bytes::expr(&block_number.cells),
         );
 
         let block_hash = cb.query_word_rlc();
@@ -139,7 +139,7 @@ impl<F: Field> ExecutionGadget<F> for BlockHashGadget<F> {
             region,
             offset,
             current_block_number,
-            block_number + F::from(257),
+            block_number + F::from(NUM_PREV_BLOCK_ALLOWED + 1),
         )?;
 
         Ok(())
@@ -149,10 +149,11 @@ impl<F: Field> ExecutionGadget<F> for BlockHashGadget<F> {
 #[cfg(test)]
 mod test {
     use crate::test_util::CircuitTestBuilder;
-    use eth_types::{bytecode, U256};
+    use bus_mapping::circuit_input_builder::NUM_PREV_BLOCK_ALLOWED;
+    use eth_types::{bytecode, evm_types::OpcodeId, U256};
     use mock::test_ctx::{helpers::*, TestContext};
 
-    fn test_ok(block_number: usize, current_block_number: u64) {
+    fn test_ctx(block_number: usize, current_block_number: u64) -> TestContext<2, 1> {
         let code = bytecode! {
             PUSH32(block_number)
             BLOCKHASH
@@ -161,23 +162,25 @@ mod test {
 
         // simple U256 values for history hashes
         let mut history_hashes = Vec::new();
-        let range = if current_block_number < 256 {
+        let range = if current_block_number < NUM_PREV_BLOCK_ALLOWED {
             0..current_block_number
         } else {
-            current_block_number - 256..current_block_number
+            current_block_number - NUM_PREV_BLOCK_ALLOWED..current_block_number
         };
         for i in range {
             history_hashes.push(U256::from(0xbeefcafeu64 + i));
         }
-        let ctx = TestContext::<2, 1>::new(
+        TestContext::<2, 1>::new(
             Some(history_hashes),
             account_0_code_account_1_no_code(code),
             tx_from_1_to_0,
             |block, _tx| block.number(current_block_number),
         )
-        .unwrap();
+        .unwrap()
+    }
 
-        CircuitTestBuilder::new_from_test_ctx(ctx).run()
+    fn test_ok(block_number: usize, current_block_number: u64) {
+        CircuitTestBuilder::new_from_test_ctx(test_ctx(block_number, current_block_number)).run()
     }
 
     #[test]
@@ -191,6 +194,14 @@ mod test {
         test_ok(6, 5);
     }
 
+    #[test]
+    fn blockhash_gadget_early_block() {
+        // Block 10 only has 10 ancestors, anything below is before genesis
+        for block_number in 0..=11 {
+            test_ok(block_number, 10);
+        }
+    }
+
     #[test]
     fn blockhash_gadget_large() {
         test_ok(0xcafe - 257, 0xcafeu64);
@@ -199,4 +210,27 @@ mod test {
         test_ok(0xcafe, 0xcafeu64);
         test_ok(0xcafe + 1, 0xcafeu64);
     }
+
+    #[test]
+    fn blockhash_gadget_matches_history_hash() {
+        let current_block_number = 0xcafeu64;
+        for block_number in [
+            current_block_number - NUM_PREV_BLOCK_ALLOWED,
+            current_block_number - 1,
+        ] {
+            let ctx = test_ctx(block_number as usize, current_block_number);
+
+            // The hash pushed by BLOCKHASH is the provided one of that block
+            let expected = ctx.history_hashes
+                [(block_number + NUM_PREV_BLOCK_ALLOWED - current_block_number) as usize];
+            let struct_logs = &ctx.geth_traces[0].struct_logs;
+            let idx = struct_logs
+                .iter()
+                .position(|step| step.op == OpcodeId::BLOCKHASH)
+                .unwrap();
+            assert_eq!(struct_logs[idx + 1].stack.last().unwrap(), expected);
+
+            CircuitTestBuilder::new_from_test_ctx(ctx).run();
+        }
+    }
 }
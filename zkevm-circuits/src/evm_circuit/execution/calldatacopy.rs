@@ -1,7 +1,7 @@
 use crate::{
     evm_circuit::{
         execution::ExecutionGadget,
-        param::{N_BYTES_MEMORY_ADDRESS, N_BYTES_MEMORY_WORD_SIZE},
+        param::{N_BYTES_MEMORY_ADDRESS, N_BYTES_MEMORY_WORD_SIZE, N_BYTES_U64},
         step::ExecutionState,
         util::{
             common_gadget::SameContextGadget,
@@ -10,7 +10,10 @@ use crate::{
                 Transition::{Delta, To},
             },
             from_bytes,
-            memory_gadget::{MemoryAddressGadget, MemoryCopierGasGadget, MemoryExpansionGadget},
+            memory_gadget::{
+                CopyPaddingGadget, MemoryAddressGadget, MemoryCopierGasGadget,
+                MemoryExpansionGadget,
+            },
             not, select, CachedRegion, Cell, MemoryAddress,
         },
         witness::{Block, Call, ExecStep, Transaction},
@@ -22,8 +25,6 @@ use bus_mapping::{circuit_input_builder::CopyDataType, evm::OpcodeId};
 use eth_types::{evm_types::GasCost, Field, ToLittleEndian, ToScalar};
 use halo2_proofs::{circuit::Value, plonk::Error};
 
-use std::cmp::min;
-
 #[derive(Clone, Debug)]
 pub(crate) struct CallDataCopyGadget<F> {
     same_context: SameContextGadget<F>,
@@ -32,6 +33,8 @@ pub(crate) struct CallDataCopyGadget<F> {
     src_id: Cell<F>,
     call_data_length: Cell<F>,
     call_data_offset: Cell<F>, // Only used in the internal call
+    /// The call data bytes past `call_data_length` are zero padded.
+    copy_padding: CopyPaddingGadget<F, N_BYTES_U64>,
     copy_rwc_inc: Cell<F>,
     memory_expansion: MemoryExpansionGadget<F, 1, N_BYTES_MEMORY_WORD_SIZE>,
     memory_copier_gas: MemoryCopierGasGadget<F, { GasCost::COPY }>,
@@ -104,6 +107,13 @@ impl<F: Field> ExecutionGadget<F> for CallDataCopyGadget<F> {
             memory_expansion.gas_cost(),
         );
 
+        let copy_padding = CopyPaddingGadget::construct(
+            cb,
+            from_bytes::expr(&data_offset.cells),
+            call_data_length.expr(),
+            memory_address.length(),
+        );
+
         let copy_rwc_inc = cb.query_cell();
         let src_tag = select::expr(
             cb.curr.state.is_root.expr(),
@@ -123,6 +133,14 @@ impl<F: Field> ExecutionGadget<F> for CallDataCopyGadget<F> {
                 0.expr(), // for CALLDATACOPY rlc_acc is 0
                 copy_rwc_inc.expr(),
             );
+            // The copy writes `length` memory bytes, and reads the caller
+            // memory only for the bytes within the call data.
+            cb.require_equal(
+                "copy_rwc_inc == length + (1 - is_root) * n_src_bytes",
+                copy_rwc_inc.expr(),
+                memory_address.length()
+                    + (1.expr() - cb.curr.state.is_root.expr()) * copy_padding.n_src_bytes(),
+            );
         });
         cb.condition(not::expr(memory_address.has_length()), |cb| {
             cb.require_zero(
@@ -152,6 +170,7 @@ impl<F: Field> ExecutionGadget<F> for CallDataCopyGadget<F> {
             src_id,
             call_data_length,
             call_data_offset,
+            copy_padding,
             copy_rwc_inc,
             memory_expansion,
             memory_copier_gas,
@@ -202,6 +221,13 @@ impl<F: Field> ExecutionGadget<F> for CallDataCopyGadget<F> {
         self.call_data_offset
             .assign(region, offset, Value::known(F::from(call_data_offset)))?;
 
+        let (n_src_bytes, _) = self.copy_padding.assign(
+            region,
+            offset,
+            data_offset.low_u64(),
+            call_data_length,
+            length.low_u64(),
+        )?;
         // rw_counter increase from copy lookup is `length` memory writes + a variable
         // number of memory reads.
         let copy_rwc_inc = length
@@ -211,12 +237,7 @@ impl<F: Field> ExecutionGadget<F> for CallDataCopyGadget<F> {
             } else {
                 // memory reads when reading from memory of caller is capped by call_data_length
                 // - data_offset.
-                min(
-                    length.low_u64(),
-                    call_data_length
-                        .checked_sub(data_offset.low_u64())
-                        .unwrap_or_default(),
-                )
+                n_src_bytes
             };
         self.copy_rwc_inc.assign(
             region,
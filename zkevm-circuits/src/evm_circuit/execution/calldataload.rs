@@ -242,9 +242,19 @@ impl<F: Field> ExecutionGadget<F> for CallDataLoadGadget<F> {
 
 #[cfg(test)]
 mod test {
-    use crate::{evm_circuit::test::rand_bytes, test_util::CircuitTestBuilder};
+    use crate::{
+        evm_circuit::{
+            test::{rand_bytes, EvmCircuitWithTxTable},
+            EvmCircuit,
+        },
+        test_util::{block_from_test_ctx, CircuitTestBuilder},
+    };
     use eth_types::{bytecode, ToWord, Word};
-    use mock::TestContext;
+    use halo2_proofs::{
+        dev::{MockProver, VerifyFailure},
+        halo2curves::bn256::Fr,
+    };
+    use mock::{test_ctx::helpers::account_0_code_account_1_no_code, TestContext};
 
     fn test_root_ok(offset: usize) {
         let bytecode = bytecode! {
@@ -321,4 +331,51 @@ mod test {
         test_internal_ok(0x40, 0x20, 0x08);
         test_internal_ok(0x1010, 0xff, 0x10);
     }
+
+    /// Tx reading its 32 bytes of calldata with CALLDATALOAD.
+    fn calldata_tx_ctx() -> TestContext<2, 1> {
+        let bytecode = bytecode! {
+            PUSH1(0x00)
+            CALLDATALOAD
+            STOP
+        };
+        TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(bytecode),
+            |mut txs, accs| {
+                txs[0]
+                    .to(accs[0].address)
+                    .from(accs[1].address)
+                    .input(vec![0x11; 32].into());
+            },
+            |block, _tx| block,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn calldataload_gadget_tx_calldata() {
+        CircuitTestBuilder::new_from_test_ctx(calldata_tx_ctx()).run();
+    }
+
+    #[test]
+    fn calldataload_gadget_tx_calldata_mismatch() {
+        // The CALLDATALOAD bytes are looked up in the tx table, so they can't
+        // differ from the calldata of the tx. Only the tx table is tampered,
+        // the gadget is still assigned the calldata of the tx.
+        let block = block_from_test_ctx(calldata_tx_ctx());
+        let mut txs = block.txs.clone();
+        txs[0].call_data[31] = 0x22;
+
+        let k = block.get_test_degree();
+        let circuit = EvmCircuitWithTxTable {
+            circuit: EvmCircuit::get_test_cicuit_from_block(block),
+            txs,
+        };
+        let prover = MockProver::<Fr>::run(k, &circuit, vec![]).unwrap();
+        let errors = prover.verify().unwrap_err();
+        assert!(errors
+            .iter()
+            .all(|error| matches!(error, VerifyFailure::Lookup { name: "Tx", .. })));
+    }
 }
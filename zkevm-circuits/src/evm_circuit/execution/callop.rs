@@ -1,19 +1,21 @@
 use crate::evm_circuit::execution::ExecutionGadget;
-use crate::evm_circuit::param::N_BYTES_GAS;
+use crate::evm_circuit::param::{N_BYTES_ACCOUNT_ADDRESS, N_BYTES_MEMORY_ADDRESS};
 use crate::evm_circuit::step::ExecutionState;
-use crate::evm_circuit::util::common_gadget::{CommonCallGadget, TransferGadget};
+use crate::evm_circuit::util::common_gadget::{CalleeGasGadget, CommonCallGadget, TransferGadget};
 use crate::evm_circuit::util::constraint_builder::Transition::{Delta, To};
 use crate::evm_circuit::util::constraint_builder::{
     ConstraintBuilder, ReversionInfo, StepStateTransition,
 };
 use crate::evm_circuit::util::math_gadget::{
-    ConstantDivisionGadget, IsZeroGadget, LtWordGadget, MinMaxGadget,
+    IsZeroGadget, IsZeroWordGadget, LtWordGadget, MinMaxGadget,
 };
+use crate::evm_circuit::util::precompile_gadget::PrecompileGadget;
 use crate::evm_circuit::util::{not, or, select, CachedRegion, Cell, Word};
 
 use crate::evm_circuit::witness::{Block, Call, ExecStep, Transaction};
 use crate::table::{AccountFieldTag, CallContextFieldTag};
 use crate::util::Expr;
+use bus_mapping::circuit_input_builder::CopyDataType;
 use bus_mapping::evm::OpcodeId;
 use eth_types::evm_types::GAS_STIPEND_CALL_WITH_VALUE;
 use eth_types::{Field, ToLittleEndian, ToScalar, U256};
@@ -38,7 +40,7 @@ pub(crate) struct CallOpGadget<F> {
     is_static: Cell<F>,
     depth: Cell<F>,
     call: CommonCallGadget<F, true>,
-    call_value_is_zero: IsZeroGadget<F>,
+    call_value_is_zero: IsZeroWordGadget<F>,
     current_value: Word<F>,
     is_warm: Cell<F>,
     is_warm_prev: Cell<F>,
@@ -48,8 +50,10 @@ pub(crate) struct CallOpGadget<F> {
     caller_balance_word: Word<F>,
     // check if insufficient balance case
     is_insufficient_balance: LtWordGadget<F>,
-    one_64th_gas: ConstantDivisionGadget<F, N_BYTES_GAS>,
-    capped_callee_gas_left: MinMaxGadget<F, N_BYTES_GAS>,
+    callee_gas: CalleeGasGadget<F>,
+    precompile: PrecompileGadget<F>,
+    // length of the output of the precompiled copied to the return data region
+    precompile_copy_length: MinMaxGadget<F, N_BYTES_MEMORY_ADDRESS>,
 }
 
 impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
@@ -103,7 +107,7 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
             is_callcode.expr(),
             is_delegatecall.expr(),
         );
-        let call_value_is_zero = IsZeroGadget::construct(cb, call_gadget.value.expr());
+        let call_value_is_zero = IsZeroWordGadget::construct(cb, &call_gadget.value);
         cb.condition(not::expr(is_call.expr() + is_callcode.expr()), |cb| {
             cb.require_zero(
                 "for non call/call code, value is zero",
@@ -212,31 +216,63 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
         let gas_cost = call_gadget.gas_cost_expr(is_warm_prev.expr(), is_call.expr());
         // Apply EIP 150
         let gas_available = cb.curr.state.gas_left.expr() - gas_cost.clone();
-        let one_64th_gas = ConstantDivisionGadget::construct(cb, gas_available.clone(), 64);
-        let all_but_one_64th_gas = gas_available - one_64th_gas.quotient();
-        let capped_callee_gas_left =
-            MinMaxGadget::construct(cb, call_gadget.gas_expr(), all_but_one_64th_gas.clone());
-        let callee_gas_left = select::expr(
+        let callee_gas = CalleeGasGadget::construct(
+            cb,
+            gas_available,
+            call_gadget.gas_expr(),
             call_gadget.gas_is_u64.expr(),
-            capped_callee_gas_left.min(),
-            all_but_one_64th_gas,
         );
+        let callee_gas_left = callee_gas.callee_gas_left();
 
-        // TODO: Handle precompiled
+        // Precompiled contracts run without entering a new call frame, like
+        // calls to accounts without code.
+        let precompile = PrecompileGadget::construct(
+            cb,
+            &call_gadget.callee_address.cells[..N_BYTES_ACCOUNT_ADDRESS],
+            call_gadget.cd_address.length(),
+            callee_gas_left.clone()
+                + call_gadget.has_value.clone() * GAS_STIPEND_CALL_WITH_VALUE.expr(),
+        );
+        cb.condition(
+            precompile.is_precompile() * not::expr(is_insufficient_balance.expr()),
+            |cb| {
+                cb.require_equal(
+                    "Call to precompiled succeeds when it doesn't run out of gas",
+                    call_gadget.is_success.expr(),
+                    precompile.is_success(),
+                );
+            },
+        );
+        let no_callee_frame = or::expr([no_callee_code, precompile.is_precompile()]);
+        let is_precompile_success = precompile.is_precompile() * call_gadget.is_success.expr();
+        let precompile_copy_length = MinMaxGadget::construct(
+            cb,
+            call_gadget.cd_address.length(),
+            call_gadget.rd_address.length(),
+        );
+        let precompile_input_rwc_inc = 2.expr() * call_gadget.cd_address.length();
+        let precompile_output_rwc_inc = 2.expr() * precompile_copy_length.min();
 
         let stack_pointer_delta =
             select::expr(is_call.expr() + is_callcode.expr(), 6.expr(), 5.expr());
         let memory_expansion = call_gadget.memory_expansion.clone();
         cb.condition(
-            no_callee_code.clone() * not::expr(is_insufficient_balance.expr()),
+            no_callee_frame.clone() * not::expr(is_insufficient_balance.expr()),
             |cb| {
-                // Save caller's call state
-                for field_tag in [
-                    CallContextFieldTag::LastCalleeId,
-                    CallContextFieldTag::LastCalleeReturnDataOffset,
-                    CallContextFieldTag::LastCalleeReturnDataLength,
+                // Save caller's call state. The output of the identity
+                // precompiled, its input, is the return data of the call.
+                for (field_tag, value) in [
+                    (
+                        CallContextFieldTag::LastCalleeId,
+                        precompile.is_precompile() * callee_call_id.expr(),
+                    ),
+                    (CallContextFieldTag::LastCalleeReturnDataOffset, 0.expr()),
+                    (
+                        CallContextFieldTag::LastCalleeReturnDataLength,
+                        is_precompile_success.clone() * call_gadget.cd_address.length(),
+                    ),
                 ] {
-                    cb.call_context_lookup(true.expr(), None, field_tag, 0.expr());
+                    cb.call_context_lookup(true.expr(), None, field_tag, value);
                 }
 
                 // For CALL opcode, it has an extra stack pop `value` (+1) and if the value is
@@ -255,14 +291,17 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
                     + is_call.expr() * 1.expr()
                     + transfer_rwc_delta.clone()
                     + is_callcode.expr()
-                    + is_delegatecall.expr() * 2.expr();
+                    + is_delegatecall.expr() * 2.expr()
+                    + is_precompile_success.clone()
+                        * (precompile_input_rwc_inc.clone() + precompile_output_rwc_inc.clone());
                 cb.require_step_state_transition(StepStateTransition {
                     rw_counter: Delta(rw_counter_delta),
                     program_counter: Delta(1.expr()),
                     stack_pointer: Delta(stack_pointer_delta.expr()),
                     gas_left: Delta(
                         call_gadget.has_value.clone() * GAS_STIPEND_CALL_WITH_VALUE.expr()
-                            - gas_cost.clone(),
+                            - gas_cost.clone()
+                            - precompile.gas_used(),
                     ),
                     memory_word_size: To(memory_expansion.next_memory_word_size()),
                     // For CALL opcode, `transfer` invocation has two account write if value is not
@@ -273,6 +312,47 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
             },
         );
 
+        // The input of the identity precompiled is copied from the caller
+        // memory to the memory of the precompiled call frame, from where the
+        // return data is read, and then to the return data region of the
+        // caller, right after the caller's call state is saved.
+        cb.condition(
+            is_precompile_success.clone() * call_gadget.cd_address.has_length(),
+            |cb| {
+                cb.copy_table_lookup(
+                    cb.curr.state.call_id.expr(),
+                    CopyDataType::Memory.expr(),
+                    callee_call_id.expr(),
+                    CopyDataType::Memory.expr(),
+                    call_gadget.cd_address.offset(),
+                    call_gadget.cd_address.address(),
+                    0.expr(),
+                    call_gadget.cd_address.length(),
+                    0.expr(),
+                    precompile_input_rwc_inc,
+                );
+            },
+        );
+        cb.condition(
+            is_precompile_success
+                * call_gadget.cd_address.has_length()
+                * call_gadget.rd_address.has_length(),
+            |cb| {
+                cb.copy_table_lookup(
+                    callee_call_id.expr(),
+                    CopyDataType::Memory.expr(),
+                    cb.curr.state.call_id.expr(),
+                    CopyDataType::Memory.expr(),
+                    0.expr(),
+                    call_gadget.cd_address.length(),
+                    call_gadget.rd_address.offset(),
+                    precompile_copy_length.min(),
+                    0.expr(),
+                    precompile_output_rwc_inc,
+                );
+            },
+        );
+
         // handle is_insufficient_balance step transition
         cb.condition(is_insufficient_balance.expr(), |cb| {
             // Save caller's call state
@@ -299,7 +379,7 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
         });
 
         cb.condition(
-            not::expr(no_callee_code) * not::expr(is_insufficient_balance.expr()),
+            not::expr(no_callee_frame) * not::expr(is_insufficient_balance.expr()),
             |cb| {
                 // Save caller's call state
                 for (field_tag, value) in [
@@ -431,8 +511,9 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
             transfer,
             caller_balance_word,
             is_insufficient_balance,
-            one_64th_gas,
-            capped_callee_gas_left,
+            callee_gas,
+            precompile,
+            precompile_copy_length,
         }
     }
 
@@ -592,8 +673,7 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
             step.memory_word_size(),
             region.word_rlc(callee_code_hash),
         )?;
-        self.call_value_is_zero
-            .assign_value(region, offset, region.word_rlc(value))?;
+        self.call_value_is_zero.assign(region, offset, value)?;
         self.is_warm
             .assign(region, offset, Value::known(F::from(is_warm as u64)))?;
         self.is_warm_prev
@@ -625,13 +705,20 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
         )?;
         let gas_available = step.gas_left - gas_cost;
 
-        self.one_64th_gas
-            .assign(region, offset, gas_available.into())?;
-        self.capped_callee_gas_left.assign(
+        let callee_gas_left = self.callee_gas.assign(region, offset, gas_available, gas)?;
+        let precompile_gas = callee_gas_left + has_value as u64 * GAS_STIPEND_CALL_WITH_VALUE;
+        self.precompile.assign(
             region,
             offset,
-            F::from(gas.low_u64()),
-            F::from(gas_available - gas_available / 64),
+            callee_address,
+            cd_length.low_u64(),
+            precompile_gas,
+        )?;
+        self.precompile_copy_length.assign(
+            region,
+            offset,
+            F::from(cd_length.low_u64()),
+            F::from(rd_length.low_u64()),
         )?;
 
         Ok(())
@@ -641,9 +728,11 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::test_util::CircuitTestBuilder;
+    use crate::test_util::{
+        block_from_test_ctx, block_from_test_ctx_with_params, CircuitTestBuilder,
+    };
     use bus_mapping::circuit_input_builder::CircuitsParams;
-    use eth_types::evm_types::OpcodeId;
+    use eth_types::evm_types::{OpcodeId, PRECOMPILE_COUNT, PRECOMPILE_IDENTITY};
     use eth_types::geth_types::Account;
     use eth_types::{address, bytecode, Address, ToWord, Word};
 
@@ -737,6 +826,14 @@ mod test {
                 rd_length: 0,
                 ..Default::default()
             },
+            // Zero length regions at offsets which don't fit in a memory address
+            Stack {
+                cd_offset: u64::MAX,
+                cd_length: 0,
+                rd_offset: u64::MAX,
+                rd_length: 0,
+                ..Default::default()
+            },
             // With memory expansion and value
             Stack {
                 cd_offset: 64,
@@ -886,6 +983,214 @@ mod test {
         }
     }
 
+    #[test]
+    fn callop_nested_gas_conservation() {
+        // A calls B, which calls C twice.
+        let caller_b = caller(
+            &OpcodeId::CALL,
+            Stack {
+                gas: 20000,
+                ..Default::default()
+            },
+            true,
+        );
+        let callee_c = callee(bytecode! { PUSH1(0) PUSH1(0) RETURN });
+        let code_a = bytecode! {
+            PUSH1(0) // retLength
+            PUSH1(0) // retOffset
+            PUSH1(0) // argsLength
+            PUSH1(0) // argsOffset
+            PUSH1(0) // value
+            PUSH32(caller_b.address.to_word()) // addr
+            PUSH32(50000) // gas
+            CALL
+            STOP
+        };
+
+        let ctx = TestContext::<4, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(address!("0x000000000000000000000000000000000000cafe"))
+                    .balance(Word::from(10u64.pow(19)));
+                accs[1].address(Address::repeat_byte(0xfd)).code(code_a);
+                accs[2]
+                    .address(caller_b.address)
+                    .code(caller_b.code)
+                    .balance(caller_b.balance);
+                accs[3]
+                    .address(callee_c.address)
+                    .code(callee_c.code)
+                    .nonce(callee_c.nonce)
+                    .balance(callee_c.balance);
+            },
+            |mut txs, accs| {
+                txs[0]
+                    .from(accs[0].address)
+                    .to(accs[1].address)
+                    .gas(100000.into());
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap();
+
+        let block = block_from_test_ctx_with_params(
+            ctx,
+            CircuitsParams {
+                max_rws: 1000,
+                ..Default::default()
+            },
+        );
+
+        // For every call, the caller resumes with its gas before the call,
+        // minus the call cost including the forwarded gas, plus the gas left
+        // by the callee.
+        let steps = &block.txs[0].steps;
+        let mut num_calls = 0;
+        for (idx, step) in steps.iter().enumerate() {
+            if step.execution_state != ExecutionState::CALL_OP
+                || steps[idx + 1].call_index == step.call_index
+            {
+                continue;
+            }
+            let resume_idx = idx
+                + 1
+                + steps[idx + 1..]
+                    .iter()
+                    .position(|s| s.call_index == step.call_index)
+                    .unwrap();
+            let callee_last_step = &steps[resume_idx - 1];
+            assert_eq!(
+                steps[resume_idx].gas_left,
+                step.gas_left - step.gas_cost + callee_last_step.gas_left
+                    - callee_last_step.gas_cost,
+            );
+            num_calls += 1;
+        }
+        assert_eq!(num_calls, 3);
+
+        CircuitTestBuilder::<4, 1>::new_from_block(block).run();
+    }
+
+    #[test]
+    fn callop_identity_precompile() {
+        // Enough gas for the identity precompiled, a return data region shorter
+        // than the input, and not enough gas, which leaves the return data
+        // empty and the return data region untouched.
+        for (gas, ret_length, ret_word, return_data_size) in [
+            (0x1000u64, 32u64, Word::MAX, 32u64),
+            (0x1000, 16, Word::MAX << 128, 32),
+            (0x10, 32, Word::zero(), 0),
+        ] {
+            let code = bytecode! {
+                PUSH32(Word::MAX)
+                PUSH1(0)
+                MSTORE
+                PUSH1(ret_length) // retLength
+                PUSH1(32) // retOffset
+                PUSH1(32) // argsLength
+                PUSH1(0) // argsOffset
+                PUSH1(0) // value
+                PUSH1(PRECOMPILE_IDENTITY) // addr
+                PUSH32(gas) // gas
+                CALL
+                PUSH1(32)
+                MLOAD
+                RETURNDATASIZE
+                STOP
+            };
+
+            let ctx = TestContext::<2, 1>::new(
+                None,
+                |accs| {
+                    accs[0]
+                        .address(address!("0x000000000000000000000000000000000000cafe"))
+                        .balance(Word::from(10u64.pow(19)));
+                    accs[1].address(Address::repeat_byte(0xfd)).code(code);
+                },
+                |mut txs, accs| {
+                    txs[0]
+                        .from(accs[0].address)
+                        .to(accs[1].address)
+                        .gas(100000.into());
+                },
+                |block, _tx| block.number(0xcafeu64),
+            )
+            .unwrap();
+
+            let block = block_from_test_ctx(ctx);
+
+            // The caller resumes right after the call, with the output in its
+            // return data region and as its return data.
+            let steps = &block.txs[0].steps;
+            let call_idx = steps
+                .iter()
+                .position(|step| step.execution_state == ExecutionState::CALL_OP)
+                .unwrap();
+            assert_eq!(steps[call_idx + 1].call_index, steps[call_idx].call_index);
+            let mload = steps
+                .iter()
+                .find(|step| step.opcode == Some(OpcodeId::MLOAD))
+                .unwrap();
+            assert_eq!(block.rws[mload.rw_indices[1]].stack_value(), ret_word);
+            let returndatasize = steps
+                .iter()
+                .find(|step| step.execution_state == ExecutionState::RETURNDATASIZE)
+                .unwrap();
+            assert_eq!(
+                block.rws[returndatasize.rw_indices[1]].stack_value(),
+                Word::from(return_data_size)
+            );
+
+            CircuitTestBuilder::<2, 1>::new_from_block(block).run();
+        }
+    }
+
+    #[test]
+    fn callop_account_after_precompiles() {
+        // 0x0a is an ordinary account without code, not a precompiled.
+        let code = bytecode! {
+            PUSH1(0) // retLength
+            PUSH1(0) // retOffset
+            PUSH1(0) // argsLength
+            PUSH1(0) // argsOffset
+            PUSH1(0) // value
+            PUSH1(PRECOMPILE_COUNT + 1) // addr
+            PUSH32(0x1000) // gas
+            CALL
+            STOP
+        };
+
+        let ctx = TestContext::<2, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(address!("0x000000000000000000000000000000000000cafe"))
+                    .balance(Word::from(10u64.pow(19)));
+                accs[1].address(Address::repeat_byte(0xfd)).code(code);
+            },
+            |mut txs, accs| {
+                txs[0]
+                    .from(accs[0].address)
+                    .to(accs[1].address)
+                    .gas(100000.into());
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap();
+
+        let block = block_from_test_ctx(ctx);
+        let steps = &block.txs[0].steps;
+        let call_idx = steps
+            .iter()
+            .position(|step| step.execution_state == ExecutionState::CALL_OP)
+            .unwrap();
+        assert_eq!(steps[call_idx + 1].call_index, steps[call_idx].call_index);
+        assert_eq!(steps[call_idx + 1].execution_state, ExecutionState::STOP);
+
+        CircuitTestBuilder::<2, 1>::new_from_block(block).run();
+    }
+
     #[test]
     fn callop_base() {
         test_ok(
@@ -1001,4 +1306,186 @@ mod test {
             callee(callee_bytecode),
         );
     }
+
+    #[test]
+    fn callop_delegatecall_chain_context() {
+        // The tx calls A, which calls B, which delegatecalls C, which
+        // delegatecalls D. D runs in the context of B, so ADDRESS is B and
+        // CALLER is A, while ORIGIN stays the tx sender.
+        let (sender, addr_a, addr_b, addr_c, addr_d) = (
+            address!("0x000000000000000000000000000000000000cafe"),
+            Address::repeat_byte(0xfa),
+            Address::repeat_byte(0xfb),
+            Address::repeat_byte(0xfc),
+            Address::repeat_byte(0xfd),
+        );
+        let call = |opcode: OpcodeId, callee: Address| {
+            let mut code = bytecode! {
+                PUSH1(0) // retLength
+                PUSH1(0) // retOffset
+                PUSH1(0) // argsLength
+                PUSH1(0) // argsOffset
+            };
+            if opcode == OpcodeId::CALL {
+                code.push(1, Word::zero()); // value
+            }
+            code.append(&bytecode! {
+                PUSH32(callee.to_word()) // addr
+                PUSH32(0x1_0000) // gas
+                .write_op(opcode)
+                STOP
+            });
+            code
+        };
+        let code_d = bytecode! {
+            ADDRESS
+            CALLER
+            ORIGIN
+            STOP
+        };
+
+        let ctx = TestContext::<5, 1>::new(
+            None,
+            |accs| {
+                accs[0].address(sender).balance(Word::from(10u64.pow(19)));
+                accs[1].address(addr_a).code(call(OpcodeId::CALL, addr_b));
+                accs[2]
+                    .address(addr_b)
+                    .code(call(OpcodeId::DELEGATECALL, addr_c));
+                accs[3]
+                    .address(addr_c)
+                    .code(call(OpcodeId::DELEGATECALL, addr_d));
+                accs[4].address(addr_d).code(code_d);
+            },
+            |mut txs, accs| {
+                txs[0]
+                    .from(accs[0].address)
+                    .to(accs[1].address)
+                    .gas(1_000_000.into());
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap();
+
+        let block = block_from_test_ctx_with_params(
+            ctx,
+            CircuitsParams {
+                max_rws: 1000,
+                ..Default::default()
+            },
+        );
+
+        let pushed_value = |opcode: OpcodeId| {
+            let step = block.txs[0]
+                .steps
+                .iter()
+                .find(|step| step.opcode == Some(opcode))
+                .unwrap();
+            block.rws[step.rw_indices[1]].stack_value()
+        };
+        assert_eq!(pushed_value(OpcodeId::ADDRESS), addr_b.to_word());
+        assert_eq!(pushed_value(OpcodeId::CALLER), addr_a.to_word());
+        assert_eq!(pushed_value(OpcodeId::ORIGIN), sender.to_word());
+
+        CircuitTestBuilder::<5, 1>::new_from_block(block).run();
+    }
+
+    #[test]
+    fn callop_self_call() {
+        // A calls itself once: the outer frame writes its value at offset 0
+        // and calls A, and the inner frame, where CALLER is A, writes and
+        // reads another value at the same offset. Then the outer frame reads
+        // back its own value.
+        let (sender, addr_a) = (
+            address!("0x000000000000000000000000000000000000cafe"),
+            Address::repeat_byte(0xfa),
+        );
+        let outer_value = Word::from_big_endian(&[0xaa; 32]);
+        let inner_value = Word::from_big_endian(&[0xbb; 32]);
+        let outer = bytecode! {
+            PUSH32(outer_value)
+            PUSH1(0)
+            MSTORE
+            PUSH1(0) // retLength
+            PUSH1(0) // retOffset
+            PUSH1(0) // argsLength
+            PUSH1(0) // argsOffset
+            PUSH1(0) // value
+            ADDRESS // addr
+            PUSH32(0x1_0000) // gas
+            CALL
+            PUSH1(0)
+            MLOAD
+            STOP
+        };
+        let inner = bytecode! {
+            JUMPDEST
+            PUSH32(inner_value)
+            PUSH1(0)
+            MSTORE
+            PUSH1(0)
+            MLOAD
+            STOP
+        };
+        // ADDRESS, CALLER, EQ, PUSH1 and JUMPI take 6 bytes.
+        let mut code = bytecode! {
+            ADDRESS
+            CALLER
+            EQ
+            PUSH1(6 + outer.code().len())
+            JUMPI
+        };
+        code.append(&outer);
+        code.append(&inner);
+
+        let ctx = TestContext::<2, 1>::new(
+            None,
+            |accs| {
+                accs[0].address(sender).balance(Word::from(10u64.pow(19)));
+                accs[1].address(addr_a).code(code);
+            },
+            |mut txs, accs| {
+                txs[0]
+                    .from(accs[0].address)
+                    .to(accs[1].address)
+                    .gas(1_000_000.into());
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap();
+
+        let block = block_from_test_ctx_with_params(
+            ctx,
+            CircuitsParams {
+                max_rws: 1000,
+                ..Default::default()
+            },
+        );
+
+        // Both frames run the same code but get a call id of their own.
+        let calls = &block.txs[0].calls;
+        assert_eq!(calls.len(), 2);
+        assert_ne!(calls[0].id, calls[1].id);
+        assert_eq!(calls[0].code_hash, calls[1].code_hash);
+
+        let mloads = block.txs[0]
+            .steps
+            .iter()
+            .filter(|step| step.opcode == Some(OpcodeId::MLOAD))
+            .collect::<Vec<_>>();
+        assert_eq!(mloads.len(), 2);
+        let (inner_mload, outer_mload) = (mloads[0], mloads[1]);
+        assert_eq!(calls[inner_mload.call_index].id, calls[1].id);
+        assert_eq!(calls[outer_mload.call_index].id, calls[0].id);
+        assert_eq!(
+            block.rws[inner_mload.rw_indices[1]].stack_value(),
+            inner_value
+        );
+        assert_eq!(
+            block.rws[outer_mload.rw_indices[1]].stack_value(),
+            outer_value
+        );
+
+        CircuitTestBuilder::<2, 1>::new_from_block(block).run();
+    }
 }
@@ -3,9 +3,10 @@ use crate::{
         execution::ExecutionGadget,
         step::ExecutionState,
         util::{
-            common_gadget::SameContextGadget,
+            common_gadget::{SameContextGadget, StackDepthGadget},
             constraint_builder::{ConstraintBuilder, StepStateTransition, Transition::Delta},
-            CachedRegion, Cell,
+            word_rlc::WordRlc,
+            CachedRegion,
         },
         witness::{Block, Call, ExecStep, Transaction},
     },
@@ -17,7 +18,8 @@ use halo2_proofs::plonk::Error;
 #[derive(Clone, Debug)]
 pub(crate) struct DupGadget<F> {
     same_context: SameContextGadget<F>,
-    value: Cell<F>,
+    stack_depth: StackDepthGadget<F>,
+    value: WordRlc<F>,
 }
 
 impl<F: Field> ExecutionGadget<F> for DupGadget<F> {
@@ -28,11 +30,13 @@ impl<F: Field> ExecutionGadget<F> for DupGadget<F> {
     fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
         let opcode = cb.query_cell();
 
-        let value = cb.query_cell_phase2();
+        let value = WordRlc::construct(cb);
 
         // The stack index we have to peek, deduced from the 'x' value of 'dupx'
         // The offset starts at 0 for DUP1
         let dup_offset = opcode.expr() - OpcodeId::DUP1.expr();
+        // DUPx needs x items on the stack
+        let stack_depth = StackDepthGadget::construct(cb, dup_offset.clone() + 1.expr());
 
         // Peek the value at `dup_offset` and push the value on the stack
         cb.stack_lookup(false.expr(), dup_offset, value.expr());
@@ -50,6 +54,7 @@ impl<F: Field> ExecutionGadget<F> for DupGadget<F> {
 
         Self {
             same_context,
+            stack_depth,
             value,
         }
     }
@@ -64,9 +69,15 @@ impl<F: Field> ExecutionGadget<F> for DupGadget<F> {
         step: &ExecStep,
     ) -> Result<(), Error> {
         self.same_context.assign_exec_step(region, offset, step)?;
+        self.stack_depth.assign(
+            region,
+            offset,
+            step,
+            step.opcode.unwrap().dup_swap_depth().unwrap() as u64,
+        )?;
 
         let value = block.rws[step.rw_indices[0]].stack_value();
-        self.value.assign(region, offset, region.word_rlc(value))?;
+        self.value.assign(region, offset, value)?;
 
         Ok(())
     }
@@ -75,7 +86,10 @@ impl<F: Field> ExecutionGadget<F> for DupGadget<F> {
 #[cfg(test)]
 
 mod test {
-    use crate::{evm_circuit::test::rand_word, test_util::CircuitTestBuilder};
+    use crate::{
+        evm_circuit::{step::ExecutionState, test::rand_word},
+        test_util::{test_stack_depth, CircuitTestBuilder},
+    };
     use eth_types::evm_types::OpcodeId;
     use eth_types::{bytecode, Word};
     use mock::TestContext;
@@ -107,6 +121,16 @@ mod test {
         test_ok(OpcodeId::DUP16, Word::max_value());
     }
 
+    #[test]
+    fn dup16_stack_underflow() {
+        test_stack_depth(OpcodeId::DUP16, 15, ExecutionState::ErrorStack);
+    }
+
+    #[test]
+    fn dup16_stack_depth_ok() {
+        test_stack_depth(OpcodeId::DUP16, 16, ExecutionState::DUP);
+    }
+
     #[test]
     #[ignore]
     fn dup_gadget_rand() {
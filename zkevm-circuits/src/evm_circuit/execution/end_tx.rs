@@ -10,7 +10,7 @@ use crate::{
                 Transition::{Delta, Same},
             },
             math_gadget::{
-                AddWordsGadget, ConstantDivisionGadget, IsEqualGadget, MinMaxGadget,
+                AddWordsGadget, ConstantDivisionGadget, IsEqualGadget, LtGadget, MinMaxGadget,
                 MulWordByU64Gadget,
             },
             CachedRegion, Cell,
@@ -41,6 +41,9 @@ pub(crate) struct EndTxGadget<F> {
     coinbase: Cell<F>,
     coinbase_reward: UpdateBalanceGadget<F, 2, true>,
     current_cumulative_gas_used: Cell<F>,
+    block_gas_limit: Cell<F>,
+    // Check the cumulative gas used doesn't go over the block gas limit.
+    block_gas_limit_lt_cumulative_gas_used: LtGadget<F, N_BYTES_GAS>,
     is_first_tx: IsEqualGadget<F>,
     is_persistent: Cell<F>,
 }
@@ -137,15 +140,34 @@ impl<F: Field> ExecutionGadget<F> for EndTxGadget<F> {
             );
         });
 
+        let cumulative_gas_used = gas_used + current_cumulative_gas_used.expr();
         cb.tx_receipt_lookup(
             1.expr(),
             tx_id.expr(),
             TxReceiptFieldTag::CumulativeGasUsed,
-            gas_used + current_cumulative_gas_used.expr(),
+            cumulative_gas_used.clone(),
+        );
+
+        // The cumulative gas used of the previous tx is within the block gas
+        // limit, and the gas used of this tx within its gas from the tx
+        // table, so the sum is far from overflowing the field and the
+        // comparison over N_BYTES_GAS is sound.
+        let block_gas_limit = cb.query_cell();
+        cb.block_lookup(
+            BlockContextFieldTag::GasLimit.expr(),
+            None,
+            block_gas_limit.expr(),
+        );
+        let block_gas_limit_lt_cumulative_gas_used =
+            LtGadget::construct(cb, block_gas_limit.expr(), cumulative_gas_used);
+        cb.require_zero(
+            "cumulative_gas_used <= block_gas_limit",
+            block_gas_limit_lt_cumulative_gas_used.expr(),
         );
 
         cb.condition(
-            cb.next.execution_state_selector([ExecutionState::BeginTx]),
+            cb.next
+                .execution_state_selector([ExecutionState::BeginTx, ExecutionState::InvalidTx]),
             |cb| {
                 cb.call_context_lookup(
                     true.expr(),
@@ -188,6 +210,8 @@ impl<F: Field> ExecutionGadget<F> for EndTxGadget<F> {
             coinbase,
             coinbase_reward,
             current_cumulative_gas_used,
+            block_gas_limit,
+            block_gas_limit_lt_cumulative_gas_used,
             is_first_tx,
             is_persistent,
         }
@@ -295,6 +319,17 @@ impl<F: Field> ExecutionGadget<F> for EndTxGadget<F> {
             offset,
             Value::known(F::from(current_cumulative_gas_used)),
         )?;
+        self.block_gas_limit.assign(
+            region,
+            offset,
+            Value::known(F::from(block.context.gas_limit)),
+        )?;
+        self.block_gas_limit_lt_cumulative_gas_used.assign(
+            region,
+            offset,
+            F::from(block.context.gas_limit),
+            F::from(current_cumulative_gas_used + gas_used),
+        )?;
         self.is_first_tx
             .assign(region, offset, F::from(tx.id as u64), F::one())?;
         self.is_persistent.assign(
@@ -309,11 +344,17 @@ impl<F: Field> ExecutionGadget<F> for EndTxGadget<F> {
 
 #[cfg(test)]
 mod test {
-    use crate::test_util::CircuitTestBuilder;
+    use crate::{
+        test_util::{builder_from_test_ctx, CircuitTestBuilder},
+        witness::block_convert,
+    };
     use bus_mapping::circuit_input_builder::CircuitsParams;
-    use eth_types::{self, bytecode};
+    use eth_types::{self, bytecode, Word};
+    use halo2_proofs::halo2curves::bn256::Fr;
 
-    use mock::{eth, test_ctx::helpers::account_0_code_account_1_no_code, TestContext};
+    use mock::{
+        eth, test_ctx::helpers::account_0_code_account_1_no_code, TestContext, MOCK_ACCOUNTS,
+    };
 
     fn test_ok<const NACC: usize, const NTX: usize>(ctx: TestContext<NACC, NTX>) {
         CircuitTestBuilder::new_from_test_ctx(ctx)
@@ -365,4 +406,113 @@ mod test {
             .unwrap(),
         );
     }
+
+    #[test]
+    fn end_tx_gadget_reverted_tx() {
+        // Both contracts write the slot 0, only the first one keeps it.
+        let code_success = bytecode! {
+            PUSH1(0x01)
+            PUSH1(0x00)
+            SSTORE
+            STOP
+        };
+        let code_revert = bytecode! {
+            PUSH1(0x01)
+            PUSH1(0x00)
+            SSTORE
+            PUSH1(0x00)
+            PUSH1(0x00)
+            REVERT
+        };
+        let ctx = TestContext::<3, 2>::new(
+            None,
+            |accs| {
+                accs[0].address(MOCK_ACCOUNTS[0]).code(code_success);
+                accs[1].address(MOCK_ACCOUNTS[1]).code(code_revert);
+                accs[2].address(MOCK_ACCOUNTS[2]).balance(eth(10));
+            },
+            |mut txs, accs| {
+                txs[0].from(accs[2].address).to(accs[0].address);
+                txs[1].from(accs[2].address).to(accs[1].address);
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap();
+
+        let builder = builder_from_test_ctx(
+            ctx,
+            CircuitsParams {
+                max_txs: 5,
+                ..Default::default()
+            },
+        );
+
+        // The reverted tx still pays its fee, but its storage write is undone.
+        let slot = Word::zero();
+        assert_eq!(
+            *builder.sdb.get_storage(&MOCK_ACCOUNTS[0], &slot).1,
+            Word::one()
+        );
+        assert_eq!(
+            *builder.sdb.get_storage(&MOCK_ACCOUNTS[1], &slot).1,
+            Word::zero()
+        );
+
+        let block = block_convert::<Fr>(&builder.block, &builder.code_db).unwrap();
+        assert!(block.txs[0].calls[0].is_success);
+        assert!(!block.txs[1].calls[0].is_success);
+        assert!(block
+            .txs
+            .iter()
+            .all(|tx| tx.gas > tx.steps.last().unwrap().gas_left));
+
+        CircuitTestBuilder::<3, 2>::new_from_block(block).run();
+    }
+
+    /// Three txs using 21000 gas each, taking up the whole gas limit of their
+    /// block, whose witness is then given a gas limit of `witness_gas_limit`.
+    fn test_block_gas_limit(witness_gas_limit: u64, is_ok: bool) {
+        const GAS_LIMIT: u64 = 3 * 21000;
+        let ctx = TestContext::<2, 3>::new(
+            None,
+            account_0_code_account_1_no_code(bytecode! { STOP }),
+            |txs, accs| {
+                for tx in txs {
+                    tx.to(accs[0].address)
+                        .from(accs[1].address)
+                        .gas(Word::from(21000));
+                }
+            },
+            |block, _tx| block.number(0xcafeu64).gas_limit(Word::from(GAS_LIMIT)),
+        )
+        .unwrap();
+
+        let mut ctb = CircuitTestBuilder::new_from_test_ctx(ctx)
+            .params(CircuitsParams {
+                max_txs: 5,
+                ..Default::default()
+            })
+            .block_modifier(Box::new(move |block| {
+                assert_eq!(block.context.gas_limit, GAS_LIMIT);
+                block.context.gas_limit = witness_gas_limit;
+            }));
+        if !is_ok {
+            ctb = ctb.evm_checks(Box::new(|prover, gate_rows, lookup_rows| {
+                assert!(prover
+                    .verify_at_rows_par(gate_rows.iter().cloned(), lookup_rows.iter().cloned())
+                    .is_err())
+            }));
+        }
+        ctb.run();
+    }
+
+    #[test]
+    fn end_tx_cumulative_gas_used_reaches_block_gas_limit() {
+        test_block_gas_limit(3 * 21000, true);
+    }
+
+    #[test]
+    fn end_tx_cumulative_gas_used_over_block_gas_limit() {
+        test_block_gas_limit(3 * 21000 - 1, false);
+    }
 }
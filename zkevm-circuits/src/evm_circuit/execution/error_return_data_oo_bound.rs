@@ -0,0 +1,353 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::N_BYTES_U64,
+        step::ExecutionState,
+        util::{
+            common_gadget::RestoreContextGadget,
+            constraint_builder::{
+                ConstraintBuilder, StepStateTransition,
+                Transition::{Delta, Same},
+            },
+            from_bytes,
+            math_gadget::{AddWordsGadget, IsZeroGadget, LtGadget},
+            not, or, sum, CachedRegion, Cell,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    table::CallContextFieldTag,
+    util::Expr,
+};
+use eth_types::{evm_types::OpcodeId, Field, ToLittleEndian, ToScalar};
+use halo2_proofs::{circuit::Value, plonk::Error};
+
+/// Gadget for RETURNDATACOPY reading past the end of the last callee return
+/// data, i.e. `data_offset + size > return_data_size`. Unlike CALLDATACOPY the
+/// EVM doesn't zero pad the missing bytes, the current call halts instead.
+#[derive(Clone, Debug)]
+pub(crate) struct ErrorReturnDataOutOfBoundGadget<F> {
+    opcode: Cell<F>,
+    memory_offset: Cell<F>,
+    /// `data_offset + size`, with the carry telling if it overflows U256.
+    data_end: AddWordsGadget<F, 2, false>,
+    is_data_end_within_u64: IsZeroGadget<F>,
+    /// Holds the size of the last callee return data.
+    return_data_size: Cell<F>,
+    is_data_end_over_size: LtGadget<F, N_BYTES_U64>,
+    rw_counter_end_of_reversion: Cell<F>,
+    restore_context: RestoreContextGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for ErrorReturnDataOutOfBoundGadget<F> {
+    const NAME: &'static str = "ErrorReturnDataOutOfBound";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::ErrorReturnDataOutOfBound;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+        cb.opcode_lookup(opcode.expr(), 1.expr());
+        cb.require_equal(
+            "ErrorReturnDataOutOfBound only happens in RETURNDATACOPY",
+            opcode.expr(),
+            OpcodeId::RETURNDATACOPY.expr(),
+        );
+
+        let memory_offset = cb.query_cell_phase2();
+        let data_offset = cb.query_word_rlc();
+        let size = cb.query_word_rlc();
+
+        // Pop memory_offset, data_offset, size from stack
+        cb.stack_pop(memory_offset.expr());
+        cb.stack_pop(data_offset.expr());
+        cb.stack_pop(size.expr());
+
+        let return_data_size = cb.query_cell();
+        cb.call_context_lookup(
+            false.expr(),
+            None,
+            CallContextFieldTag::LastCalleeReturnDataLength,
+            return_data_size.expr(),
+        );
+
+        // The copy is out of bound when `data_offset + size` overflows U256,
+        // doesn't fit in u64 or exceeds the return data size.
+        let data_end = cb.query_word_rlc();
+        let data_end = AddWordsGadget::construct(cb, [data_offset, size], data_end);
+        let is_data_end_within_u64 =
+            IsZeroGadget::construct(cb, sum::expr(&data_end.sum().cells[N_BYTES_U64..]));
+        let is_data_end_over_size = LtGadget::construct(
+            cb,
+            return_data_size.expr(),
+            from_bytes::expr(&data_end.sum().cells[..N_BYTES_U64]),
+        );
+        cb.require_equal(
+            "data_offset + size > return_data_size",
+            or::expr([
+                data_end.carry().as_ref().unwrap().expr(),
+                not::expr(is_data_end_within_u64.expr()),
+                is_data_end_over_size.expr(),
+            ]),
+            1.expr(),
+        );
+
+        cb.call_context_lookup(false.expr(), None, CallContextFieldTag::IsSuccess, 0.expr());
+
+        let rw_counter_end_of_reversion = cb.query_cell();
+        cb.call_context_lookup(
+            false.expr(),
+            None,
+            CallContextFieldTag::RwCounterEndOfReversion,
+            rw_counter_end_of_reversion.expr(),
+        );
+
+        // Go to EndTx only when is_root
+        let is_to_end_tx = cb.next.execution_state_selector([ExecutionState::EndTx]);
+        cb.require_equal(
+            "Go to EndTx only when is_root",
+            cb.curr.state.is_root.expr(),
+            is_to_end_tx,
+        );
+
+        // When it's a root call
+        cb.condition(cb.curr.state.is_root.expr(), |cb| {
+            // Do step state transition
+            cb.require_step_state_transition(StepStateTransition {
+                call_id: Same,
+                rw_counter: Delta(6.expr() + cb.curr.state.reversible_write_counter.expr()),
+                ..StepStateTransition::any()
+            });
+        });
+
+        // When it's an internal call, need to restore caller's state as finishing this
+        // call. Restore caller state to next StepState
+        let restore_context = cb.condition(1.expr() - cb.curr.state.is_root.expr(), |cb| {
+            RestoreContextGadget::construct(
+                cb,
+                0.expr(),
+                0.expr(),
+                0.expr(),
+                0.expr(),
+                0.expr(),
+                0.expr(),
+            )
+        });
+
+        // constrain RwCounterEndOfReversion
+        let rw_counter_end_of_step =
+            cb.curr.state.rw_counter.expr() + cb.rw_counter_offset() - 1.expr();
+        cb.require_equal(
+            "rw_counter_end_of_reversion = rw_counter_end_of_step + reversible_counter",
+            rw_counter_end_of_reversion.expr(),
+            rw_counter_end_of_step + cb.curr.state.reversible_write_counter.expr(),
+        );
+
+        Self {
+            opcode,
+            memory_offset,
+            data_end,
+            is_data_end_within_u64,
+            return_data_size,
+            is_data_end_over_size,
+            rw_counter_end_of_reversion,
+            restore_context,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let opcode = step.opcode.unwrap();
+        self.opcode
+            .assign(region, offset, Value::known(F::from(opcode.as_u64())))?;
+
+        let [memory_offset, data_offset, size] =
+            [0, 1, 2].map(|idx| block.rws[step.rw_indices[idx]].stack_value());
+        let memory_offset_rlc = region.word_rlc(memory_offset);
+        self.memory_offset
+            .assign(region, offset, memory_offset_rlc)?;
+
+        let (data_end, _) = data_offset.overflowing_add(size);
+        self.data_end
+            .assign(region, offset, [data_offset, size], data_end)?;
+        let data_end_bytes = data_end.to_le_bytes();
+        self.is_data_end_within_u64.assign(
+            region,
+            offset,
+            data_end_bytes[N_BYTES_U64..]
+                .iter()
+                .fold(F::zero(), |acc, byte| acc + F::from(*byte as u64)),
+        )?;
+
+        let return_data_size = block.rws[step.rw_indices[3]].call_context_value();
+        self.return_data_size.assign(
+            region,
+            offset,
+            Value::known(
+                return_data_size
+                    .to_scalar()
+                    .expect("unexpected U256 -> Scalar conversion failure"),
+            ),
+        )?;
+        self.is_data_end_over_size.assign(
+            region,
+            offset,
+            F::from(return_data_size.low_u64()),
+            F::from(data_end.low_u64()),
+        )?;
+
+        self.rw_counter_end_of_reversion.assign(
+            region,
+            offset,
+            Value::known(F::from(call.rw_counter_end_of_reversion as u64)),
+        )?;
+        self.restore_context
+            .assign(region, offset, block, call, step, 6)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::CircuitTestBuilder;
+    use bus_mapping::circuit_input_builder::CircuitsParams;
+    use eth_types::{bytecode, bytecode::Bytecode, ToWord, Word};
+    use mock::TestContext;
+
+    /// Code returning `return_data_size` bytes of memory.
+    fn callee_bytecode(return_data_size: u64) -> Bytecode {
+        bytecode! {
+            PUSH32(Word::MAX)
+            PUSH1(0x00)
+            MSTORE
+            PUSH32(return_data_size)
+            PUSH1(0x00)
+            RETURN
+        }
+    }
+
+    /// Code calling `callee` and then copying `size` bytes of its return data
+    /// from `data_offset`.
+    fn call_and_copy(callee: Word, data_offset: Word, size: Word) -> Bytecode {
+        bytecode! {
+            PUSH1(0x00) // retLength
+            PUSH1(0x00) // retOffset
+            PUSH1(0x00) // argsLength
+            PUSH1(0x00) // argsOffset
+            PUSH1(0x00) // value
+            PUSH32(callee) // addr
+            PUSH32(0x1_0000) // gas
+            CALL
+            PUSH32(size) // size
+            PUSH32(data_offset) // offset
+            PUSH1(0x00) // dest_offset
+            RETURNDATACOPY
+            STOP
+        }
+    }
+
+    fn test_root_ok(return_data_size: u64, data_offset: Word, size: Word) {
+        let (addr_a, addr_b) = (mock::MOCK_ACCOUNTS[0], mock::MOCK_ACCOUNTS[1]);
+
+        let ctx = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(addr_a)
+                    .code(call_and_copy(addr_b.to_word(), data_offset, size));
+                accs[1]
+                    .address(addr_b)
+                    .code(callee_bytecode(return_data_size));
+                accs[2]
+                    .address(mock::MOCK_ACCOUNTS[2])
+                    .balance(Word::from(1u64 << 30));
+            },
+            |mut txs, accs| {
+                txs[0].to(accs[0].address).from(accs[2].address);
+            },
+            |block, _tx| block,
+        )
+        .unwrap();
+
+        CircuitTestBuilder::new_from_test_ctx(ctx)
+            .params(CircuitsParams {
+                max_rws: 2048,
+                ..Default::default()
+            })
+            .run();
+    }
+
+    fn test_internal_ok(return_data_size: u64, data_offset: Word, size: Word) {
+        let (addr_a, addr_b, addr_c) = (
+            mock::MOCK_ACCOUNTS[0],
+            mock::MOCK_ACCOUNTS[1],
+            mock::MOCK_ACCOUNTS[2],
+        );
+
+        // code A calls code B, which fails copying the return data of code C.
+        let code_a = bytecode! {
+            PUSH1(0x00) // retLength
+            PUSH1(0x00) // retOffset
+            PUSH1(0x00) // argsLength
+            PUSH1(0x00) // argsOffset
+            PUSH1(0x00) // value
+            PUSH32(addr_b.to_word()) // addr
+            PUSH32(0x2_0000) // gas
+            CALL
+            STOP
+        };
+
+        let ctx = TestContext::<4, 1>::new(
+            None,
+            |accs| {
+                accs[0].address(addr_a).code(code_a);
+                accs[1]
+                    .address(addr_b)
+                    .code(call_and_copy(addr_c.to_word(), data_offset, size));
+                accs[2]
+                    .address(addr_c)
+                    .code(callee_bytecode(return_data_size));
+                accs[3]
+                    .address(mock::MOCK_ACCOUNTS[3])
+                    .balance(Word::from(1u64 << 30));
+            },
+            |mut txs, accs| {
+                txs[0].to(accs[0].address).from(accs[3].address);
+            },
+            |block, _tx| block,
+        )
+        .unwrap();
+
+        CircuitTestBuilder::new_from_test_ctx(ctx)
+            .params(CircuitsParams {
+                max_rws: 2048,
+                ..Default::default()
+            })
+            .run();
+    }
+
+    #[test]
+    fn return_data_out_of_bound_root() {
+        test_root_ok(0x20, Word::from(0x10), Word::from(0x11));
+    }
+
+    #[test]
+    fn return_data_out_of_bound_offset_past_end() {
+        test_root_ok(0x20, Word::from(0x21), Word::zero());
+    }
+
+    #[test]
+    fn return_data_out_of_bound_overflow() {
+        test_root_ok(0x20, Word::MAX, Word::from(0x02));
+    }
+
+    #[test]
+    fn return_data_out_of_bound_internal() {
+        test_internal_ok(0x20, Word::from(0x10), Word::from(0x11));
+    }
+}
@@ -1,5 +1,6 @@
 use crate::evm_circuit::{
     execution::ExecutionGadget,
+    param::N_BYTES_STACK,
     step::ExecutionState,
     util::{
         common_gadget::RestoreContextGadget,
@@ -17,8 +18,6 @@ use crate::util::Expr;
 use eth_types::Field;
 use halo2_proofs::{circuit::Value, plonk::Error};
 
-const N_BYTES_STACK: usize = 2;
-
 #[derive(Clone, Debug)]
 pub(crate) struct ErrorStackGadget<F> {
     opcode: Cell<F>,
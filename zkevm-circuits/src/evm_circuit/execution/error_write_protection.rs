@@ -0,0 +1,303 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        step::ExecutionState,
+        util::{
+            common_gadget::RestoreContextGadget,
+            constraint_builder::{
+                ConstraintBuilder, StepStateTransition,
+                Transition::{Delta, Same},
+            },
+            math_gadget::{IsEqualGadget, IsZeroGadget},
+            sum, CachedRegion, Cell, Word,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    table::CallContextFieldTag,
+    util::Expr,
+};
+use eth_types::{evm_types::OpcodeId, Field, ToLittleEndian, U256};
+use halo2_proofs::{circuit::Value, plonk::Error};
+
+/// Gadget for the opcodes modifying the state in a static call, i.e. SSTORE,
+/// CREATE, CREATE2, SELFDESTRUCT, LOG0-4 and CALL with a non-zero value. The
+/// static flag is propagated to the callees by the call opcodes, and the
+/// opcodes succeeding only in a non-static call check it by
+/// `require_not_static`.
+#[derive(Clone, Debug)]
+pub(crate) struct ErrorWriteProtectionGadget<F> {
+    opcode: Cell<F>,
+    is_call: IsEqualGadget<F>,
+    value: Word<F>,
+    is_value_zero: IsZeroGadget<F>,
+    rw_counter_end_of_reversion: Cell<F>,
+    restore_context: RestoreContextGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for ErrorWriteProtectionGadget<F> {
+    const NAME: &'static str = "ErrorWriteProtection";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::ErrorWriteProtection;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+        cb.opcode_lookup(opcode.expr(), 1.expr());
+        cb.require_in_set(
+            "ErrorWriteProtection only happens in the opcodes modifying the state",
+            opcode.expr(),
+            vec![
+                OpcodeId::SSTORE.expr(),
+                OpcodeId::CREATE.expr(),
+                OpcodeId::CREATE2.expr(),
+                OpcodeId::SELFDESTRUCT.expr(),
+                OpcodeId::LOG0.expr(),
+                OpcodeId::LOG1.expr(),
+                OpcodeId::LOG2.expr(),
+                OpcodeId::LOG3.expr(),
+                OpcodeId::LOG4.expr(),
+                OpcodeId::CALL.expr(),
+            ],
+        );
+
+        // CALL only fails when it transfers a non-zero value, which is the
+        // third item of its stack inputs.
+        let is_call = IsEqualGadget::construct(cb, opcode.expr(), OpcodeId::CALL.expr());
+        let value = cb.query_word_rlc();
+        let is_value_zero = IsZeroGadget::construct(cb, sum::expr(&value.cells));
+        cb.condition(is_call.expr(), |cb| {
+            cb.stack_lookup(
+                false.expr(),
+                cb.stack_pointer_offset() + 2.expr(),
+                value.expr(),
+            );
+            cb.require_zero("CALL value is not zero", is_value_zero.expr());
+        });
+
+        cb.call_context_lookup(false.expr(), None, CallContextFieldTag::IsStatic, 1.expr());
+        cb.call_context_lookup(false.expr(), None, CallContextFieldTag::IsSuccess, 0.expr());
+
+        let rw_counter_end_of_reversion = cb.query_cell();
+        cb.call_context_lookup(
+            false.expr(),
+            None,
+            CallContextFieldTag::RwCounterEndOfReversion,
+            rw_counter_end_of_reversion.expr(),
+        );
+
+        // Go to EndTx only when is_root
+        let is_to_end_tx = cb.next.execution_state_selector([ExecutionState::EndTx]);
+        cb.require_equal(
+            "Go to EndTx only when is_root",
+            cb.curr.state.is_root.expr(),
+            is_to_end_tx,
+        );
+
+        // When it's a root call
+        cb.condition(cb.curr.state.is_root.expr(), |cb| {
+            // Do step state transition
+            cb.require_step_state_transition(StepStateTransition {
+                call_id: Same,
+                rw_counter: Delta(
+                    3.expr() + is_call.expr() + cb.curr.state.reversible_write_counter.expr(),
+                ),
+                ..StepStateTransition::any()
+            });
+        });
+
+        // When it's an internal call, need to restore caller's state as finishing this
+        // call. Restore caller state to next StepState
+        let restore_context = cb.condition(1.expr() - cb.curr.state.is_root.expr(), |cb| {
+            RestoreContextGadget::construct(
+                cb,
+                0.expr(),
+                0.expr(),
+                0.expr(),
+                0.expr(),
+                0.expr(),
+                0.expr(),
+            )
+        });
+
+        // constrain RwCounterEndOfReversion
+        let rw_counter_end_of_step =
+            cb.curr.state.rw_counter.expr() + cb.rw_counter_offset() - 1.expr();
+        cb.require_equal(
+            "rw_counter_end_of_reversion = rw_counter_end_of_step + reversible_counter",
+            rw_counter_end_of_reversion.expr(),
+            rw_counter_end_of_step + cb.curr.state.reversible_write_counter.expr(),
+        );
+
+        Self {
+            opcode,
+            is_call,
+            value,
+            is_value_zero,
+            rw_counter_end_of_reversion,
+            restore_context,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let opcode = step.opcode.unwrap();
+        let is_call = opcode == OpcodeId::CALL;
+        self.opcode
+            .assign(region, offset, Value::known(F::from(opcode.as_u64())))?;
+        self.is_call.assign(
+            region,
+            offset,
+            F::from(opcode.as_u64()),
+            F::from(OpcodeId::CALL.as_u64()),
+        )?;
+
+        let value = if is_call {
+            block.rws[step.rw_indices[0]].stack_value()
+        } else {
+            U256::zero()
+        };
+        self.value
+            .assign(region, offset, Some(value.to_le_bytes()))?;
+        self.is_value_zero.assign(
+            region,
+            offset,
+            value
+                .to_le_bytes()
+                .iter()
+                .fold(F::zero(), |acc, byte| acc + F::from(*byte as u64)),
+        )?;
+
+        self.rw_counter_end_of_reversion.assign(
+            region,
+            offset,
+            Value::known(F::from(call.rw_counter_end_of_reversion as u64)),
+        )?;
+        self.restore_context
+            .assign(region, offset, block, call, step, 3 + is_call as usize)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        evm_circuit::step::ExecutionState,
+        test_util::{block_from_test_ctx_with_params, CircuitTestBuilder},
+    };
+    use bus_mapping::circuit_input_builder::CircuitsParams;
+    use eth_types::{bytecode, bytecode::Bytecode, ToWord, Word};
+    use mock::TestContext;
+
+    /// Code STATICCALLing `callee`.
+    fn static_caller(callee: Word) -> Bytecode {
+        bytecode! {
+            PUSH1(0x00) // retLength
+            PUSH1(0x00) // retOffset
+            PUSH1(0x00) // argsLength
+            PUSH1(0x00) // argsOffset
+            PUSH32(callee) // addr
+            PUSH32(0x1_0000) // gas
+            STATICCALL
+            STOP
+        }
+    }
+
+    fn test_ok(callee_code: Bytecode) {
+        let (addr_a, addr_b) = (mock::MOCK_ACCOUNTS[0], mock::MOCK_ACCOUNTS[1]);
+
+        let ctx = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(addr_a)
+                    .code(static_caller(addr_b.to_word()));
+                accs[1]
+                    .address(addr_b)
+                    .balance(Word::from(1u64 << 20))
+                    .code(callee_code);
+                accs[2]
+                    .address(mock::MOCK_ACCOUNTS[2])
+                    .balance(Word::from(1u64 << 30));
+            },
+            |mut txs, accs| {
+                txs[0].to(accs[0].address).from(accs[2].address);
+            },
+            |block, _tx| block,
+        )
+        .unwrap();
+
+        let block = block_from_test_ctx_with_params(
+            ctx,
+            CircuitsParams {
+                max_rws: 2048,
+                ..Default::default()
+            },
+        );
+        assert!(block.txs[0]
+            .steps
+            .iter()
+            .any(|step| step.execution_state == ExecutionState::ErrorWriteProtection));
+
+        CircuitTestBuilder::<3, 1>::new_from_block(block).run();
+    }
+
+    #[test]
+    fn write_protection_sstore() {
+        test_ok(bytecode! {
+            PUSH1(0x01) // value
+            PUSH1(0x00) // key
+            SSTORE
+            STOP
+        });
+    }
+
+    #[test]
+    fn write_protection_log() {
+        test_ok(bytecode! {
+            PUSH1(0x00) // size
+            PUSH1(0x00) // offset
+            LOG0
+            STOP
+        });
+    }
+
+    #[test]
+    fn write_protection_create() {
+        test_ok(bytecode! {
+            PUSH1(0x00) // size
+            PUSH1(0x00) // offset
+            PUSH1(0x00) // value
+            CREATE
+            STOP
+        });
+    }
+
+    #[test]
+    fn write_protection_selfdestruct() {
+        test_ok(bytecode! {
+            PUSH32(mock::MOCK_ACCOUNTS[2].to_word()) // beneficiary
+            SELFDESTRUCT
+        });
+    }
+
+    #[test]
+    fn write_protection_call_with_value() {
+        test_ok(bytecode! {
+            PUSH1(0x00) // retLength
+            PUSH1(0x00) // retOffset
+            PUSH1(0x00) // argsLength
+            PUSH1(0x00) // argsOffset
+            PUSH1(0x01) // value
+            PUSH32(mock::MOCK_ACCOUNTS[2].to_word()) // addr
+            PUSH32(0x1000) // gas
+            CALL
+            STOP
+        });
+    }
+}
@@ -255,7 +255,7 @@ impl<F: Field> ExecutionGadget<F> for ExponentiationGadget<F> {
 #[cfg(test)]
 mod tests {
     use crate::test_util::CircuitTestBuilder;
-    use eth_types::{bytecode, Word};
+    use eth_types::{bytecode, evm_types::OpcodeId, Word};
     use mock::TestContext;
 
     fn test_ok(base: Word, exponent: Word) {
@@ -271,6 +271,31 @@ mod tests {
         .run();
     }
 
+    fn test_gas_cost(exponent: Word, exponent_byte_size: u64) {
+        let code = bytecode! {
+            PUSH32(exponent)
+            PUSH32(3)
+            EXP
+            STOP
+        };
+        let ctx = TestContext::<2, 1>::simple_ctx_with_bytecode(code).unwrap();
+
+        // 10 static gas plus 50 per significant byte of the exponent
+        let struct_logs = &ctx.geth_traces[0].struct_logs;
+        let idx = struct_logs
+            .iter()
+            .position(|step| step.op == OpcodeId::EXP)
+            .unwrap();
+        let expected = 10 + 50 * exponent_byte_size;
+        assert_eq!(struct_logs[idx].gas_cost.as_u64(), expected);
+        assert_eq!(
+            struct_logs[idx].gas.0 - struct_logs[idx + 1].gas.0,
+            expected
+        );
+
+        CircuitTestBuilder::new_from_test_ctx(ctx).run();
+    }
+
     #[test]
     fn exp_gadget_zero() {
         test_ok(Word::zero(), Word::zero());
@@ -300,4 +325,13 @@ mod tests {
         test_ok(Word::MAX, 2.into());
         test_ok(Word::MAX, 3.into());
     }
+
+    #[test]
+    fn exp_gadget_gas_cost() {
+        test_gas_cost(Word::zero(), 0);
+        test_gas_cost(255.into(), 1);
+        test_gas_cost(0x1234.into(), 2);
+        test_gas_cost(Word::one() << 128, 17);
+        test_gas_cost(Word::MAX, 32);
+    }
 }
@@ -60,12 +60,10 @@ impl<F: Field> ExecutionGadget<F> for ExtcodecopyGadget<F> {
 
         let tx_id = cb.call_context(None, CallContextFieldTag::TxId);
         let mut reversion_info = cb.reversion_info_read(None);
-        let is_warm = cb.query_bool();
-        cb.account_access_list_write(
+        let is_warm = cb.add_to_access_list(
             tx_id.expr(),
             from_bytes::expr(&external_address.cells),
-            1.expr(),
-            is_warm.expr(),
+            None,
             Some(&mut reversion_info),
         );
 
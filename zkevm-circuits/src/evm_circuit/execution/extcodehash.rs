@@ -41,12 +41,10 @@ impl<F: Field> ExecutionGadget<F> for ExtcodehashGadget<F> {
         let tx_id = cb.call_context(None, CallContextFieldTag::TxId);
         let mut reversion_info = cb.reversion_info_read(None);
 
-        let is_warm = cb.query_bool();
-        cb.account_access_list_write(
+        let is_warm = cb.add_to_access_list(
             tx_id.expr(),
             address.expr(),
-            1.expr(),
-            is_warm.expr(),
+            None,
             Some(&mut reversion_info),
         );
 
@@ -120,10 +118,14 @@ impl<F: Field> ExecutionGadget<F> for ExtcodehashGadget<F> {
 
 #[cfg(test)]
 mod test {
-    use crate::test_util::CircuitTestBuilder;
+    use crate::{
+        evm_circuit::step::ExecutionState,
+        test_util::{block_from_test_ctx, CircuitTestBuilder},
+    };
     use eth_types::{
         address, bytecode, geth_types::Account, Address, Bytecode, Bytes, ToWord, Word, U256,
     };
+    use ethers_core::utils::keccak256;
     use lazy_static::lazy_static;
     use mock::TestContext;
 
@@ -132,7 +134,7 @@ mod test {
             address!("0xaabbccddee000000000000000000000000000000");
     }
 
-    fn test_ok(external_account: Option<Account>, is_warm: bool) {
+    fn test_ctx(external_account: Option<Account>, is_warm: bool) -> TestContext<3, 1> {
         let external_address = external_account
             .as_ref()
             .map(|a| a.address)
@@ -156,7 +158,7 @@ mod test {
         });
 
         // Execute the bytecode and get trace
-        let ctx = TestContext::<3, 1>::new(
+        TestContext::<3, 1>::new(
             None,
             |accs| {
                 accs[0]
@@ -180,9 +182,61 @@ mod test {
             },
             |block, _tx| block.number(0xcafeu64),
         )
-        .unwrap();
+        .unwrap()
+    }
+
+    fn test_ok(external_account: Option<Account>, is_warm: bool) {
+        CircuitTestBuilder::new_from_test_ctx(test_ctx(external_account, is_warm)).run();
+    }
+
+    /// Check the code hash pushed by EXTCODEHASH, which is read from the rw
+    /// table where a non-existing account has a code hash of 0.
+    fn test_code_hash(external_account: Option<Account>, expected: Word) {
+        let block = block_from_test_ctx(test_ctx(external_account, false));
+
+        let step = block.txs[0]
+            .steps
+            .iter()
+            .find(|step| step.execution_state == ExecutionState::EXTCODEHASH)
+            .unwrap();
+        assert_eq!(
+            block.rws[step.rw_indices[5]].account_value_pair().0,
+            expected
+        );
+        assert_eq!(block.rws[step.rw_indices[6]].stack_value(), expected);
+
+        CircuitTestBuilder::<3, 1>::new_from_block(block).run();
+    }
+
+    #[test]
+    fn extcodehash_nonexistent_account_is_zero() {
+        test_code_hash(None, Word::zero());
+    }
 
-        CircuitTestBuilder::new_from_test_ctx(ctx).run();
+    #[test]
+    fn extcodehash_account_without_code_is_empty_hash() {
+        test_code_hash(
+            Some(Account {
+                address: *EXTERNAL_ADDRESS,
+                balance: U256::from(900),
+                ..Default::default()
+            }),
+            Word::from_big_endian(&keccak256(Bytes::default())),
+        );
+    }
+
+    #[test]
+    fn extcodehash_contract_is_code_hash() {
+        let code = Bytes::from([32, 59]);
+        test_code_hash(
+            Some(Account {
+                address: *EXTERNAL_ADDRESS,
+                nonce: U256::from(1),
+                code: code.clone(),
+                ..Default::default()
+            }),
+            Word::from_big_endian(&keccak256(&code)),
+        );
     }
 
     #[test]
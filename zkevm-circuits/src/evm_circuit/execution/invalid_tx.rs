@@ -0,0 +1,426 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::N_BYTES_GAS,
+        step::ExecutionState,
+        util::{
+            constraint_builder::{
+                ConstraintBuilder, StepStateTransition,
+                Transition::{Delta, To},
+            },
+            math_gadget::{
+                AddWordsGadget, IsEqualGadget, LtGadget, LtWordGadget, MulWordByU64Gadget,
+            },
+            not, or, select, CachedRegion, Cell, Word,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    table::{AccountFieldTag, CallContextFieldTag, TxContextFieldTag, TxReceiptFieldTag},
+    util::Expr,
+};
+use eth_types::{evm_types::GasCost, Field, ToLittleEndian, ToScalar};
+use halo2_proofs::{circuit::Value, plonk::Error};
+
+/// Gadget for a tx which is rejected before execution. At least one of the
+/// following must hold:
+/// - tx nonce doesn't match the caller's nonce
+/// - tx nonce is `2^64 - 1`, which can't be increased (EIP-2681)
+/// - tx gas is below the intrinsic gas
+/// - caller's balance can't cover `gas * gas_price + value`
+///
+/// Like clients do, the tx is skipped: nothing is charged and the state is
+/// left untouched, only an empty receipt is written.
+#[derive(Clone, Debug)]
+pub(crate) struct InvalidTxGadget<F> {
+    tx_id: Cell<F>,
+    tx_nonce: Cell<F>,
+    tx_gas: Cell<F>,
+    tx_caller_address: Cell<F>,
+    tx_is_create: Cell<F>,
+    tx_call_data_gas_cost: Cell<F>,
+    tx_gas_price: Word<F>,
+    tx_value: Word<F>,
+    caller_nonce: Cell<F>,
+    caller_balance: Word<F>,
+    is_nonce_match: IsEqualGadget<F>,
+    is_nonce_max: IsEqualGadget<F>,
+    is_insufficient_gas: LtGadget<F, N_BYTES_GAS>,
+    mul_gas_fee_by_gas: MulWordByU64Gadget<F>,
+    total_cost: AddWordsGadget<F, 2, false>,
+    is_insufficient_balance: LtWordGadget<F>,
+    current_cumulative_gas_used: Cell<F>,
+    is_first_tx: IsEqualGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for InvalidTxGadget<F> {
+    const NAME: &'static str = "InvalidTx";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::InvalidTx;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        // Use rw_counter of the step as call_id, like BeginTx does.
+        let call_id = cb.curr.state.rw_counter.clone();
+
+        let tx_id = cb.query_cell();
+        cb.call_context_lookup(
+            1.expr(),
+            Some(call_id.expr()),
+            CallContextFieldTag::TxId,
+            tx_id.expr(),
+        );
+
+        cb.step_first(|cb| {
+            cb.require_equal("tx_id is initialized to be 1", tx_id.expr(), 1.expr());
+        });
+
+        let [tx_nonce, tx_gas, tx_caller_address, tx_is_create, tx_call_data_gas_cost] = [
+            TxContextFieldTag::Nonce,
+            TxContextFieldTag::Gas,
+            TxContextFieldTag::CallerAddress,
+            TxContextFieldTag::IsCreate,
+            TxContextFieldTag::CallDataGasCost,
+        ]
+        .map(|field_tag| cb.tx_context(tx_id.expr(), field_tag, None));
+        let [tx_gas_price, tx_value] = [TxContextFieldTag::GasPrice, TxContextFieldTag::Value]
+            .map(|field_tag| cb.tx_context_as_word(tx_id.expr(), field_tag, None));
+
+        // Read (not write) the caller's nonce and balance, since an invalid tx
+        // doesn't modify them.
+        let caller_nonce = cb.query_cell();
+        cb.account_read(
+            tx_caller_address.expr(),
+            AccountFieldTag::Nonce,
+            caller_nonce.expr(),
+        );
+        let caller_balance = cb.query_word_rlc();
+        cb.account_read(
+            tx_caller_address.expr(),
+            AccountFieldTag::Balance,
+            caller_balance.expr(),
+        );
+
+        // 1. Nonce mismatch
+        let is_nonce_match = IsEqualGadget::construct(cb, tx_nonce.expr(), caller_nonce.expr());
+
+        // 2. Nonce at its maximum (EIP-2681)
+        let is_nonce_max = IsEqualGadget::construct(cb, tx_nonce.expr(), u64::MAX.expr());
+
+        // 3. Gas limit below intrinsic gas
+        let intrinsic_gas_cost = select::expr(
+            tx_is_create.expr(),
+            GasCost::CREATION_TX.expr(),
+            GasCost::TX.expr(),
+        ) + tx_call_data_gas_cost.expr();
+        let is_insufficient_gas = LtGadget::construct(cb, tx_gas.expr(), intrinsic_gas_cost);
+
+        // 4. Balance can't cover the up-front cost. The sum of the gas fee and
+        // the value can overflow 256 bits, in which case it's never
+        // affordable. The gas fee itself must fit in 256 bits, since
+        // MulWordByU64Gadget disallows overflow.
+        let mul_gas_fee_by_gas =
+            MulWordByU64Gadget::construct(cb, tx_gas_price.clone(), tx_gas.expr());
+        let total_cost = cb.query_word_rlc();
+        let total_cost = AddWordsGadget::construct(
+            cb,
+            [mul_gas_fee_by_gas.product().clone(), tx_value.clone()],
+            total_cost,
+        );
+        let is_insufficient_balance =
+            LtWordGadget::construct(cb, &caller_balance, total_cost.sum());
+
+        cb.require_zero(
+            "tx is invalid for at least one reason",
+            is_nonce_match.expr()
+                * not::expr(is_nonce_max.expr())
+                * not::expr(is_insufficient_gas.expr())
+                * not::expr(or::expr([
+                    is_insufficient_balance.expr(),
+                    total_cost.carry().as_ref().unwrap().expr(),
+                ])),
+        );
+
+        // Write an empty receipt, the cumulative gas used stays the same.
+        cb.tx_receipt_lookup(
+            1.expr(),
+            tx_id.expr(),
+            TxReceiptFieldTag::PostStateOrStatus,
+            0.expr(),
+        );
+        cb.tx_receipt_lookup(
+            1.expr(),
+            tx_id.expr(),
+            TxReceiptFieldTag::LogLength,
+            0.expr(),
+        );
+
+        let is_first_tx = IsEqualGadget::construct(cb, tx_id.expr(), 1.expr());
+
+        let current_cumulative_gas_used = cb.query_cell();
+        cb.condition(is_first_tx.expr(), |cb| {
+            cb.require_zero(
+                "current_cumulative_gas_used is zero when tx is first tx",
+                current_cumulative_gas_used.expr(),
+            );
+        });
+        cb.condition(not::expr(is_first_tx.expr()), |cb| {
+            cb.tx_receipt_lookup(
+                0.expr(),
+                tx_id.expr() - 1.expr(),
+                TxReceiptFieldTag::CumulativeGasUsed,
+                current_cumulative_gas_used.expr(),
+            );
+        });
+        cb.tx_receipt_lookup(
+            1.expr(),
+            tx_id.expr(),
+            TxReceiptFieldTag::CumulativeGasUsed,
+            current_cumulative_gas_used.expr(),
+        );
+
+        cb.condition(
+            cb.next
+                .execution_state_selector([ExecutionState::BeginTx, ExecutionState::InvalidTx]),
+            |cb| {
+                cb.call_context_lookup(
+                    true.expr(),
+                    Some(cb.next.state.rw_counter.expr()),
+                    CallContextFieldTag::TxId,
+                    tx_id.expr() + 1.expr(),
+                );
+
+                cb.require_step_state_transition(StepStateTransition {
+                    rw_counter: Delta(8.expr() - is_first_tx.expr()),
+                    ..StepStateTransition::any()
+                });
+            },
+        );
+
+        cb.condition(
+            cb.next.execution_state_selector([ExecutionState::EndBlock]),
+            |cb| {
+                cb.require_step_state_transition(StepStateTransition {
+                    rw_counter: Delta(7.expr() - is_first_tx.expr()),
+                    // EndBlock reads the last tx_id from the call context.
+                    call_id: To(call_id.expr()),
+                    ..StepStateTransition::any()
+                });
+            },
+        );
+
+        Self {
+            tx_id,
+            tx_nonce,
+            tx_gas,
+            tx_caller_address,
+            tx_is_create,
+            tx_call_data_gas_cost,
+            tx_gas_price,
+            tx_value,
+            caller_nonce,
+            caller_balance,
+            is_nonce_match,
+            is_nonce_max,
+            is_insufficient_gas,
+            mul_gas_fee_by_gas,
+            total_cost,
+            is_insufficient_balance,
+            current_cumulative_gas_used,
+            is_first_tx,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        tx: &Transaction,
+        _: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let [(caller_nonce, _), (caller_balance, _)] =
+            [step.rw_indices[1], step.rw_indices[2]].map(|idx| block.rws[idx].account_value_pair());
+        // The receipt of the first tx has no read of the previous cumulative gas.
+        let current_cumulative_gas_used =
+            block.rws[step.rw_indices[if tx.id == 1 { 5 } else { 6 }]].receipt_value();
+
+        self.tx_id
+            .assign(region, offset, Value::known(F::from(tx.id as u64)))?;
+        self.tx_nonce
+            .assign(region, offset, Value::known(F::from(tx.nonce)))?;
+        self.tx_gas
+            .assign(region, offset, Value::known(F::from(tx.gas)))?;
+        self.tx_caller_address.assign(
+            region,
+            offset,
+            Value::known(
+                tx.caller_address
+                    .to_scalar()
+                    .expect("unexpected Address -> Scalar conversion failure"),
+            ),
+        )?;
+        self.tx_is_create
+            .assign(region, offset, Value::known(F::from(tx.is_create as u64)))?;
+        self.tx_call_data_gas_cost.assign(
+            region,
+            offset,
+            Value::known(F::from(tx.call_data_gas_cost)),
+        )?;
+        self.tx_gas_price
+            .assign(region, offset, Some(tx.gas_price.to_le_bytes()))?;
+        self.tx_value
+            .assign(region, offset, Some(tx.value.to_le_bytes()))?;
+
+        let caller_nonce = caller_nonce
+            .to_scalar()
+            .expect("unexpected U256 -> Scalar conversion failure");
+        self.caller_nonce
+            .assign(region, offset, Value::known(caller_nonce))?;
+        self.caller_balance
+            .assign(region, offset, Some(caller_balance.to_le_bytes()))?;
+
+        self.is_nonce_match
+            .assign(region, offset, F::from(tx.nonce), caller_nonce)?;
+        self.is_nonce_max
+            .assign(region, offset, F::from(tx.nonce), F::from(u64::MAX))?;
+
+        let intrinsic_gas_cost = if tx.is_create {
+            GasCost::CREATION_TX.as_u64()
+        } else {
+            GasCost::TX.as_u64()
+        } + tx.call_data_gas_cost;
+        self.is_insufficient_gas.assign(
+            region,
+            offset,
+            F::from(tx.gas),
+            F::from(intrinsic_gas_cost),
+        )?;
+
+        let gas_fee = tx
+            .gas_price
+            .checked_mul(tx.gas.into())
+            .ok_or(Error::Synthesis)?;
+        self.mul_gas_fee_by_gas
+            .assign(region, offset, tx.gas_price, tx.gas, gas_fee)?;
+        let (total_cost, _) = gas_fee.overflowing_add(tx.value);
+        self.total_cost
+            .assign(region, offset, [gas_fee, tx.value], total_cost)?;
+        self.is_insufficient_balance
+            .assign(region, offset, caller_balance, total_cost)?;
+
+        self.current_cumulative_gas_used.assign(
+            region,
+            offset,
+            Value::known(F::from(current_cumulative_gas_used)),
+        )?;
+        self.is_first_tx
+            .assign(region, offset, F::from(tx.id as u64), F::one())?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::CircuitTestBuilder;
+    use eth_types::{self, bytecode, evm_types::GasCost, Word};
+    use mock::{eth, gwei, TestContext, MOCK_ACCOUNTS};
+
+    fn test_invalid_tx(nonce: u64, gas: u64, balance: Word) {
+        let ctx = TestContext::<2, 1>::new(
+            None,
+            |accs| {
+                accs[0].address(MOCK_ACCOUNTS[0]).balance(balance);
+                accs[1]
+                    .address(MOCK_ACCOUNTS[1])
+                    .balance(eth(10))
+                    .code(bytecode! { STOP });
+            },
+            |mut txs, accs| {
+                txs[0]
+                    .from(accs[0].address)
+                    .to(accs[1].address)
+                    .nonce(Word::from(nonce))
+                    .gas(Word::from(gas))
+                    .gas_price(gwei(2))
+                    .value(eth(1));
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap();
+        assert!(ctx.geth_traces[0].invalid);
+
+        CircuitTestBuilder::new_from_test_ctx(ctx).run();
+    }
+
+    #[test]
+    fn invalid_tx_nonce_mismatch() {
+        test_invalid_tx(1, 100_000, eth(10));
+    }
+
+    #[test]
+    fn invalid_tx_insufficient_balance() {
+        test_invalid_tx(0, 100_000, eth(1));
+    }
+
+    #[test]
+    fn invalid_tx_gas_below_intrinsic() {
+        test_invalid_tx(0, GasCost::TX.as_u64() - 1, eth(10));
+    }
+
+    #[test]
+    fn invalid_tx_nonce_max() {
+        let ctx = TestContext::<2, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(MOCK_ACCOUNTS[0])
+                    .balance(eth(10))
+                    .nonce(Word::from(u64::MAX));
+                accs[1].address(MOCK_ACCOUNTS[1]).balance(eth(10));
+            },
+            |mut txs, accs| {
+                txs[0]
+                    .from(accs[0].address)
+                    .to(accs[1].address)
+                    .nonce(Word::from(u64::MAX))
+                    .value(eth(1));
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap();
+        assert!(ctx.geth_traces[0].invalid);
+
+        CircuitTestBuilder::new_from_test_ctx(ctx).run();
+    }
+
+    #[test]
+    fn invalid_tx_followed_by_valid_tx() {
+        let ctx = TestContext::<2, 2>::new(
+            None,
+            |accs| {
+                accs[0].address(MOCK_ACCOUNTS[0]).balance(eth(10));
+                accs[1].address(MOCK_ACCOUNTS[1]).balance(eth(10));
+            },
+            |mut txs, accs| {
+                // Nonce is ahead of the account's, this tx is skipped.
+                txs[0]
+                    .from(accs[0].address)
+                    .to(accs[1].address)
+                    .nonce(Word::from(5))
+                    .value(eth(1));
+                txs[1]
+                    .from(accs[0].address)
+                    .to(accs[1].address)
+                    .nonce(Word::zero())
+                    .value(eth(1));
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap();
+        assert!(ctx.geth_traces[0].invalid);
+        assert!(!ctx.geth_traces[1].invalid);
+
+        CircuitTestBuilder::new_from_test_ctx(ctx).run();
+    }
+}
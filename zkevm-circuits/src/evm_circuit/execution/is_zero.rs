@@ -5,21 +5,21 @@ use crate::{
         util::{
             common_gadget::SameContextGadget,
             constraint_builder::{ConstraintBuilder, StepStateTransition, Transition::Delta},
-            math_gadget, CachedRegion, Cell,
+            math_gadget, CachedRegion, Word,
         },
         witness::{Block, Call, ExecStep, Transaction},
     },
     util::Expr,
 };
 use bus_mapping::evm::OpcodeId;
-use eth_types::Field;
+use eth_types::{Field, ToLittleEndian};
 use halo2_proofs::plonk::Error;
 
 #[derive(Clone, Debug)]
 pub(crate) struct IsZeroGadget<F> {
     same_context: SameContextGadget<F>,
-    value: Cell<F>,
-    is_zero: math_gadget::IsZeroGadget<F>,
+    value: Word<F>,
+    is_zero: math_gadget::IsZeroWordGadget<F>,
 }
 
 impl<F: Field> ExecutionGadget<F> for IsZeroGadget<F> {
@@ -30,8 +30,8 @@ impl<F: Field> ExecutionGadget<F> for IsZeroGadget<F> {
     fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
         let opcode = cb.query_cell();
 
-        let value = cb.query_cell_phase2();
-        let is_zero = math_gadget::IsZeroGadget::construct(cb, value.expr());
+        let value = cb.query_word_rlc();
+        let is_zero = math_gadget::IsZeroWordGadget::construct(cb, &value);
 
         cb.stack_pop(value.expr());
         cb.stack_push(is_zero.expr());
@@ -65,9 +65,9 @@ impl<F: Field> ExecutionGadget<F> for IsZeroGadget<F> {
         self.same_context.assign_exec_step(region, offset, step)?;
 
         let value = block.rws[step.rw_indices[0]].stack_value();
-        let value = region.word_rlc(value);
-        self.value.assign(region, offset, value)?;
-        self.is_zero.assign_value(region, offset, value)?;
+        self.value
+            .assign(region, offset, Some(value.to_le_bytes()))?;
+        self.is_zero.assign(region, offset, value)?;
 
         Ok(())
     }
@@ -10,8 +10,8 @@ use crate::{
                 Transition::{Delta, To},
             },
             from_bytes,
-            math_gadget::IsZeroGadget,
-            select, CachedRegion, Cell, RandomLinearCombination,
+            math_gadget::IsZeroWordGadget,
+            select, CachedRegion, RandomLinearCombination, Word,
         },
         witness::{Block, Call, ExecStep, Transaction},
     },
@@ -24,8 +24,8 @@ use halo2_proofs::plonk::Error;
 pub(crate) struct JumpiGadget<F> {
     same_context: SameContextGadget<F>,
     destination: RandomLinearCombination<F, N_BYTES_PROGRAM_COUNTER>,
-    phase2_condition: Cell<F>,
-    is_condition_zero: IsZeroGadget<F>,
+    condition: Word<F>,
+    is_condition_zero: IsZeroWordGadget<F>,
 }
 
 impl<F: Field> ExecutionGadget<F> for JumpiGadget<F> {
@@ -35,14 +35,14 @@ impl<F: Field> ExecutionGadget<F> for JumpiGadget<F> {
 
     fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
         let destination = cb.query_word_rlc();
-        let phase2_condition = cb.query_cell_phase2();
+        let condition = cb.query_word_rlc();
 
         // Pop the value from the stack
         cb.stack_pop(destination.expr());
-        cb.stack_pop(phase2_condition.expr());
+        cb.stack_pop(condition.expr());
 
         // Determine if the jump condition is met
-        let is_condition_zero = IsZeroGadget::construct(cb, phase2_condition.expr());
+        let is_condition_zero = IsZeroWordGadget::construct(cb, &condition);
         let should_jump = 1.expr() - is_condition_zero.expr();
 
         // Lookup opcode at destination when should_jump
@@ -76,7 +76,7 @@ impl<F: Field> ExecutionGadget<F> for JumpiGadget<F> {
         Self {
             same_context,
             destination,
-            phase2_condition,
+            condition,
             is_condition_zero,
         }
     }
@@ -94,7 +94,6 @@ impl<F: Field> ExecutionGadget<F> for JumpiGadget<F> {
 
         let [destination, condition] =
             [step.rw_indices[0], step.rw_indices[1]].map(|idx| block.rws[idx].stack_value());
-        let condition = region.word_rlc(condition);
 
         self.destination.assign(
             region,
@@ -105,9 +104,9 @@ impl<F: Field> ExecutionGadget<F> for JumpiGadget<F> {
                     .unwrap(),
             ),
         )?;
-        self.phase2_condition.assign(region, offset, condition)?;
-        self.is_condition_zero
-            .assign_value(region, offset, condition)?;
+        self.condition
+            .assign(region, offset, Some(condition.to_le_bytes()))?;
+        self.is_condition_zero.assign(region, offset, condition)?;
 
         Ok(())
     }
@@ -32,7 +32,6 @@ pub(crate) struct LogGadget<F> {
     topic_selectors: [Cell<F>; 4],
 
     contract_address: Cell<F>,
-    is_static_call: Cell<F>,
     is_persistent: Cell<F>,
     tx_id: Cell<F>,
     copy_rwc_inc: Cell<F>,
@@ -54,8 +53,7 @@ impl<F: Field> ExecutionGadget<F> for LogGadget<F> {
         // read tx id
         let tx_id = cb.call_context(None, CallContextFieldTag::TxId);
         // constrain not in static call
-        let is_static_call = cb.call_context(None, CallContextFieldTag::IsStatic);
-        cb.require_zero("is_static_call is false", is_static_call.expr());
+        cb.require_not_static();
 
         // check contract_address in CallContext & TxLog
         // use call context's  callee address as contract address
@@ -173,7 +171,6 @@ impl<F: Field> ExecutionGadget<F> for LogGadget<F> {
             phase2_topics,
             topic_selectors,
             contract_address,
-            is_static_call,
             is_persistent,
             tx_id,
             copy_rwc_inc,
@@ -237,8 +234,6 @@ impl<F: Field> ExecutionGadget<F> for LogGadget<F> {
             ),
         )?;
 
-        self.is_static_call
-            .assign(region, offset, Value::known(F::from(call.is_static as u64)))?;
         self.is_persistent
             .assign(region, offset, Value::known(F::from(is_persistent)))?;
         self.tx_id
@@ -175,11 +175,11 @@ impl<F: Field> ExecutionGadget<F> for MemoryGadget<F> {
 
 #[cfg(test)]
 mod test {
-    use crate::evm_circuit::test::rand_word;
-    use crate::test_util::CircuitTestBuilder;
+    use crate::evm_circuit::{step::ExecutionState, test::rand_word};
+    use crate::test_util::{block_from_test_ctx, CircuitTestBuilder};
     use eth_types::bytecode;
     use eth_types::evm_types::{GasCost, OpcodeId};
-    use eth_types::Word;
+    use eth_types::{ToWord, Word};
     use mock::test_ctx::{helpers::*, TestContext};
     use std::iter;
 
@@ -285,4 +285,73 @@ mod test {
             21000,
         );
     }
+
+    #[test]
+    fn memory_isolated_per_call() {
+        let (addr_a, addr_b) = (mock::MOCK_ACCOUNTS[0], mock::MOCK_ACCOUNTS[1]);
+        let caller_value = Word::from_big_endian(&[0xaa; 32]);
+        let callee_value = Word::from_big_endian(&[0xbb; 32]);
+
+        // The caller and the callee both write at offset 0, and the caller
+        // reads back its own memory after the call.
+        let code_a = bytecode! {
+            PUSH32(caller_value)
+            PUSH1(0x00)
+            MSTORE
+            PUSH1(0x00) // retLength
+            PUSH1(0x00) // retOffset
+            PUSH1(0x00) // argsLength
+            PUSH1(0x00) // argsOffset
+            PUSH1(0x00) // value
+            PUSH32(addr_b.to_word()) // addr
+            PUSH32(0x1_0000) // gas
+            CALL
+            PUSH1(0x00)
+            MLOAD
+            STOP
+        };
+        let code_b = bytecode! {
+            PUSH32(callee_value)
+            PUSH1(0x00)
+            MSTORE
+            STOP
+        };
+
+        let ctx = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0].address(addr_a).code(code_a);
+                accs[1].address(addr_b).code(code_b);
+                accs[2]
+                    .address(mock::MOCK_ACCOUNTS[2])
+                    .balance(Word::from(1u64 << 30));
+            },
+            |mut txs, accs| {
+                txs[0].to(accs[0].address).from(accs[2].address);
+            },
+            |block, _tx| block,
+        )
+        .unwrap();
+
+        let block = block_from_test_ctx(ctx);
+
+        let memory_steps = block.txs[0]
+            .steps
+            .iter()
+            .filter(|step| step.execution_state == ExecutionState::MEMORY)
+            .collect::<Vec<_>>();
+        assert_eq!(memory_steps.len(), 3);
+        let (caller_mstore, callee_mstore, caller_mload) =
+            (memory_steps[0], memory_steps[1], memory_steps[2]);
+        // The memory rows are keyed by the call id, so the callee gets a memory
+        // of its own.
+        assert_ne!(caller_mstore.call_index, callee_mstore.call_index);
+        assert_eq!(caller_mstore.call_index, caller_mload.call_index);
+        assert_eq!(
+            block.rws[caller_mload.rw_indices[1]].stack_value(),
+            caller_value
+        );
+
+        CircuitTestBuilder::<3, 1>::new_from_block(block).run();
+    }
 }
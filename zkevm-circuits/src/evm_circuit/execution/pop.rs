@@ -5,7 +5,8 @@ use crate::{
         util::{
             common_gadget::SameContextGadget,
             constraint_builder::{ConstraintBuilder, StepStateTransition, Transition::Delta},
-            CachedRegion, Cell,
+            word_rlc::WordRlc,
+            CachedRegion,
         },
         witness::{Block, Call, ExecStep, Transaction},
     },
@@ -18,7 +19,7 @@ use halo2_proofs::plonk::Error;
 #[derive(Clone, Debug)]
 pub(crate) struct PopGadget<F> {
     same_context: SameContextGadget<F>,
-    phase2_value: Cell<F>,
+    value: WordRlc<F>,
 }
 
 impl<F: Field> ExecutionGadget<F> for PopGadget<F> {
@@ -27,10 +28,10 @@ impl<F: Field> ExecutionGadget<F> for PopGadget<F> {
     const EXECUTION_STATE: ExecutionState = ExecutionState::POP;
 
     fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
-        let phase2_value = cb.query_cell_phase2();
+        let value = WordRlc::construct(cb);
 
         // Pop the value from the stack
-        cb.stack_pop(phase2_value.expr());
+        cb.stack_pop(value.expr());
 
         // State transition
         let step_state_transition = StepStateTransition {
@@ -45,7 +46,7 @@ impl<F: Field> ExecutionGadget<F> for PopGadget<F> {
 
         Self {
             same_context,
-            phase2_value,
+            value,
         }
     }
 
@@ -61,8 +62,7 @@ impl<F: Field> ExecutionGadget<F> for PopGadget<F> {
         self.same_context.assign_exec_step(region, offset, step)?;
 
         let value = block.rws[step.rw_indices[0]].stack_value();
-        self.phase2_value
-            .assign(region, offset, region.word_rlc(value))?;
+        self.value.assign(region, offset, value)?;
 
         Ok(())
     }
@@ -3,23 +3,21 @@ use crate::{
         execution::ExecutionGadget,
         step::ExecutionState,
         util::{
-            common_gadget::SameContextGadget,
+            common_gadget::{PushDataGadget, SameContextGadget},
             constraint_builder::{ConstraintBuilder, StepStateTransition, Transition::Delta},
-            sum, CachedRegion, Cell, Word,
+            CachedRegion,
         },
         witness::{Block, Call, ExecStep, Transaction},
     },
     util::Expr,
 };
-use array_init::array_init;
-use eth_types::{evm_types::OpcodeId, Field, ToLittleEndian};
-use halo2_proofs::{circuit::Value, plonk::Error};
+use eth_types::{evm_types::OpcodeId, Field};
+use halo2_proofs::plonk::Error;
 
 #[derive(Clone, Debug)]
 pub(crate) struct PushGadget<F> {
     same_context: SameContextGadget<F>,
-    value: Word<F>,
-    selectors: [Cell<F>; 31],
+    push_data: PushDataGadget<F>,
 }
 
 impl<F: Field> ExecutionGadget<F> for PushGadget<F> {
@@ -30,69 +28,13 @@ impl<F: Field> ExecutionGadget<F> for PushGadget<F> {
     fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
         let opcode = cb.query_cell();
 
-        let value = cb.query_word_rlc();
-        // Query selectors for each opcode_lookup
-        let selectors = array_init(|_| cb.query_bool());
-
-        // The pushed bytes are viewed as left-padded big-endian, but our random
-        // linear combination uses little-endian, so we lookup from the LSB
-        // which has index (program_counter + num_pushed), and then move left
-        // (program_counter + num_pushed - idx) to lookup all 32 bytes
-        // condiionally by selectors.
-        // For PUSH2 as an example, we lookup from byte0, byte1, ..., byte31,
-        // where the byte2 is actually the PUSH2 itself, and lookup are only
-        // enabled for byte0 and byte1.
-        //
-        //                    program_counter    program_counter + num_pushed(2)
-        //                           ▼                     ▼
-        //   [byte31,     ...,     byte2,     byte1,     byte0]
-        //
-        for idx in 0..32 {
-            let byte = &value.cells[idx];
-            let index = cb.curr.state.program_counter.expr() + opcode.expr()
-                - (OpcodeId::PUSH1.as_u8() - 1 + idx as u8).expr();
-            if idx == 0 {
-                cb.opcode_lookup_at(index, byte.expr(), 0.expr())
-            } else {
-                cb.condition(selectors[idx - 1].expr(), |cb| {
-                    cb.opcode_lookup_at(index, byte.expr(), 0.expr())
-                });
-            }
-        }
-
-        for idx in 0..31 {
-            let selector_prev = if idx == 0 {
-                // First selector will always be 1
-                1.expr()
-            } else {
-                selectors[idx - 1].expr()
-            };
-            // selector can transit from 1 to 0 only once as [1, 1, 1, ...,
-            // 0, 0, 0]
-            cb.require_boolean(
-                "Constrain selector can only transit from 1 to 0",
-                selector_prev - selectors[idx].expr(),
-            );
-            // byte should be 0 when selector is 0
-            cb.require_zero(
-                "Constrain byte == 0 when selector == 0",
-                value.cells[idx + 1].expr() * (1.expr() - selectors[idx].expr()),
-            );
-        }
-
-        // Deduce the number of additional bytes to push than PUSH1. Note that
-        // num_additional_pushed = n - 1 where n is the suffix number of PUSH*.
-        let num_additional_pushed = opcode.expr() - OpcodeId::PUSH1.as_u64().expr();
-        // Sum of selectors needs to be exactly the number of additional bytes
-        // that needs to be pushed.
-        cb.require_equal(
-            "Constrain sum of selectors equal to num_additional_pushed",
-            sum::expr(&selectors),
-            num_additional_pushed,
-        );
+        // Deduce the number of bytes pushed from the opcode, i.e. the n suffix
+        // of PUSH*.
+        let num_pushed = opcode.expr() - (OpcodeId::PUSH1.as_u64() - 1).expr();
+        let push_data = PushDataGadget::construct(cb, num_pushed);
 
         // Push the value on the stack
-        cb.stack_push(value.expr());
+        cb.stack_push(push_data.expr());
 
         // State transition
         // `program_counter` needs to be increased by number of bytes pushed + 1
@@ -107,8 +49,7 @@ impl<F: Field> ExecutionGadget<F> for PushGadget<F> {
 
         Self {
             same_context,
-            value,
-            selectors,
+            push_data,
         }
     }
 
@@ -118,25 +59,26 @@ impl<F: Field> ExecutionGadget<F> for PushGadget<F> {
         offset: usize,
         block: &Block<F>,
         _: &Transaction,
-        _: &Call,
+        call: &Call,
         step: &ExecStep,
     ) -> Result<(), Error> {
         self.same_context.assign_exec_step(region, offset, step)?;
 
         let opcode = step.opcode.unwrap();
+        let code = block
+            .bytecodes
+            .get(&call.code_hash)
+            .expect("could not find current environment's bytecode");
 
         let value = block.rws[step.rw_indices[0]].stack_value();
-        self.value
-            .assign(region, offset, Some(value.to_le_bytes()))?;
-
-        let num_additional_pushed = opcode.postfix().expect("opcode with postfix") - 1;
-        for (idx, selector) in self.selectors.iter().enumerate() {
-            selector.assign(
-                region,
-                offset,
-                Value::known(F::from((idx < num_additional_pushed as usize) as u64)),
-            )?;
-        }
+        self.push_data.assign(
+            region,
+            offset,
+            value,
+            opcode.postfix().expect("opcode with postfix") as u64,
+            step.program_counter,
+            code.bytes.len() as u64,
+        )?;
 
         Ok(())
     }
@@ -186,6 +128,34 @@ mod test {
         );
     }
 
+    #[test]
+    fn push_gadget_full_word() {
+        test_ok(OpcodeId::PUSH32, &[0xff; 32]);
+    }
+
+    #[test]
+    fn push_gadget_truncated_by_code_end() {
+        // The missing bytes are read as zero, and the execution stops at the
+        // end of the code.
+        for (opcode, bytes) in [
+            (OpcodeId::PUSH1, vec![]),
+            (OpcodeId::PUSH4, vec![0x12, 0x34]),
+            (OpcodeId::PUSH32, vec![0xff; 31]),
+        ] {
+            let mut bytecode = bytecode! {
+                .write_op(opcode)
+            };
+            for b in bytes {
+                bytecode.write(b, false);
+            }
+
+            CircuitTestBuilder::new_from_test_ctx(
+                TestContext::<2, 1>::simple_ctx_with_bytecode(bytecode).unwrap(),
+            )
+            .run();
+        }
+    }
+
     #[test]
     #[ignore]
     fn push_gadget_rand() {
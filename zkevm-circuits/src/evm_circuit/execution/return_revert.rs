@@ -9,7 +9,7 @@ use crate::{
                 ConstraintBuilder, ReversionInfo, StepStateTransition,
                 Transition::{Delta, To},
             },
-            math_gadget::{IsZeroGadget, MinMaxGadget},
+            math_gadget::{IsEqualGadget, IsZeroGadget, LtGadget, MinMaxGadget},
             memory_gadget::{MemoryAddressGadget, MemoryExpansionGadget},
             not, CachedRegion, Cell,
         },
@@ -19,7 +19,10 @@ use crate::{
     util::Expr,
 };
 use bus_mapping::{circuit_input_builder::CopyDataType, evm::OpcodeId};
-use eth_types::{Field, ToScalar, U256};
+use eth_types::{
+    evm_types::{INVALID_CODE_FIRST_BYTE, MAX_CODE_SIZE},
+    Field, ToScalar, U256,
+};
 use ethers_core::utils::keccak256;
 use halo2_proofs::{circuit::Value, plonk::Error};
 
@@ -40,7 +43,10 @@ pub(crate) struct ReturnRevertGadget<F> {
     return_data_length: Cell<F>,
 
     memory_expansion: MemoryExpansionGadget<F, 1, N_BYTES_MEMORY_WORD_SIZE>,
+    is_code_size_valid: LtGadget<F, N_BYTES_MEMORY_ADDRESS>,
     code_hash: Cell<F>,
+    code_first_byte: Cell<F>,
+    is_code_first_byte_invalid: IsEqualGadget<F>,
 
     caller_id: Cell<F>,
     address: Cell<F>,
@@ -60,6 +66,8 @@ impl<F: Field> ExecutionGadget<F> for ReturnRevertGadget<F> {
         let length = cb.query_word_rlc();
         cb.stack_pop(offset.expr());
         cb.stack_pop(length.expr());
+        // When length is zero no memory is accessed, so the offset is ignored
+        // and doesn't have to fit in a memory address.
         let range = MemoryAddressGadget::construct(cb, offset, length);
 
         let is_success = cb.call_context(None, CallContextFieldTag::IsSuccess);
@@ -85,51 +93,87 @@ impl<F: Field> ExecutionGadget<F> for ReturnRevertGadget<F> {
         let memory_expansion = MemoryExpansionGadget::construct(cb, [range.address()]);
 
         // Case A in the specs.
+        let is_code_size_valid =
+            LtGadget::construct(cb, range.length(), (MAX_CODE_SIZE + 1).expr());
         cb.condition(is_create.clone() * is_success.expr(), |cb| {
             cb.require_equal(
                 "increase rw counter once for each memory to bytecode byte copied",
                 copy_rw_increase.expr(),
                 range.length(),
             );
+            // A longer code fails the creation with ErrorMaxCodeSizeExceeded
+            // (EIP-170).
+            cb.require_equal(
+                "deployed code size is valid",
+                is_code_size_valid.expr(),
+                1.expr(),
+            );
         });
 
         let is_contract_deployment =
             is_create.clone() * is_success.expr() * not::expr(copy_rw_increase_is_zero.expr());
-        let (caller_id, address, reversion_info, code_hash) =
-            cb.condition(is_contract_deployment.clone(), |cb| {
-                // We don't need to place any additional constraints on code_hash because the
-                // copy circuit enforces that it is the hash of the bytes in the copy lookup.
-                let code_hash = cb.query_cell_phase2();
-                cb.copy_table_lookup(
-                    cb.curr.state.call_id.expr(),
-                    CopyDataType::Memory.expr(),
-                    code_hash.expr(),
-                    CopyDataType::Bytecode.expr(),
-                    range.offset(),
-                    range.address(),
-                    0.expr(),
-                    range.length(),
-                    0.expr(),
-                    copy_rw_increase.expr(),
-                );
+        let (
+            caller_id,
+            address,
+            reversion_info,
+            code_hash,
+            code_first_byte,
+            is_code_first_byte_invalid,
+        ) = cb.condition(is_contract_deployment.clone(), |cb| {
+            // We don't need to place any additional constraints on code_hash because the
+            // copy circuit enforces that it is the hash of the bytes in the copy lookup.
+            let code_hash = cb.query_cell_phase2();
+            cb.copy_table_lookup(
+                cb.curr.state.call_id.expr(),
+                CopyDataType::Memory.expr(),
+                code_hash.expr(),
+                CopyDataType::Bytecode.expr(),
+                range.offset(),
+                range.address(),
+                0.expr(),
+                range.length(),
+                0.expr(),
+                copy_rw_increase.expr(),
+            );
 
-                let [caller_id, address] = [
-                    CallContextFieldTag::CallerId,
-                    CallContextFieldTag::CalleeAddress,
-                ]
-                .map(|tag| cb.call_context(None, tag));
-                let mut reversion_info = cb.reversion_info_read(None);
-
-                cb.account_write(
-                    address.expr(),
-                    AccountFieldTag::CodeHash,
-                    code_hash.expr(),
-                    cb.empty_hash_rlc(),
-                    Some(&mut reversion_info),
-                );
+            // A code starting with 0xef fails the creation with
+            // ErrorInvalidCreationCode (EIP-3541).
+            let code_first_byte = cb.query_cell();
+            cb.bytecode_lookup(code_hash.expr(), 0.expr(), 1.expr(), code_first_byte.expr());
+            let is_code_first_byte_invalid = IsEqualGadget::construct(
+                cb,
+                code_first_byte.expr(),
+                INVALID_CODE_FIRST_BYTE.expr(),
+            );
+            cb.require_zero(
+                "deployed code doesn't start with 0xef",
+                is_code_first_byte_invalid.expr(),
+            );
 
-                (caller_id, address, reversion_info, code_hash)
-            });
+            let [caller_id, address] = [
+                CallContextFieldTag::CallerId,
+                CallContextFieldTag::CalleeAddress,
+            ]
+            .map(|tag| cb.call_context(None, tag));
+            let mut reversion_info = cb.reversion_info_read(None);
+
+            cb.account_write(
+                address.expr(),
+                AccountFieldTag::CodeHash,
+                code_hash.expr(),
+                cb.empty_hash_rlc(),
+                Some(&mut reversion_info),
+            );
+
+            (
+                caller_id,
+                address,
+                reversion_info,
+                code_hash,
+                code_first_byte,
+                is_code_first_byte_invalid,
+            )
+        });
 
         // Case B in the specs.
         cb.condition(is_root.expr(), |cb| {
@@ -227,7 +271,10 @@ impl<F: Field> ExecutionGadget<F> for ReturnRevertGadget<F> {
             return_data_length,
             restore_context,
             memory_expansion,
+            is_code_size_valid,
             code_hash,
+            code_first_byte,
+            is_code_first_byte_invalid,
             address,
             caller_id,
             reversion_info,
@@ -253,6 +300,12 @@ impl<F: Field> ExecutionGadget<F> for ReturnRevertGadget<F> {
         let range = self.range.assign(region, offset, memory_offset, length)?;
         self.memory_expansion
             .assign(region, offset, step.memory_word_size(), [range])?;
+        self.is_code_size_valid.assign(
+            region,
+            offset,
+            F::from(length.as_u64()),
+            F::from(MAX_CODE_SIZE + 1),
+        )?;
 
         self.is_success
             .assign(region, offset, Value::known(call.is_success.into()))?;
@@ -284,6 +337,19 @@ impl<F: Field> ExecutionGadget<F> for ReturnRevertGadget<F> {
                 offset,
                 region.word_rlc(U256::from_little_endian(&code_hash)),
             )?;
+            if let Some(&first_byte) = values.first() {
+                self.code_first_byte.assign(
+                    region,
+                    offset,
+                    Value::known(F::from(first_byte as u64)),
+                )?;
+                self.is_code_first_byte_invalid.assign(
+                    region,
+                    offset,
+                    F::from(first_byte as u64),
+                    F::from(INVALID_CODE_FIRST_BYTE as u64),
+                )?;
+            }
         }
 
         let copy_rw_increase = if call.is_create && call.is_success {
@@ -340,11 +406,19 @@ impl<F: Field> ExecutionGadget<F> for ReturnRevertGadget<F> {
 
 #[cfg(test)]
 mod test {
-    use crate::test_util::CircuitTestBuilder;
+    use crate::{
+        evm_circuit::step::ExecutionState,
+        table::RwTableTag,
+        test_util::{block_from_test_ctx, CircuitTestBuilder},
+        witness::{Block, Rw},
+    };
     use eth_types::{
-        address, bytecode, evm_types::OpcodeId, geth_types::Account, Address, Bytecode, ToWord,
-        Word,
+        address, bytecode,
+        evm_types::{OpcodeId, INVALID_CODE_FIRST_BYTE, MAX_CODE_SIZE},
+        geth_types::Account,
+        Address, Bytecode, ToWord, Word,
     };
+    use halo2_proofs::halo2curves::bn256::Fr;
     use itertools::Itertools;
     use mock::{eth, TestContext, MOCK_ACCOUNTS};
 
@@ -449,6 +523,67 @@ mod test {
         }
     }
 
+    /// Code returning (or reverting) zero bytes at `offset`.
+    fn zero_length_callee_bytecode(is_return: bool, offset: Word) -> Bytecode {
+        let mut code = bytecode! {
+            PUSH1(0) // length
+            PUSH32(offset)
+        };
+        code.write_op(if is_return {
+            OpcodeId::RETURN
+        } else {
+            OpcodeId::REVERT
+        });
+        code
+    }
+
+    #[test]
+    fn test_return_zero_length_large_offset() {
+        let offsets = [Word::from(u64::MAX), Word::MAX];
+        for (offset, is_return) in offsets.iter().cartesian_product(&[true, false]) {
+            // Root call
+            let code = zero_length_callee_bytecode(*is_return, *offset);
+            CircuitTestBuilder::new_from_test_ctx(
+                TestContext::<2, 1>::simple_ctx_with_bytecode(code).unwrap(),
+            )
+            .run();
+
+            // Internal call, the caller expects return data which it doesn't get.
+            let callee = Account {
+                address: CALLEE_ADDRESS,
+                code: zero_length_callee_bytecode(*is_return, *offset).into(),
+                nonce: Word::one(),
+                ..Default::default()
+            };
+            let caller = Account {
+                address: CALLER_ADDRESS,
+                code: caller_bytecode(0, 10).into(),
+                nonce: Word::one(),
+                ..Default::default()
+            };
+            let ctx = TestContext::<3, 1>::new(
+                None,
+                |accs| {
+                    accs[0]
+                        .address(address!("0x000000000000000000000000000000000000cafe"))
+                        .balance(Word::from(10u64.pow(19)));
+                    accs[1].account(&caller);
+                    accs[2].account(&callee);
+                },
+                |mut txs, accs| {
+                    txs[0]
+                        .from(accs[0].address)
+                        .to(accs[1].address)
+                        .gas(100000u64.into());
+                },
+                |block, _tx| block.number(0xcafeu64),
+            )
+            .unwrap();
+
+            CircuitTestBuilder::new_from_test_ctx(ctx).run();
+        }
+    }
+
     #[test]
     fn test_return_root_create() {
         let test_parameters = [(0, 0), (0, 10), (300, 20), (1000, 0)];
@@ -571,4 +706,147 @@ mod test {
 
         CircuitTestBuilder::new_from_test_ctx(ctx).run();
     }
+
+    /// Returns the execution state of the first RETURN step of `block`.
+    fn return_execution_state(block: &Block<Fr>) -> ExecutionState {
+        block
+            .txs
+            .iter()
+            .flat_map(|tx| tx.steps.iter())
+            .find(|step| step.opcode == Some(OpcodeId::RETURN))
+            .unwrap()
+            .execution_state
+    }
+
+    /// Deploys the code returned by `initializer` from a creation tx and from
+    /// a CREATE, which fails with `expected` at the RETURN.
+    fn test_create_failure(initializer: Bytecode, expected: ExecutionState) {
+        let initializer = initializer.code();
+
+        // Creation tx
+        let tx_input = initializer.clone();
+        let ctx = TestContext::<1, 1>::new(
+            None,
+            |accs| {
+                accs[0].address(MOCK_ACCOUNTS[0]).balance(eth(10));
+            },
+            |mut txs, accs| {
+                txs[0].from(accs[0].address).input(tx_input.into());
+            },
+            |block, _| block,
+        )
+        .unwrap();
+        let block = block_from_test_ctx(ctx);
+        assert_eq!(return_execution_state(&block), expected);
+        CircuitTestBuilder::<1, 1>::new_from_block(block).run();
+
+        // CREATE
+        let root_code = bytecode! {
+            PUSH32(Word::from_big_endian(&initializer))
+            PUSH1(0)
+            MSTORE
+
+            PUSH1(initializer.len())        // size
+            PUSH1(32 - initializer.len())   // offset
+            PUSH1(0)                        // value
+
+            CREATE
+        };
+        let caller = Account {
+            address: CALLER_ADDRESS,
+            code: root_code.into(),
+            nonce: Word::one(),
+            balance: eth(10),
+            ..Default::default()
+        };
+        let ctx = TestContext::<2, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(address!("0x000000000000000000000000000000000000cafe"))
+                    .balance(eth(10));
+                accs[1].account(&caller);
+            },
+            |mut txs, accs| {
+                txs[0]
+                    .from(accs[0].address)
+                    .to(accs[1].address)
+                    .gas(100000u64.into());
+            },
+            |block, _| block,
+        )
+        .unwrap();
+        let block = block_from_test_ctx(ctx);
+        assert_eq!(return_execution_state(&block), expected);
+        CircuitTestBuilder::<2, 1>::new_from_block(block).run();
+    }
+
+    #[test]
+    fn test_return_create_max_code_size_exceeded() {
+        test_create_failure(
+            bytecode! {
+                PUSH2(MAX_CODE_SIZE + 1)
+                PUSH1(0)
+                RETURN
+            },
+            ExecutionState::ErrorMaxCodeSizeExceeded,
+        );
+    }
+
+    #[test]
+    fn test_return_create_invalid_code() {
+        test_create_failure(
+            bytecode! {
+                PUSH1(INVALID_CODE_FIRST_BYTE)
+                PUSH1(0)
+                MSTORE8
+                PUSH1(1)
+                PUSH1(0)
+                RETURN
+            },
+            ExecutionState::ErrorInvalidCreationCode,
+        );
+    }
+
+    #[test]
+    fn test_return_create_invalid_code_as_success() {
+        let tx_input = callee_bytecode(true, 0, 10).code();
+        let ctx = TestContext::<1, 1>::new(
+            None,
+            |accs| {
+                accs[0].address(MOCK_ACCOUNTS[0]).balance(eth(10));
+            },
+            |mut txs, accs| {
+                txs[0].from(accs[0].address).input(tx_input.into());
+            },
+            |block, _| block,
+        )
+        .unwrap();
+
+        CircuitTestBuilder::new_from_test_ctx(ctx)
+            .block_modifier(Box::new(|block| {
+                // Make the successful RETURN deploy a code starting with 0xef,
+                // by patching the first byte it reads from memory, after the
+                // stack pops and the IsSuccess read.
+                let step = block.txs[0]
+                    .steps
+                    .iter()
+                    .find(|step| step.opcode == Some(OpcodeId::RETURN))
+                    .unwrap();
+                assert_eq!(step.execution_state, ExecutionState::RETURN_REVERT);
+                let (tag, index) = step.rw_indices[3];
+                assert_eq!(tag, RwTableTag::Memory);
+                if let Rw::Memory { byte, .. } = &mut block.rws.0.get_mut(&tag).unwrap()[index] {
+                    *byte = INVALID_CODE_FIRST_BYTE;
+                }
+            }))
+            .evm_checks(Box::new(|prover, gate_rows, lookup_rows| {
+                assert!(prover
+                    .verify_at_rows_par(gate_rows.iter().cloned(), lookup_rows.iter().cloned())
+                    .is_err())
+            }))
+            // The patched read no longer matches the memory written before it.
+            .state_checks(Box::new(|_, _, _| {}))
+            .run();
+    }
 }
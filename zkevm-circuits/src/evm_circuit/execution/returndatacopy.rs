@@ -375,13 +375,14 @@ mod test {
         test_ok_internal(0x200, 0x200, 0x200, 0x00, 0x150);
     }
 
-    // TODO: Add negative cases for out-of-bound and out-of-gas
-    // #[test]
-    // #[should_panic]
-    // fn returndatacopy_gadget_out_of_bound() {
-    //     test_ok_internal(0x00, 0x10, 0x20, 0x10, 0x10);
-    // }
+    #[test]
+    fn returndatacopy_gadget_exact_fit() {
+        // offset + size == return data size is still in bound
+        test_ok_internal(0x00, 0x20, 0x40, 0x08, 0x18);
+    }
 
+    // Out-of-bound copies are covered by ErrorReturnDataOutOfBoundGadget.
+    // TODO: Add negative case for out-of-gas
     // #[test]
     // #[should_panic]
     // fn returndatacopy_gadget_out_of_gas() {
@@ -86,8 +86,8 @@ impl<F: Field> ExecutionGadget<F> for ReturnDataSizeGadget<F> {
 
 #[cfg(test)]
 mod test {
-    use crate::evm_circuit::test::rand_bytes;
-    use crate::test_util::CircuitTestBuilder;
+    use crate::evm_circuit::{step::ExecutionState, test::rand_bytes};
+    use crate::test_util::{block_from_test_ctx, CircuitTestBuilder};
     use eth_types::{bytecode, ToWord, Word};
     use mock::test_ctx::TestContext;
 
@@ -166,4 +166,77 @@ mod test {
         )
         .run();
     }
+
+    #[test]
+    fn returndatasize_gadget_reset_on_call() {
+        let addr_return = mock::MOCK_ACCOUNTS[0];
+        let addr_revert = mock::MOCK_ACCOUNTS[1];
+        let addr_stop = mock::MOCK_ACCOUNTS[2];
+
+        let code_return = bytecode! {
+            PUSH1(0x20)
+            PUSH1(0x00)
+            RETURN
+        };
+        let code_revert = bytecode! {
+            PUSH1(0x05)
+            PUSH1(0x00)
+            REVERT
+        };
+        let code_stop = bytecode! {
+            STOP
+        };
+
+        // Every call replaces the return data of the previous one, whether it
+        // succeeds or not.
+        let mut code = bytecode! {};
+        for addr in [addr_return, addr_stop, addr_revert, addr_stop] {
+            code.append(&bytecode! {
+                PUSH1(0x00) // retLength
+                PUSH1(0x00) // retOffset
+                PUSH1(0x00) // argsLength
+                PUSH1(0x00) // argsOffset
+                PUSH1(0x00) // value
+                PUSH32(addr.to_word()) // addr
+                PUSH32(0x1_0000) // gas
+                CALL
+                POP
+                RETURNDATASIZE
+                POP
+            });
+        }
+        code.append(&bytecode! {
+            STOP
+        });
+
+        let ctx = TestContext::<5, 1>::new(
+            None,
+            |accs| {
+                accs[0].address(addr_return).code(code_return);
+                accs[1].address(addr_revert).code(code_revert);
+                accs[2].address(addr_stop).code(code_stop);
+                accs[3].address(mock::MOCK_ACCOUNTS[3]).code(code);
+                accs[4]
+                    .address(mock::MOCK_ACCOUNTS[4])
+                    .balance(Word::from(1u64 << 30));
+            },
+            |mut txs, accs| {
+                txs[0].to(accs[3].address).from(accs[4].address);
+            },
+            |block, _tx| block,
+        )
+        .unwrap();
+
+        let block = block_from_test_ctx(ctx);
+
+        let return_data_sizes: Vec<_> = block.txs[0]
+            .steps
+            .iter()
+            .filter(|step| step.execution_state == ExecutionState::RETURNDATASIZE)
+            .map(|step| block.rws[step.rw_indices[1]].stack_value())
+            .collect();
+        assert_eq!(return_data_sizes, [0x20, 0, 5, 0].map(Word::from).to_vec());
+
+        CircuitTestBuilder::<5, 1>::new_from_block(block).run();
+    }
 }
@@ -5,7 +5,9 @@ use crate::{
         util::{
             common_gadget::SameContextGadget,
             constraint_builder::{ConstraintBuilder, StepStateTransition, Transition::Delta},
-            math_gadget::{AbsWordGadget, IsZeroGadget, LtGadget, LtWordGadget, MulAddWordsGadget},
+            math_gadget::{
+                AbsWordGadget, IsZeroGadget, LtWordGadget, MulAddWordsGadget, SignedDivSignGadget,
+            },
             select, sum, CachedRegion,
         },
         witness::{Block, Call, ExecStep, Transaction},
@@ -25,10 +27,8 @@ pub(crate) struct SignedDivModGadget<F> {
     dividend_abs_word: AbsWordGadget<F>,
     mul_add_words: MulAddWordsGadget<F>,
     remainder_abs_lt_divisor_abs: LtWordGadget<F>,
-    dividend_is_signed_overflow: LtGadget<F, 1>,
-    quotient_is_zero: IsZeroGadget<F>,
     divisor_is_zero: IsZeroGadget<F>,
-    remainder_is_zero: IsZeroGadget<F>,
+    signed_div_sign: SignedDivSignGadget<F>,
 }
 
 impl<F: Field> ExecutionGadget<F> for SignedDivModGadget<F> {
@@ -44,10 +44,7 @@ impl<F: Field> ExecutionGadget<F> for SignedDivModGadget<F> {
         let divisor_abs_word = AbsWordGadget::construct(cb);
         let remainder_abs_word = AbsWordGadget::construct(cb);
         let dividend_abs_word = AbsWordGadget::construct(cb);
-        let quotient_is_zero = IsZeroGadget::construct(cb, sum::expr(&quotient_abs_word.x().cells));
         let divisor_is_zero = IsZeroGadget::construct(cb, sum::expr(&divisor_abs_word.x().cells));
-        let remainder_is_zero =
-            IsZeroGadget::construct(cb, sum::expr(&remainder_abs_word.x().cells));
 
         cb.stack_pop(dividend_abs_word.x().expr());
         cb.stack_pop(divisor_abs_word.x().expr());
@@ -76,41 +73,15 @@ impl<F: Field> ExecutionGadget<F> for SignedDivModGadget<F> {
             (1.expr() - remainder_abs_lt_divisor_abs.expr()) * (1.expr() - divisor_is_zero.expr()),
         );
 
-        cb.condition(
-            (1.expr() - quotient_is_zero.expr())
-            * (1.expr() - divisor_is_zero.expr())
-            * (1.expr() - remainder_is_zero.expr()),
-            |cb| cb.add_constraint(
-                "sign(dividend) == sign(remainder) when quotient, divisor and remainder are all non-zero",
-                dividend_abs_word.is_neg().expr() - remainder_abs_word.is_neg().expr(),
-            )
-        );
-
-        // For a special `SDIV` case, when input `dividend = -(1 << 255)` and
-        // `divisor = -1`, the quotient result should be `1 << 255`. But a
-        // `signed` word could only express `signed` value from `-(1 << 255)` to
-        // `(1 << 255) - 1`. So constraint
-        // `sign(dividend) == sign(divisor) ^ sign(quotient)` cannot be applied
-        // for this case.
-        let dividend_is_signed_overflow =
-            LtGadget::construct(cb, 127.expr(), dividend_abs_word.x_abs().cells[31].expr());
-
-        // Constrain sign(dividend) == sign(divisor) ^ sign(quotient) when both
-        // quotient and divisor are non-zero and dividend is not signed overflow.
-        cb.condition(
-            (1.expr() - quotient_is_zero.expr())
-                * (1.expr() - divisor_is_zero.expr())
-                * (1.expr() - dividend_is_signed_overflow.expr()),
-            |cb| {
-                cb.add_constraint(
-                    "sign(dividend) == sign(divisor) ^ sign(quotient)",
-                    quotient_abs_word.is_neg().expr() + divisor_abs_word.is_neg().expr()
-                        - dividend_abs_word.is_neg().expr()
-                        - 2.expr()
-                            * quotient_abs_word.is_neg().expr()
-                            * divisor_abs_word.is_neg().expr(),
-                )
-            },
+        // Constrain the signs of quotient and remainder, which are the results
+        // of `SDIV` and `SMOD` respectively.
+        let signed_div_sign = SignedDivSignGadget::construct(
+            cb,
+            &quotient_abs_word,
+            &divisor_abs_word,
+            &remainder_abs_word,
+            &dividend_abs_word,
+            divisor_is_zero.expr(),
         );
 
         let step_state_transition = StepStateTransition {
@@ -130,10 +101,8 @@ impl<F: Field> ExecutionGadget<F> for SignedDivModGadget<F> {
             dividend_abs_word,
             mul_add_words,
             remainder_abs_lt_divisor_abs,
-            dividend_is_signed_overflow,
-            quotient_is_zero,
             divisor_is_zero,
-            remainder_is_zero,
+            signed_div_sign,
         }
     }
 
@@ -198,21 +167,11 @@ impl<F: Field> ExecutionGadget<F> for SignedDivModGadget<F> {
         )?;
         self.remainder_abs_lt_divisor_abs
             .assign(region, offset, remainder_abs, divisor_abs)?;
-        self.dividend_is_signed_overflow.assign(
-            region,
-            offset,
-            127.into(),
-            u64::from(dividend_abs.to_le_bytes()[31]).into(),
-        )?;
-        let quotient_sum = (0..32).fold(0, |acc, idx| acc + quotient.byte(idx) as u64);
         let divisor_sum = (0..32).fold(0, |acc, idx| acc + divisor.byte(idx) as u64);
-        let remainder_sum = (0..32).fold(0, |acc, idx| acc + remainder.byte(idx) as u64);
-        self.quotient_is_zero
-            .assign(region, offset, F::from(quotient_sum))?;
         self.divisor_is_zero
             .assign(region, offset, F::from(divisor_sum))?;
-        self.remainder_is_zero
-            .assign(region, offset, F::from(remainder_sum))?;
+        self.signed_div_sign
+            .assign(region, offset, quotient, remainder, dividend_abs)?;
         Ok(())
     }
 }
@@ -159,11 +159,15 @@ impl<F: Field> ExecutionGadget<F> for Sha3Gadget<F> {
 
 #[cfg(test)]
 mod tests {
-    use crate::test_util::CircuitTestBuilder;
+    use crate::{
+        evm_circuit::step::ExecutionState,
+        test_util::{block_from_test_ctx, CircuitTestBuilder},
+    };
     use bus_mapping::{
         circuit_input_builder::CircuitsParams,
         evm::{gen_sha3_code, MemoryKind},
     };
+    use eth_types::{bytecode, evm_types::GasCost};
     use mock::TestContext;
 
     fn test_ok(offset: usize, size: usize, mem_kind: MemoryKind) {
@@ -198,4 +202,36 @@ mod tests {
         test_ok(0x303, 0x404, MemoryKind::EqualToSize);
         test_ok(0x404, 0x505, MemoryKind::MoreThanSize);
     }
+
+    #[test]
+    fn sha3_gadget_gas() {
+        for size in [0u64, 32, 64] {
+            // Expand the memory beforehand so that SHA3 only pays for hashing
+            let code = bytecode! {
+                PUSH1(0)
+                PUSH1(0x40)
+                MSTORE
+                PUSH1(size)
+                PUSH1(0)
+                SHA3
+                STOP
+            };
+            let block =
+                block_from_test_ctx(TestContext::<2, 1>::simple_ctx_with_bytecode(code).unwrap());
+
+            let steps = &block.txs[0].steps;
+            let idx = steps
+                .iter()
+                .position(|step| step.execution_state == ExecutionState::SHA3)
+                .unwrap();
+            assert_eq!(
+                steps[idx].gas_left - steps[idx + 1].gas_left,
+                GasCost::SHA3.as_u64() + GasCost::COPY_SHA3.as_u64() * (size / 32),
+                "gas of hashing {} bytes",
+                size
+            );
+
+            CircuitTestBuilder::<2, 1>::new_from_block(block).run();
+        }
+    }
 }
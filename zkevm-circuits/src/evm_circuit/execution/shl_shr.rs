@@ -8,7 +8,7 @@ use crate::{
             common_gadget::SameContextGadget,
             constraint_builder::{ConstraintBuilder, StepStateTransition, Transition::Delta},
             from_bytes,
-            math_gadget::{IsZeroGadget, LtWordGadget, MulAddWordsGadget},
+            math_gadget::{IsZeroGadget, MulAddWordsGadget, ShrWordGadget},
             sum, CachedRegion, Cell,
         },
         witness::{Block, Call, ExecStep, Transaction},
@@ -22,7 +22,8 @@ use halo2_proofs::{circuit::Value, plonk::Error};
 /// ShlShrGadget verifies opcode SHL and SHR.
 /// For SHL, verify pop1 * (2^pop2) % 2^256 == push;
 /// For SHR, verify pop1 / (2^pop2) % 2^256 == push;
-/// when pop1, pop2, push are 256-bit words.
+/// when pop1, pop2, push are 256-bit words. SHR shares the right shift
+/// decomposition of SAR, filling with zero instead of the sign bit.
 #[derive(Clone, Debug)]
 pub(crate) struct ShlShrGadget<F> {
     same_context: SameContextGadget<F>,
@@ -34,14 +35,15 @@ pub(crate) struct ShlShrGadget<F> {
     shift: util::Word<F>,
     /// First byte of shift word
     shf0: Cell<F>,
-    /// Gadget that verifies quotient * divisor + remainder = dividend
+    /// Gadget that verifies quotient * divisor + remainder = dividend for
+    /// opcode SHL
     mul_add_words: MulAddWordsGadget<F>,
     /// Check if divisor is zero
     divisor_is_zero: IsZeroGadget<F>,
     /// Check if remainder is zero
     remainder_is_zero: IsZeroGadget<F>,
-    /// Check if remainder < divisor when divisor != 0
-    remainder_lt_divisor: LtWordGadget<F>,
+    /// Gadget that verifies quotient = dividend >> shift for opcode SHR
+    shr_word: ShrWordGadget<F>,
 }
 
 impl<F: Field> ExecutionGadget<F> for ShlShrGadget<F> {
@@ -61,11 +63,16 @@ impl<F: Field> ExecutionGadget<F> for ShlShrGadget<F> {
         let shift = cb.query_word_rlc();
         let shf0 = cb.query_cell();
 
-        let mul_add_words =
-            MulAddWordsGadget::construct(cb, [&quotient, &divisor, &remainder, &dividend]);
+        let (mul_add_words, remainder_is_zero) = cb.condition(is_shl.expr(), |cb| {
+            (
+                MulAddWordsGadget::construct(cb, [&quotient, &divisor, &remainder, &dividend]),
+                IsZeroGadget::construct(cb, sum::expr(&remainder.cells)),
+            )
+        });
         let divisor_is_zero = IsZeroGadget::construct(cb, sum::expr(&divisor.cells));
-        let remainder_is_zero = IsZeroGadget::construct(cb, sum::expr(&remainder.cells));
-        let remainder_lt_divisor = LtWordGadget::construct(cb, &remainder, &divisor);
+        let shr_word = cb.condition(is_shr.expr(), |cb| {
+            ShrWordGadget::construct(cb, &dividend, &shift, &quotient, 0.expr())
+        });
 
         // Constrain stack pops and pushes as:
         // - for SHL, two pops are shift and quotient, and push is dividend.
@@ -73,8 +80,8 @@ impl<F: Field> ExecutionGadget<F> for ShlShrGadget<F> {
         cb.stack_pop(shift.expr());
         cb.stack_pop(is_shl.expr() * quotient.expr() + is_shr.expr() * dividend.expr());
         cb.stack_push(
-            (is_shl.expr() * dividend.expr() + is_shr.expr() * quotient.expr())
-                * (1.expr() - divisor_is_zero.expr()),
+            is_shl.expr() * dividend.expr() * (1.expr() - divisor_is_zero.expr())
+                + is_shr.expr() * quotient.expr(),
         );
 
         cb.require_zero(
@@ -87,21 +94,11 @@ impl<F: Field> ExecutionGadget<F> for ShlShrGadget<F> {
             (1.expr() - divisor_is_zero.expr()) * (shift.expr() - shift.cells[0].expr()),
         );
 
-        cb.require_zero(
-            "remainder < divisor when divisor != 0",
-            (1.expr() - divisor_is_zero.expr()) * (1.expr() - remainder_lt_divisor.expr()),
-        );
-
         cb.require_zero(
             "remainder == 0 for opcode SHL",
             is_shl * (1.expr() - remainder_is_zero.expr()),
         );
 
-        cb.require_zero(
-            "overflow == 0 for opcode SHR",
-            is_shr * mul_add_words.overflow(),
-        );
-
         // Constrain divisor_lo == 2^shf0 when shf0 < 128, and
         // divisor_hi == 2^(128 - shf0) otherwise.
         let divisor_lo = from_bytes::expr(&divisor.cells[..16]);
@@ -137,7 +134,7 @@ impl<F: Field> ExecutionGadget<F> for ShlShrGadget<F> {
             mul_add_words,
             divisor_is_zero,
             remainder_is_zero,
-            remainder_lt_divisor,
+            shr_word,
         }
     }
 
@@ -185,8 +182,7 @@ impl<F: Field> ExecutionGadget<F> for ShlShrGadget<F> {
         let remainder_sum = (0..32).fold(0, |acc, idx| acc + remainder.byte(idx) as u64);
         self.remainder_is_zero
             .assign(region, offset, F::from(remainder_sum))?;
-        self.remainder_lt_divisor
-            .assign(region, offset, remainder, divisor)
+        self.shr_word.assign(region, offset, dividend, pop1, false)
     }
 }
 
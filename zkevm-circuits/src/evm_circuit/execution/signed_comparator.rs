@@ -3,10 +3,10 @@ use crate::{
         execution::ExecutionGadget,
         step::ExecutionState,
         util::{
-            common_gadget::SameContextGadget,
+            common_gadget::{OpcodeSelectGadget, SameContextGadget},
             constraint_builder::{ConstraintBuilder, StepStateTransition, Transition::Delta},
-            from_bytes,
-            math_gadget::{ComparisonGadget, IsEqualGadget, LtGadget},
+            debug_assert_witness, from_bytes,
+            math_gadget::{ComparisonGadget, LtGadget},
             select, CachedRegion, Cell, Word,
         },
         witness::{Block, Call, ExecStep, Transaction},
@@ -31,7 +31,7 @@ pub(crate) struct SignedComparatorGadget<F> {
     comparison_hi: ComparisonGadget<F, 16>,
     a_lt_b: Cell<F>,
 
-    is_sgt: IsEqualGadget<F>,
+    is_sgt: OpcodeSelectGadget<F, 1>,
 }
 
 impl<F: Field> ExecutionGadget<F> for SignedComparatorGadget<F> {
@@ -48,7 +48,7 @@ impl<F: Field> ExecutionGadget<F> for SignedComparatorGadget<F> {
         // The Signed Comparator gadget is used for both opcodes SLT and SGT.
         // Depending on whether the opcode is SLT or SGT, we
         // swap the order in which the inputs are placed on the stack.
-        let is_sgt = IsEqualGadget::construct(cb, opcode.expr(), OpcodeId::SGT.expr());
+        let is_sgt = OpcodeSelectGadget::construct(cb, opcode.expr(), [OpcodeId::SGT]);
 
         // Both a and b are to be treated as two's complement signed 256-bit
         // (32 cells) integers. This means, the first bit denotes the sign
@@ -112,8 +112,8 @@ impl<F: Field> ExecutionGadget<F> for SignedComparatorGadget<F> {
         let result = a_neg_b_pos.clone() + (1.expr() - a_neg_b_pos - b_neg_a_pos) * a_lt_b.expr();
 
         // Pop a and b from the stack, push the result on the stack.
-        cb.stack_pop(select::expr(is_sgt.expr(), b.expr(), a.expr()));
-        cb.stack_pop(select::expr(is_sgt.expr(), a.expr(), b.expr()));
+        cb.stack_pop(is_sgt.select([b.expr()], a.expr()));
+        cb.stack_pop(is_sgt.select([a.expr()], b.expr()));
         cb.stack_push(result);
 
         // The read-write counter changes by three since we're reading two words
@@ -156,12 +156,7 @@ impl<F: Field> ExecutionGadget<F> for SignedComparatorGadget<F> {
         let opcode = step.opcode.unwrap();
 
         // SLT opcode is the default check in the SCMP gadget. Swap rw for SGT.
-        self.is_sgt.assign(
-            region,
-            offset,
-            F::from(opcode.as_u8() as u64),
-            F::from(OpcodeId::SGT.as_u8() as u64),
-        )?;
+        self.is_sgt.assign(region, offset, opcode)?;
         let indices = if opcode == OpcodeId::SGT {
             [step.rw_indices[1], step.rw_indices[0]]
         } else {
@@ -212,6 +207,15 @@ impl<F: Field> ExecutionGadget<F> for SignedComparatorGadget<F> {
         self.a.assign(region, offset, Some(a_le_bytes))?;
         self.b.assign(region, offset, Some(b_le_bytes))?;
 
+        let (a_neg, b_neg) = (a.bit(255), b.bit(255));
+        let expected = if a_neg != b_neg { a_neg } else { a < b };
+        debug_assert_witness(
+            Self::NAME,
+            "result",
+            block.rws[step.rw_indices[2]].stack_value(),
+            eth_types::Word::from(expected as u64),
+        );
+
         Ok(())
     }
 }
@@ -223,7 +227,11 @@ mod test {
     use eth_types::Word;
     use mock::TestContext;
 
-    use crate::{evm_circuit::test::rand_word, test_util::CircuitTestBuilder};
+    use crate::{
+        evm_circuit::{step::ExecutionState, test::rand_word, witness::Rw},
+        table::RwTableTag,
+        test_util::CircuitTestBuilder,
+    };
 
     fn test_ok(pairs: Vec<(OpcodeId, Word, Word)>) {
         let mut bytecode = bytecode! {};
@@ -240,6 +248,36 @@ mod test {
         .run();
     }
 
+    #[test]
+    fn signed_comparator_row_budget() {
+        let bytecode = bytecode! {
+            PUSH32(Word::from(1))
+            PUSH32(Word::MAX)
+            SLT
+            STOP
+        };
+
+        // The first row and EndBlock, the steps around SLT and at most 8 rows
+        // for the SCMP step.
+        let max_rows = 2
+            + [
+                ExecutionState::BeginTx,
+                ExecutionState::PUSH,
+                ExecutionState::PUSH,
+                ExecutionState::STOP,
+                ExecutionState::EndTx,
+            ]
+            .iter()
+            .map(ExecutionState::get_step_height)
+            .sum::<usize>()
+            + 8;
+        CircuitTestBuilder::new_from_test_ctx(
+            TestContext::<2, 1>::simple_ctx_with_bytecode(bytecode).unwrap(),
+        )
+        .max_evm_rows(max_rows)
+        .run();
+    }
+
     #[test]
     fn signed_comparator_gadget_a_b_neg() {
         let minus_1 = Word::from_big_endian(&[255u8; 32]);
@@ -313,4 +351,33 @@ mod test {
             (OpcodeId::SGT, b, a),
         ]);
     }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(expected = "assigned result doesn't match the expected value")]
+    fn signed_comparator_gadget_wrong_result_witness() {
+        let mut bytecode = bytecode! {};
+        bytecode.push(32, Word::from(2));
+        bytecode.push(32, Word::from(1));
+        bytecode.write_op(OpcodeId::SLT);
+        bytecode.write_op(OpcodeId::STOP);
+
+        CircuitTestBuilder::new_from_test_ctx(
+            TestContext::<2, 1>::simple_ctx_with_bytecode(bytecode).unwrap(),
+        )
+        .block_modifier(Box::new(|block| {
+            // Flip the result pushed by SLT, which is the last stack write.
+            let stack_rws = block.rws.0.get_mut(&RwTableTag::Stack).unwrap();
+            if let Some(Rw::Stack { value, .. }) =
+                stack_rws.iter_mut().rev().find(|rw| rw.is_write())
+            {
+                *value = if value.is_zero() {
+                    Word::one()
+                } else {
+                    Word::zero()
+                };
+            }
+        }))
+        .run();
+    }
 }
@@ -60,13 +60,10 @@ impl<F: Field> ExecutionGadget<F> for SloadGadget<F> {
 
         cb.stack_push(phase2_value.expr());
 
-        let is_warm = cb.query_bool();
-        cb.account_storage_access_list_write(
+        let is_warm = cb.add_to_access_list(
             tx_id.expr(),
             callee_address.expr(),
-            phase2_key.expr(),
-            true.expr(),
-            is_warm.expr(),
+            Some(phase2_key.expr()),
             Some(&mut reversion_info),
         );
 
@@ -166,7 +163,10 @@ impl<F: Field> SloadGasGadget<F> {
 #[cfg(test)]
 mod test {
 
-    use crate::{evm_circuit::test::rand_word, test_util::CircuitTestBuilder};
+    use crate::{
+        evm_circuit::{step::ExecutionState, test::rand_word},
+        test_util::{block_from_test_ctx, CircuitTestBuilder},
+    };
     use eth_types::{bytecode, Word};
     use mock::{test_ctx::helpers::tx_from_1_to_0, TestContext, MOCK_ACCOUNTS};
 
@@ -225,4 +225,43 @@ mod test {
         let value = rand_word();
         test_ok(key, value);
     }
+
+    #[test]
+    fn sload_gadget_second_access_warm() {
+        let key = Word::from(0x030201);
+        let ctx = TestContext::<2, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(MOCK_ACCOUNTS[0])
+                    .balance(Word::from(10u64.pow(19)))
+                    .code(bytecode! {
+                        PUSH32(key)
+                        SLOAD
+                        PUSH32(key)
+                        SLOAD
+                        STOP
+                    })
+                    .storage(vec![(key, Word::from(0x060504))].into_iter());
+                accs[1]
+                    .address(MOCK_ACCOUNTS[1])
+                    .balance(Word::from(10u64.pow(19)));
+            },
+            tx_from_1_to_0,
+            |block, _txs| block,
+        )
+        .unwrap();
+
+        let block = block_from_test_ctx(ctx);
+
+        let is_warm: Vec<_> = block.txs[0]
+            .steps
+            .iter()
+            .filter(|step| step.execution_state == ExecutionState::SLOAD)
+            .map(|step| block.rws[step.rw_indices[7]].tx_access_list_value_pair().1)
+            .collect();
+        assert_eq!(is_warm, vec![false, true]);
+
+        CircuitTestBuilder::<2, 1>::new_from_block(block).run();
+    }
 }
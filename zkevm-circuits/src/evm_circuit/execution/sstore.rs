@@ -26,7 +26,6 @@ use halo2_proofs::{
 pub(crate) struct SstoreGadget<F> {
     same_context: SameContextGadget<F>,
     tx_id: Cell<F>,
-    is_static: Cell<F>,
     reversion_info: ReversionInfo<F>,
     callee_address: Cell<F>,
     phase2_key: Cell<F>,
@@ -50,8 +49,7 @@ impl<F: Field> ExecutionGadget<F> for SstoreGadget<F> {
         let tx_id = cb.call_context(None, CallContextFieldTag::TxId);
 
         // constrain not in static call
-        let is_static = cb.call_context(None, CallContextFieldTag::IsStatic);
-        cb.require_zero("is_static is false", is_static.expr());
+        cb.require_not_static();
 
         let mut reversion_info = cb.reversion_info_read(None);
         let callee_address = cb.call_context(None, CallContextFieldTag::CalleeAddress);
@@ -76,13 +74,10 @@ impl<F: Field> ExecutionGadget<F> for SstoreGadget<F> {
             Some(&mut reversion_info),
         );
 
-        let is_warm = cb.query_bool();
-        cb.account_storage_access_list_write(
+        let is_warm = cb.add_to_access_list(
             tx_id.expr(),
             callee_address.expr(),
-            phase2_key.expr(),
-            true.expr(),
-            is_warm.expr(),
+            Some(phase2_key.expr()),
             Some(&mut reversion_info),
         );
 
@@ -122,7 +117,6 @@ impl<F: Field> ExecutionGadget<F> for SstoreGadget<F> {
         Self {
             same_context,
             tx_id,
-            is_static,
             reversion_info,
             callee_address,
             phase2_key,
@@ -149,8 +143,6 @@ impl<F: Field> ExecutionGadget<F> for SstoreGadget<F> {
 
         self.tx_id
             .assign(region, offset, Value::known(F::from(tx.id as u64)))?;
-        self.is_static
-            .assign(region, offset, Value::known(F::from(call.is_static as u64)))?;
         self.reversion_info.assign(
             region,
             offset,
@@ -530,8 +522,12 @@ fn calc_expected_tx_refund(
 #[cfg(test)]
 mod test {
 
-    use crate::test_util::CircuitTestBuilder;
-    use eth_types::{bytecode, Word};
+    use crate::{
+        evm_circuit::step::ExecutionState,
+        test_util::{block_from_test_ctx, block_from_test_ctx_with_params, CircuitTestBuilder},
+    };
+    use bus_mapping::circuit_input_builder::CircuitsParams;
+    use eth_types::{bytecode, evm_types::GasCost, Word};
     use mock::{test_ctx::helpers::tx_from_1_to_0, TestContext, MOCK_ACCOUNTS};
 
     #[test]
@@ -595,6 +591,107 @@ mod test {
         );
     }
 
+    #[test]
+    fn sstore_gadget_clear_slot_refund() {
+        let key = Word::from(0x030201);
+        let ctx = TestContext::<2, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(MOCK_ACCOUNTS[0])
+                    .balance(Word::from(10u64.pow(19)))
+                    .code(bytecode! {
+                        PUSH1(0)
+                        PUSH32(key)
+                        SSTORE
+                        STOP
+                    })
+                    .storage(vec![(key, Word::from(0x060504))].into_iter());
+                accs[1]
+                    .address(MOCK_ACCOUNTS[1])
+                    .balance(Word::from(10u64.pow(19)));
+            },
+            tx_from_1_to_0,
+            |block, _txs| block,
+        )
+        .unwrap();
+
+        let block = block_from_test_ctx(ctx);
+
+        // Clearing a slot whose original value is non-zero refunds
+        // SSTORE_CLEARS_SCHEDULE (EIP-3529), visible from the step after it.
+        let steps = &block.txs[0].steps;
+        let sstore_idx = steps
+            .iter()
+            .position(|step| step.execution_state == ExecutionState::SSTORE)
+            .unwrap();
+        assert_eq!(steps[sstore_idx].gas_refund, 0);
+        assert_eq!(
+            steps[sstore_idx + 1].gas_refund,
+            GasCost::SSTORE_CLEARS_SCHEDULE.as_u64()
+        );
+    }
+
+    #[test]
+    fn sstore_gadget_original_value_per_tx() {
+        // Both txs set the slot to 2 and then to 1. For the first tx, the
+        // original value is 0x060504 so nothing is refunded. For the second,
+        // which starts from the value left by the first, the slot is reset to
+        // its original value.
+        let key = Word::from(0x030201);
+        let ctx = TestContext::<2, 2>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(MOCK_ACCOUNTS[0])
+                    .balance(Word::from(10u64.pow(19)))
+                    .code(bytecode! {
+                        PUSH1(2)
+                        PUSH32(key)
+                        SSTORE
+                        PUSH1(1)
+                        PUSH32(key)
+                        SSTORE
+                        STOP
+                    })
+                    .storage(vec![(key, Word::from(0x060504))].into_iter());
+                accs[1]
+                    .address(MOCK_ACCOUNTS[1])
+                    .balance(Word::from(10u64.pow(19)));
+            },
+            |mut txs, accs| {
+                txs[0].from(accs[1].address).to(accs[0].address);
+                txs[1].from(accs[1].address).to(accs[0].address);
+            },
+            |block, _txs| block,
+        )
+        .unwrap();
+
+        let block = block_from_test_ctx_with_params(
+            ctx,
+            CircuitsParams {
+                max_txs: 2,
+                ..Default::default()
+            },
+        );
+
+        let refund_at_stop = |tx_idx: usize| {
+            block.txs[tx_idx]
+                .steps
+                .iter()
+                .find(|step| step.execution_state == ExecutionState::STOP)
+                .unwrap()
+                .gas_refund
+        };
+        assert_eq!(refund_at_stop(0), 0);
+        assert_eq!(
+            refund_at_stop(1),
+            GasCost::SSTORE_RESET.as_u64() - GasCost::WARM_ACCESS.as_u64()
+        );
+
+        CircuitTestBuilder::<2, 2>::new_from_block(block).run();
+    }
+
     fn test_ok(key: Word, value: Word, value_prev: Word, original_value: Word) {
         // Here we use two bytecodes to test both is_persistent(STOP) or not(REVERT)
         // Besides, in bytecode we use two SSTOREs,
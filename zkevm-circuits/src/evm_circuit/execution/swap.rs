@@ -3,9 +3,10 @@ use crate::{
         execution::ExecutionGadget,
         step::ExecutionState,
         util::{
-            common_gadget::SameContextGadget,
+            common_gadget::{SameContextGadget, StackDepthGadget},
             constraint_builder::{ConstraintBuilder, StepStateTransition, Transition::Delta},
-            CachedRegion, Cell,
+            word_rlc::WordRlc,
+            CachedRegion,
         },
         witness::{Block, Call, ExecStep, Transaction},
     },
@@ -17,7 +18,8 @@ use halo2_proofs::plonk::Error;
 #[derive(Clone, Debug)]
 pub(crate) struct SwapGadget<F> {
     same_context: SameContextGadget<F>,
-    phase2_values: [Cell<F>; 2],
+    stack_depth: StackDepthGadget<F>,
+    values: [WordRlc<F>; 2],
 }
 
 impl<F: Field> ExecutionGadget<F> for SwapGadget<F> {
@@ -28,20 +30,22 @@ impl<F: Field> ExecutionGadget<F> for SwapGadget<F> {
     fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
         let opcode = cb.query_cell();
 
-        let phase2_values = [cb.query_cell_phase2(), cb.query_cell_phase2()];
+        let values = [WordRlc::construct(cb), WordRlc::construct(cb)];
 
         // The stack index we have to peek, deduced from the 'x' value of
         // 'swapx' The offset starts at 1 for SWAP1
         let swap_offset = opcode.expr() - (OpcodeId::SWAP1.as_u64() - 1).expr();
+        // SWAPx needs x + 1 items on the stack
+        let stack_depth = StackDepthGadget::construct(cb, swap_offset.clone() + 1.expr());
 
         // Peek the value at `swap_offset`
-        cb.stack_lookup(false.expr(), swap_offset.clone(), phase2_values[0].expr());
+        cb.stack_lookup(false.expr(), swap_offset.clone(), values[0].expr());
         // Peek the value at the top of the stack
-        cb.stack_lookup(false.expr(), 0.expr(), phase2_values[1].expr());
+        cb.stack_lookup(false.expr(), 0.expr(), values[1].expr());
         // Write the value previously at the top of the stack to `swap_offset`
-        cb.stack_lookup(true.expr(), swap_offset, phase2_values[1].expr());
+        cb.stack_lookup(true.expr(), swap_offset, values[1].expr());
         // Write the value previously at `swap_offset` to the top of the stack
-        cb.stack_lookup(true.expr(), 0.expr(), phase2_values[0].expr());
+        cb.stack_lookup(true.expr(), 0.expr(), values[0].expr());
 
         // State transition
         let step_state_transition = StepStateTransition {
@@ -54,7 +58,8 @@ impl<F: Field> ExecutionGadget<F> for SwapGadget<F> {
 
         Self {
             same_context,
-            phase2_values,
+            stack_depth,
+            values,
         }
     }
 
@@ -68,13 +73,19 @@ impl<F: Field> ExecutionGadget<F> for SwapGadget<F> {
         step: &ExecStep,
     ) -> Result<(), Error> {
         self.same_context.assign_exec_step(region, offset, step)?;
-
-        for (cell, value) in self.phase2_values.iter().zip(
+        self.stack_depth.assign(
+            region,
+            offset,
+            step,
+            step.opcode.unwrap().dup_swap_depth().unwrap() as u64,
+        )?;
+
+        for (word_rlc, value) in self.values.iter().zip(
             [step.rw_indices[0], step.rw_indices[1]]
                 .map(|idx| block.rws[idx].stack_value())
                 .iter(),
         ) {
-            cell.assign(region, offset, region.word_rlc(*value))?;
+            word_rlc.assign(region, offset, *value)?;
         }
 
         Ok(())
@@ -83,7 +94,10 @@ impl<F: Field> ExecutionGadget<F> for SwapGadget<F> {
 
 #[cfg(test)]
 mod test {
-    use crate::{evm_circuit::test::rand_word, test_util::CircuitTestBuilder};
+    use crate::{
+        evm_circuit::{step::ExecutionState, test::rand_word},
+        test_util::{test_stack_depth, CircuitTestBuilder},
+    };
     use eth_types::evm_types::OpcodeId;
     use eth_types::{bytecode, Word};
     use mock::TestContext;
@@ -117,6 +131,16 @@ mod test {
         test_ok(OpcodeId::SWAP16, Word::from(0x030201), Word::from(0x040506));
     }
 
+    #[test]
+    fn swap16_stack_underflow() {
+        test_stack_depth(OpcodeId::SWAP16, 16, ExecutionState::ErrorStack);
+    }
+
+    #[test]
+    fn swap16_stack_depth_ok() {
+        test_stack_depth(OpcodeId::SWAP16, 17, ExecutionState::SWAP);
+    }
+
     #[test]
     #[ignore]
     fn swap_gadget_rand() {
@@ -55,6 +55,9 @@ pub(crate) const N_BYTES_MEMORY_WORD_SIZE: usize = 4;
 
 pub(crate) const STACK_CAPACITY: usize = 1024;
 
+// Number of bytes that will be used for the stack pointer.
+pub(crate) const N_BYTES_STACK: usize = 2;
+
 // Number of bytes that will be used of prorgam counter. Although the maximum
 // size of execution bytecode could be at most 128kB due to the size limit of a
 // transaction, which could be covered by 3 bytes, we still support program
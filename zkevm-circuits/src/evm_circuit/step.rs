@@ -24,6 +24,7 @@ pub enum ExecutionState {
     // Internal state
     BeginTx,
     EndTx,
+    InvalidTx,
     EndBlock,
     // Opcode successful cases
     STOP,
@@ -91,6 +92,7 @@ pub enum ExecutionState {
     ErrorDepth,
     ErrorInsufficientBalance,
     ErrorContractAddressCollision,
+    ErrorNonceUintOverflow,
     ErrorInvalidCreationCode,
     ErrorMaxCodeSizeExceeded,
     ErrorInvalidJump,
@@ -139,6 +141,7 @@ impl ExecutionState {
                 | Self::ErrorDepth
                 | Self::ErrorInsufficientBalance
                 | Self::ErrorContractAddressCollision
+                | Self::ErrorNonceUintOverflow
                 | Self::ErrorInvalidCreationCode
                 | Self::ErrorMaxCodeSizeExceeded
                 | Self::ErrorInvalidJump
@@ -5,10 +5,11 @@ use bus_mapping::evm::OpcodeId;
 use eth_types::Field;
 use gadgets::util::Expr;
 use halo2_proofs::plonk::Expression;
+use rayon::prelude::*;
 use strum::IntoEnumIterator;
 use strum_macros::EnumIter;
 
-#[derive(Clone, Copy, Debug, EnumIter)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, EnumIter)]
 pub enum FixedTableTag {
     Zero = 0,
     Range5,
@@ -29,7 +30,24 @@ pub enum FixedTableTag {
 }
 impl_expr!(FixedTableTag);
 
+/// Builds the rows of `fixed_table_tags`, in order. The rows of the different
+/// tags are generated in parallel, since only their assignment to the region
+/// has to be sequential.
+pub fn build_fixed_table_rows<F: Field>(fixed_table_tags: &[FixedTableTag]) -> Vec<[F; 4]> {
+    fixed_table_tags
+        .par_iter()
+        .map(|tag| tag.build().collect::<Vec<_>>())
+        .collect::<Vec<_>>()
+        .concat()
+}
+
 impl FixedTableTag {
+    /// Returns true for the tags of the bitwise rows, which are by far the
+    /// largest part of the fixed table.
+    pub fn is_bitwise(&self) -> bool {
+        matches!(self, Self::BitwiseAnd | Self::BitwiseOr | Self::BitwiseXor)
+    }
+
     pub fn build<F: Field>(&self) -> Box<dyn Iterator<Item = [F; 4]>> {
         let tag = F::from(*self as u64);
         match self {
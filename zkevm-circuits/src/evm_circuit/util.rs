@@ -24,6 +24,8 @@ pub(crate) mod common_gadget;
 pub(crate) mod constraint_builder;
 pub(crate) mod math_gadget;
 pub(crate) mod memory_gadget;
+pub(crate) mod precompile_gadget;
+pub(crate) mod word_rlc;
 
 pub use gadgets::util::{and, not, or, select, sum};
 
@@ -504,7 +506,9 @@ pub(crate) mod from_bytes {
     pub(crate) fn expr<F: FieldExt, E: Expr<F>>(bytes: &[E]) -> Expression<F> {
         debug_assert!(
             bytes.len() <= MAX_N_BYTES_INTEGER,
-            "Too many bytes to compose an integer in field"
+            "Too many bytes to compose an integer in field: {} > {}",
+            bytes.len(),
+            MAX_N_BYTES_INTEGER,
         );
         let mut value = 0.expr();
         let mut multiplier = F::one();
@@ -518,7 +522,9 @@ pub(crate) mod from_bytes {
     pub(crate) fn value<F: FieldExt>(bytes: &[u8]) -> F {
         debug_assert!(
             bytes.len() <= MAX_N_BYTES_INTEGER,
-            "Too many bytes to compose an integer in field"
+            "Too many bytes to compose an integer in field: {} > {}",
+            bytes.len(),
+            MAX_N_BYTES_INTEGER,
         );
         let mut value = F::zero();
         let mut multiplier = F::one();
@@ -528,6 +534,34 @@ pub(crate) mod from_bytes {
         }
         value
     }
+
+    #[cfg(test)]
+    mod tests {
+        use halo2_proofs::{arithmetic::Field as Halo2Field, halo2curves::bn256::Fr};
+
+        #[test]
+        fn from_bytes_value_max_n_bytes() {
+            assert_eq!(
+                super::value::<Fr>(&[0xff; super::MAX_N_BYTES_INTEGER]),
+                Fr::from(2).pow(&[8 * super::MAX_N_BYTES_INTEGER as u64, 0, 0, 0]) - Fr::one()
+            );
+        }
+
+        // 32 bytes can exceed the field modulus and wrap around silently.
+        #[cfg(debug_assertions)]
+        #[test]
+        #[should_panic(expected = "Too many bytes to compose an integer in field: 32 > 31")]
+        fn from_bytes_value_word_overflow() {
+            super::value::<Fr>(&[0xff; 32]);
+        }
+
+        #[cfg(debug_assertions)]
+        #[test]
+        #[should_panic(expected = "Too many bytes to compose an integer in field: 32 > 31")]
+        fn from_bytes_expr_word_overflow() {
+            super::expr::<Fr, _>(&[0u64; 32]);
+        }
+    }
 }
 
 /// Returns the random linear combination of the inputs.
@@ -611,3 +645,20 @@ pub(crate) fn transpose_val_ret<F, E>(value: Value<Result<F, E>>) -> Result<Valu
     });
     ret
 }
+
+/// Asserts in debug builds that a witness value assigned by a gadget matches
+/// the `expected` value recomputed outside of the circuit, so that a divergence
+/// between the witness generation and the constraints is caught at assignment
+/// time instead of as an opaque constraint failure.
+pub(crate) fn debug_assert_witness<T: PartialEq + std::fmt::Debug>(
+    gadget: &str,
+    name: &str,
+    assigned: T,
+    expected: T,
+) {
+    debug_assert_eq!(
+        assigned, expected,
+        "{}: assigned {} doesn't match the expected value",
+        gadget, name
+    );
+}
@@ -1,12 +1,18 @@
 use super::{
     from_bytes,
-    math_gadget::{IsEqualGadget, IsZeroGadget},
+    math_gadget::{
+        ConstantDivisionGadget, IsEqualGadget, IsZeroGadget, IsZeroWordGadget, LtGadget,
+        MinMaxGadget,
+    },
     memory_gadget::{MemoryAddressGadget, MemoryExpansionGadget},
     CachedRegion,
 };
 use crate::{
     evm_circuit::{
-        param::{N_BYTES_ACCOUNT_ADDRESS, N_BYTES_GAS, N_BYTES_MEMORY_WORD_SIZE},
+        param::{
+            N_BYTES_ACCOUNT_ADDRESS, N_BYTES_GAS, N_BYTES_MEMORY_WORD_SIZE,
+            N_BYTES_PROGRAM_COUNTER, N_BYTES_STACK, STACK_CAPACITY,
+        },
         table::{FixedTableTag, Lookup},
         util::{
             constraint_builder::{
@@ -21,7 +27,11 @@ use crate::{
     util::Expr,
     witness::{Block, Call, ExecStep},
 };
-use eth_types::{evm_types::GasCost, Field, ToLittleEndian, ToScalar, U256};
+use array_init::array_init;
+use eth_types::{
+    evm_types::{GasCost, OpcodeId},
+    Field, ToLittleEndian, ToScalar, U256,
+};
 use gadgets::util::{select, sum};
 use halo2_proofs::{
     circuit::Value,
@@ -88,6 +98,161 @@ impl<F: Field> SameContextGadget<F> {
     }
 }
 
+/// Reads the `num_pushed` bytes of immediate data following the opcode at the
+/// current program counter from the bytecode table, as a word. The bytes past
+/// the end of the code are read as zero, like the EVM does for a PUSH
+/// truncated by the end of the code.
+#[derive(Clone, Debug)]
+pub(crate) struct PushDataGadget<F> {
+    value: Word<F>,
+    /// `selectors[idx]` tells if the byte `idx + 1` of the value is pushed,
+    /// the byte 0 always is.
+    selectors: [Cell<F>; 31],
+    code_length: Cell<F>,
+    /// `code_length < program_counter + 1 + num_pushed`
+    is_truncated: LtGadget<F, N_BYTES_PROGRAM_COUNTER>,
+    /// `is_in_code[idx]` tells if the byte `idx` of the value is read from the
+    /// code, the ones past the end of the code being the lowest ones.
+    is_in_code: [Cell<F>; 32],
+}
+
+impl<F: Field> PushDataGadget<F> {
+    pub(crate) fn construct(cb: &mut ConstraintBuilder<F>, num_pushed: Expression<F>) -> Self {
+        let value = cb.query_word_rlc();
+        // Query selectors for each opcode_lookup
+        let selectors = array_init(|_| cb.query_bool());
+        let is_in_code = array_init(|_| cb.query_bool());
+
+        let code_length = cb.query_cell();
+        cb.bytecode_length(cb.curr.state.code_hash.expr(), code_length.expr());
+
+        // The pushed bytes are viewed as left-padded big-endian, but our random
+        // linear combination uses little-endian, so we lookup from the LSB
+        // which has index (program_counter + num_pushed), and then move left
+        // (program_counter + num_pushed - idx) to lookup all 32 bytes
+        // condiionally by selectors.
+        // For PUSH2 as an example, we lookup from byte0, byte1, ..., byte31,
+        // where the byte2 is actually the PUSH2 itself, and lookup are only
+        // enabled for byte0 and byte1.
+        //
+        //                    program_counter    program_counter + num_pushed(2)
+        //                           ▼                     ▼
+        //   [byte31,     ...,     byte2,     byte1,     byte0]
+        //
+        for idx in 0..32 {
+            let byte = &value.cells[idx];
+            let index = cb.curr.state.program_counter.expr() + num_pushed.clone() - idx.expr();
+            let selector = if idx == 0 {
+                1.expr()
+            } else {
+                selectors[idx - 1].expr()
+            };
+            cb.condition(selector * is_in_code[idx].expr(), |cb| {
+                cb.opcode_lookup_at(index, byte.expr(), 0.expr())
+            });
+            // byte should be 0 when it's past the end of the code
+            cb.require_zero(
+                "Constrain byte == 0 when is_in_code == 0",
+                byte.expr() * (1.expr() - is_in_code[idx].expr()),
+            );
+        }
+
+        for idx in 0..31 {
+            let selector_prev = if idx == 0 {
+                // First selector will always be 1
+                1.expr()
+            } else {
+                selectors[idx - 1].expr()
+            };
+            // selector can transit from 1 to 0 only once as [1, 1, 1, ...,
+            // 0, 0, 0]
+            cb.require_boolean(
+                "Constrain selector can only transit from 1 to 0",
+                selector_prev - selectors[idx].expr(),
+            );
+            // byte should be 0 when selector is 0
+            cb.require_zero(
+                "Constrain byte == 0 when selector == 0",
+                value.cells[idx + 1].expr() * (1.expr() - selectors[idx].expr()),
+            );
+            // is_in_code can transit from 0 to 1 only once as [0, 0, ..., 1,
+            // 1, 1]
+            cb.require_boolean(
+                "Constrain is_in_code can only transit from 0 to 1",
+                is_in_code[idx + 1].expr() - is_in_code[idx].expr(),
+            );
+        }
+
+        // Sum of selectors needs to be exactly the number of additional bytes
+        // that needs to be pushed.
+        cb.require_equal(
+            "Constrain sum of selectors equal to num_pushed - 1",
+            sum::expr(&selectors),
+            num_pushed.clone() - 1.expr(),
+        );
+
+        // The number of bytes past the end of the code is the overflow of the
+        // pushed bytes over the code length, if any.
+        let code_end = cb.curr.state.program_counter.expr() + 1.expr() + num_pushed;
+        let is_truncated = LtGadget::construct(cb, code_length.expr(), code_end.clone());
+        cb.require_equal(
+            "Constrain number of bytes past the end of the code",
+            32.expr() - sum::expr(&is_in_code),
+            is_truncated.expr() * (code_end - code_length.expr()),
+        );
+
+        Self {
+            value,
+            selectors,
+            code_length,
+            is_truncated,
+            is_in_code,
+        }
+    }
+
+    /// The pushed value
+    pub(crate) fn expr(&self) -> Expression<F> {
+        self.value.expr()
+    }
+
+    pub(crate) fn assign(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        value: U256,
+        num_pushed: u64,
+        program_counter: u64,
+        code_length: u64,
+    ) -> Result<(), Error> {
+        self.value
+            .assign(region, offset, Some(value.to_le_bytes()))?;
+
+        for (idx, selector) in self.selectors.iter().enumerate() {
+            selector.assign(
+                region,
+                offset,
+                Value::known(F::from((idx as u64 + 1 < num_pushed) as u64)),
+            )?;
+        }
+
+        self.code_length
+            .assign(region, offset, Value::known(F::from(code_length)))?;
+        let code_end = program_counter + 1 + num_pushed;
+        self.is_truncated
+            .assign(region, offset, F::from(code_length), F::from(code_end))?;
+        let num_past_code_end = code_end.saturating_sub(code_length);
+        for (idx, is_in_code) in self.is_in_code.iter().enumerate() {
+            is_in_code.assign(
+                region,
+                offset,
+                Value::known(F::from((idx as u64 >= num_past_code_end) as u64)),
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
 /// Construction of step state transition that restores caller's state.
 #[derive(Clone, Debug)]
 pub(crate) struct RestoreContextGadget<F> {
@@ -157,6 +322,9 @@ impl<F: Field> RestoreContextGadget<F> {
             cb.curr.state.gas_left.expr() - memory_expansion_cost - code_deposit_cost
         };
 
+        // The caller's `GasLeft` was saved by the *CALL* gadget with the call
+        // cost and the forwarded gas already deducted, so adding back the
+        // callee's leftover gas keeps the gas conserved across call frames.
         let gas_left = caller_gas_left.expr() + gas_refund;
 
         // Accumulate reversible_write_counter in case this call stack reverts in the
@@ -458,7 +626,7 @@ pub(crate) struct CommonCallGadget<F, const IS_SUCCESS_CALL: bool> {
     pub rd_address: MemoryAddressGadget<F>,
     pub memory_expansion: MemoryExpansionGadget<F, 2, N_BYTES_MEMORY_WORD_SIZE>,
 
-    value_is_zero: IsZeroGadget<F>,
+    value_is_zero: IsZeroWordGadget<F>,
     pub has_value: Expression<F>,
     pub phase2_callee_code_hash: Cell<F>,
     pub is_empty_code_hash: IsEqualGadget<F>,
@@ -513,7 +681,7 @@ impl<F: Field, const IS_SUCCESS_CALL: bool> CommonCallGadget<F, IS_SUCCESS_CALL>
             MemoryExpansionGadget::construct(cb, [cd_address.address(), rd_address.address()]);
 
         // construct common gadget
-        let value_is_zero = IsZeroGadget::construct(cb, sum::expr(&value.cells));
+        let value_is_zero = IsZeroWordGadget::construct(cb, &value);
         let has_value = select::expr(
             is_delegatecall.expr(),
             0.expr(),
@@ -614,8 +782,7 @@ impl<F: Field, const IS_SUCCESS_CALL: bool> CommonCallGadget<F, IS_SUCCESS_CALL>
             [cd_address, rd_address],
         )?;
 
-        self.value_is_zero
-            .assign(region, offset, sum::value(&value.to_le_bytes()))?;
+        self.value_is_zero.assign(region, offset, value)?;
         self.phase2_callee_code_hash
             .assign(region, offset, phase2_callee_code_hash)?;
         self.is_empty_code_hash.assign_value(
@@ -656,3 +823,437 @@ impl<F: Field, const IS_SUCCESS_CALL: bool> CommonCallGadget<F, IS_SUCCESS_CALL>
         Ok(gas_cost)
     }
 }
+
+/// Gas given to a callee by the CALL and CREATE families (EIP-150): the
+/// requested gas capped to all but one 64th of the gas available after
+/// paying for the call. `is_requested_u64` is 0 when the requested gas
+/// doesn't fit in a u64, in which case it's always above the cap; CREATE
+/// doesn't request an amount and passes 0 to get the cap.
+#[derive(Clone, Debug)]
+pub(crate) struct CalleeGasGadget<F> {
+    one_64th_gas: ConstantDivisionGadget<F, N_BYTES_GAS>,
+    capped_callee_gas_left: MinMaxGadget<F, N_BYTES_GAS>,
+    callee_gas_left: Expression<F>,
+}
+
+impl<F: Field> CalleeGasGadget<F> {
+    pub(crate) fn construct(
+        cb: &mut ConstraintBuilder<F>,
+        gas_available: Expression<F>,
+        requested_gas: Expression<F>,
+        is_requested_u64: Expression<F>,
+    ) -> Self {
+        let one_64th_gas = ConstantDivisionGadget::construct(cb, gas_available.clone(), 64);
+        let all_but_one_64th_gas = gas_available - one_64th_gas.quotient();
+        let capped_callee_gas_left =
+            MinMaxGadget::construct(cb, requested_gas, all_but_one_64th_gas.clone());
+        let callee_gas_left = select::expr(
+            is_requested_u64,
+            capped_callee_gas_left.min(),
+            all_but_one_64th_gas,
+        );
+
+        Self {
+            one_64th_gas,
+            capped_callee_gas_left,
+            callee_gas_left,
+        }
+    }
+
+    pub(crate) fn callee_gas_left(&self) -> Expression<F> {
+        self.callee_gas_left.clone()
+    }
+
+    /// Returns the gas given to the callee.
+    pub(crate) fn assign(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        gas_available: u64,
+        requested_gas: U256,
+    ) -> Result<u64, Error> {
+        self.one_64th_gas
+            .assign(region, offset, gas_available.into())?;
+        let all_but_one_64th_gas = gas_available - gas_available / 64;
+        self.capped_callee_gas_left.assign(
+            region,
+            offset,
+            F::from(requested_gas.low_u64()),
+            F::from(all_but_one_64th_gas),
+        )?;
+
+        Ok(if requested_gas.bits() <= N_BYTES_GAS * 8 {
+            all_but_one_64th_gas.min(requested_gas.low_u64())
+        } else {
+            all_but_one_64th_gas
+        })
+    }
+}
+
+/// Construction of the opcode selectors of an execution state that handles
+/// several opcodes, with one `IsEqualGadget` per given opcode. The opcode
+/// handled by the execution state but not given to the gadget is the default
+/// one, dispatched to the `default` of `select`.
+#[derive(Clone, Debug)]
+pub(crate) struct OpcodeSelectGadget<F, const N: usize> {
+    opcodes: [OpcodeId; N],
+    is_opcode: [IsEqualGadget<F>; N],
+}
+
+impl<F: Field, const N: usize> OpcodeSelectGadget<F, N> {
+    pub(crate) fn construct(
+        cb: &mut ConstraintBuilder<F>,
+        opcode: Expression<F>,
+        opcodes: [OpcodeId; N],
+    ) -> Self {
+        let is_opcode =
+            opcodes.map(|expected| IsEqualGadget::construct(cb, opcode.clone(), expected.expr()));
+
+        Self { opcodes, is_opcode }
+    }
+
+    /// Returns 1 when the opcode is `opcodes[idx]`, otherwise 0.
+    pub(crate) fn expr(&self, idx: usize) -> Expression<F> {
+        self.is_opcode[idx].expr()
+    }
+
+    /// Returns `values[idx]` when the opcode is `opcodes[idx]`, otherwise
+    /// `default`.
+    pub(crate) fn select(
+        &self,
+        values: [Expression<F>; N],
+        default: Expression<F>,
+    ) -> Expression<F> {
+        self.is_opcode
+            .iter()
+            .zip(values)
+            .rev()
+            .fold(default, |acc, (is_opcode, value)| {
+                select::expr(is_opcode.expr(), value, acc)
+            })
+    }
+
+    /// Assigns the selectors and returns their values.
+    pub(crate) fn assign(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        opcode: OpcodeId,
+    ) -> Result<[F; N], Error> {
+        let mut selectors = [F::zero(); N];
+        for ((selector, is_opcode), expected) in selectors
+            .iter_mut()
+            .zip(self.is_opcode.iter())
+            .zip(self.opcodes)
+        {
+            *selector = is_opcode.assign(
+                region,
+                offset,
+                F::from(opcode.as_u64()),
+                F::from(expected.as_u64()),
+            )?;
+        }
+
+        Ok(selectors)
+    }
+}
+
+/// Construction of the check that the stack holds at least `depth` items, for
+/// the opcodes reaching deep into the stack, i.e. DUPn and SWAPn (see
+/// `OpcodeId::dup_swap_depth`). On a shallower stack the step can only be an
+/// `ErrorStack` with a stack underflow.
+#[derive(Clone, Debug)]
+pub(crate) struct StackDepthGadget<F> {
+    is_underflow: LtGadget<F, N_BYTES_STACK>,
+}
+
+impl<F: Field> StackDepthGadget<F> {
+    pub(crate) fn construct(cb: &mut ConstraintBuilder<F>, depth: Expression<F>) -> Self {
+        let is_underflow = LtGadget::construct(
+            cb,
+            STACK_CAPACITY.expr() - cb.curr.state.stack_pointer.expr(),
+            depth,
+        );
+        cb.require_zero("stack holds at least depth items", is_underflow.expr());
+
+        Self { is_underflow }
+    }
+
+    pub(crate) fn assign(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        step: &ExecStep,
+        depth: u64,
+    ) -> Result<(), Error> {
+        self.is_underflow.assign(
+            region,
+            offset,
+            F::from((STACK_CAPACITY - step.stack_pointer) as u64),
+            F::from(depth),
+        )?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::math_gadget::test_util::*;
+    use super::*;
+    use eth_types::Word;
+    use halo2_proofs::halo2curves::bn256::Fr;
+
+    #[derive(Clone)]
+    /// CalleeGasGadgetTestContainer: require(callee_gas_left == expected)
+    struct CalleeGasGadgetTestContainer<F> {
+        callee_gas: CalleeGasGadget<F>,
+        gas_available: Cell<F>,
+        requested_gas: Cell<F>,
+        is_requested_u64: Cell<F>,
+        expected: Cell<F>,
+    }
+
+    impl<F: Field> MathGadgetContainer<F> for CalleeGasGadgetTestContainer<F> {
+        fn configure_gadget_container(cb: &mut ConstraintBuilder<F>) -> Self {
+            let gas_available = cb.query_cell();
+            let requested_gas = cb.query_cell();
+            let is_requested_u64 = cb.query_bool();
+            let expected = cb.query_cell();
+            let callee_gas = CalleeGasGadget::construct(
+                cb,
+                gas_available.expr(),
+                requested_gas.expr(),
+                is_requested_u64.expr(),
+            );
+            cb.require_equal(
+                "callee_gas_left == expected",
+                callee_gas.callee_gas_left(),
+                expected.expr(),
+            );
+
+            CalleeGasGadgetTestContainer {
+                callee_gas,
+                gas_available,
+                requested_gas,
+                is_requested_u64,
+                expected,
+            }
+        }
+
+        fn assign_gadget_container(
+            &self,
+            witnesses: &[Word],
+            region: &mut CachedRegion<'_, '_, F>,
+        ) -> Result<(), Error> {
+            let gas_available = witnesses[0].as_u64();
+            let requested_gas = witnesses[1];
+            let expected = witnesses[2].as_u64();
+            let offset = 0;
+
+            self.gas_available
+                .assign(region, offset, Value::known(F::from(gas_available)))?;
+            self.requested_gas.assign(
+                region,
+                offset,
+                Value::known(F::from(requested_gas.low_u64())),
+            )?;
+            self.is_requested_u64.assign(
+                region,
+                offset,
+                Value::known(F::from((requested_gas.bits() <= N_BYTES_GAS * 8) as u64)),
+            )?;
+            self.expected
+                .assign(region, offset, Value::known(F::from(expected)))?;
+            self.callee_gas
+                .assign(region, offset, gas_available, requested_gas)?;
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_callee_gas_all_but_one_64th() {
+        // Requesting more than available gets all but one 64th
+        for (gas_available, expected) in [
+            (0, 0),
+            (1, 1),
+            (63, 63),
+            (64, 63),
+            (127, 126),
+            (128, 126),
+            (6400, 6300),
+            (1_000_000, 984_375),
+        ] {
+            try_test!(
+                CalleeGasGadgetTestContainer<Fr>,
+                vec![
+                    Word::from(gas_available),
+                    Word::from(u64::MAX),
+                    Word::from(expected)
+                ],
+                true,
+            );
+        }
+    }
+
+    #[test]
+    fn test_callee_gas_requested() {
+        try_test!(
+            CalleeGasGadgetTestContainer<Fr>,
+            vec![Word::from(6400), Word::from(100), Word::from(100)],
+            true,
+        );
+        try_test!(
+            CalleeGasGadgetTestContainer<Fr>,
+            vec![Word::from(6400), Word::from(6300), Word::from(6300)],
+            true,
+        );
+        // Requested gas over u64
+        try_test!(
+            CalleeGasGadgetTestContainer<Fr>,
+            vec![Word::from(6400), Word::MAX, Word::from(6300)],
+            true,
+        );
+    }
+
+    #[test]
+    fn test_callee_gas_unexpected() {
+        try_test!(
+            CalleeGasGadgetTestContainer<Fr>,
+            vec![Word::from(64), Word::from(u64::MAX), Word::from(64)],
+            false,
+        );
+        try_test!(
+            CalleeGasGadgetTestContainer<Fr>,
+            vec![Word::from(6400), Word::from(6301), Word::from(6301)],
+            false,
+        );
+    }
+
+    #[derive(Clone)]
+    /// OpcodeSelectGadgetTestContainer: require(select([ADD, SUB, MUL] => [1,
+    /// 2, 3], 0) == expected), and the same for the hand-written selectors
+    struct OpcodeSelectGadgetTestContainer<F> {
+        opcode_select: OpcodeSelectGadget<F, 3>,
+        is_add: IsEqualGadget<F>,
+        is_sub: IsEqualGadget<F>,
+        is_mul: IsEqualGadget<F>,
+        opcode: Cell<F>,
+        expected: Cell<F>,
+    }
+
+    impl<F: Field> MathGadgetContainer<F> for OpcodeSelectGadgetTestContainer<F> {
+        fn configure_gadget_container(cb: &mut ConstraintBuilder<F>) -> Self {
+            let opcode = cb.query_cell();
+            let expected = cb.query_cell();
+            let opcode_select = OpcodeSelectGadget::construct(
+                cb,
+                opcode.expr(),
+                [OpcodeId::ADD, OpcodeId::SUB, OpcodeId::MUL],
+            );
+            let is_add = IsEqualGadget::construct(cb, opcode.expr(), OpcodeId::ADD.expr());
+            let is_sub = IsEqualGadget::construct(cb, opcode.expr(), OpcodeId::SUB.expr());
+            let is_mul = IsEqualGadget::construct(cb, opcode.expr(), OpcodeId::MUL.expr());
+
+            cb.require_equal(
+                "select == expected",
+                opcode_select.select([1.expr(), 2.expr(), 3.expr()], 0.expr()),
+                expected.expr(),
+            );
+            cb.require_equal(
+                "hand-written select == expected",
+                select::expr(
+                    is_add.expr(),
+                    1.expr(),
+                    select::expr(
+                        is_sub.expr(),
+                        2.expr(),
+                        select::expr(is_mul.expr(), 3.expr(), 0.expr()),
+                    ),
+                ),
+                expected.expr(),
+            );
+            for (idx, is_opcode) in [&is_add, &is_sub, &is_mul].into_iter().enumerate() {
+                cb.require_equal(
+                    "selector == hand-written selector",
+                    opcode_select.expr(idx),
+                    is_opcode.expr(),
+                );
+            }
+
+            OpcodeSelectGadgetTestContainer {
+                opcode_select,
+                is_add,
+                is_sub,
+                is_mul,
+                opcode,
+                expected,
+            }
+        }
+
+        fn assign_gadget_container(
+            &self,
+            witnesses: &[Word],
+            region: &mut CachedRegion<'_, '_, F>,
+        ) -> Result<(), Error> {
+            let opcode = OpcodeId::from(witnesses[0].as_u64() as u8);
+            let expected = witnesses[1].as_u64();
+            let offset = 0;
+
+            self.opcode
+                .assign(region, offset, Value::known(F::from(opcode.as_u64())))?;
+            self.expected
+                .assign(region, offset, Value::known(F::from(expected)))?;
+            let selectors = self.opcode_select.assign(region, offset, opcode)?;
+            for ((is_opcode, expected_opcode), selector) in [
+                (&self.is_add, OpcodeId::ADD),
+                (&self.is_sub, OpcodeId::SUB),
+                (&self.is_mul, OpcodeId::MUL),
+            ]
+            .into_iter()
+            .zip(selectors)
+            {
+                let value = is_opcode.assign(
+                    region,
+                    offset,
+                    F::from(opcode.as_u64()),
+                    F::from(expected_opcode.as_u64()),
+                )?;
+                assert_eq!(value, selector);
+            }
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_opcode_select() {
+        for (opcode, expected) in [
+            (OpcodeId::ADD, 1),
+            (OpcodeId::SUB, 2),
+            (OpcodeId::MUL, 3),
+            (OpcodeId::DIV, 0),
+        ] {
+            try_test!(
+                OpcodeSelectGadgetTestContainer<Fr>,
+                vec![Word::from(opcode.as_u64()), Word::from(expected)],
+                true,
+            );
+        }
+    }
+
+    #[test]
+    fn test_opcode_select_unexpected() {
+        try_test!(
+            OpcodeSelectGadgetTestContainer<Fr>,
+            vec![Word::from(OpcodeId::SUB.as_u64()), Word::from(1)],
+            false,
+        );
+        try_test!(
+            OpcodeSelectGadgetTestContainer<Fr>,
+            vec![Word::from(OpcodeId::DIV.as_u64()), Word::from(3)],
+            false,
+        );
+    }
+}
@@ -261,6 +261,34 @@ pub(crate) struct Constraints<F> {
     pub(crate) not_step_last: Vec<(&'static str, Expression<F>)>,
 }
 
+/// Number of constraints and lookups contributed by an execution gadget, to
+/// find out which gadgets dominate the size of the EVM circuit.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct ConstraintCounts {
+    pub(crate) constraints: usize,
+    pub(crate) lookups: usize,
+}
+
+impl ConstraintCounts {
+    pub(crate) fn new<F>(
+        constraints: &Constraints<F>,
+        stored_expressions: &[StoredExpression<F>],
+    ) -> Self {
+        Self {
+            constraints: constraints.step.len()
+                + constraints.step_first.len()
+                + constraints.step_last.len()
+                + constraints.not_step_last.len(),
+            lookups: stored_expressions
+                .iter()
+                .filter(|stored_expression| {
+                    matches!(stored_expression.cell_type, CellType::Lookup(_))
+                })
+                .count(),
+        }
+    }
+}
+
 pub(crate) struct ConstraintBuilder<'a, F> {
     pub max_degree: usize,
     pub(crate) curr: Step<F>,
@@ -857,6 +885,39 @@ impl<'a, F: Field> ConstraintBuilder<'a, F> {
         );
     }
 
+    /// Adds the account `account_address` to the access list of the tx
+    /// `tx_id` (EIP-2929), or its storage slot when `storage_key` is given,
+    /// with an access list write. Returns the boolean cell telling if it was
+    /// already in it, so gadgets charging cold/warm access gas don't have to
+    /// re-derive it.
+    pub(crate) fn add_to_access_list(
+        &mut self,
+        tx_id: Expression<F>,
+        account_address: Expression<F>,
+        storage_key: Option<Expression<F>>,
+        reversion_info: Option<&mut ReversionInfo<F>>,
+    ) -> Cell<F> {
+        let is_warm = self.query_bool();
+        match storage_key {
+            Some(storage_key) => self.account_storage_access_list_write(
+                tx_id,
+                account_address,
+                storage_key,
+                1.expr(),
+                is_warm.expr(),
+                reversion_info,
+            ),
+            None => self.account_access_list_write(
+                tx_id,
+                account_address,
+                1.expr(),
+                is_warm.expr(),
+                reversion_info,
+            ),
+        }
+        is_warm
+    }
+
     // Tx Refund
 
     pub(crate) fn tx_refund_read(&mut self, tx_id: Expression<F>, value: Expression<F>) {
@@ -1056,6 +1117,13 @@ impl<'a, F: Field> ConstraintBuilder<'a, F> {
         );
     }
 
+    /// Constrains the current call not to be static, as required by the opcodes
+    /// modifying the state. In a static call they halt with
+    /// `ErrorWriteProtection` instead.
+    pub(crate) fn require_not_static(&mut self) {
+        self.call_context_lookup(false.expr(), None, CallContextFieldTag::IsStatic, 0.expr());
+    }
+
     fn reversion_info(
         &mut self,
         call_id: Option<Expression<F>>,
@@ -1103,6 +1171,11 @@ impl<'a, F: Field> ConstraintBuilder<'a, F> {
         self.stack_pointer_offset = self.stack_pointer_offset.clone() + self.condition_expr();
     }
 
+    /// Pushes `value`, the RLC of a word as encoded in the rw table. Its bytes
+    /// must be range checked for the value to fit in 256 bits: the bytes of a
+    /// `Word` are byte cells looked up in the byte table, while a `WordRlc` or
+    /// another cell without bytes is only valid when bound to a value looked
+    /// up from a table.
     pub(crate) fn stack_push(&mut self, value: Expression<F>) {
         self.stack_pointer_offset = self.stack_pointer_offset.clone() - self.condition_expr();
         self.stack_lookup(true.expr(), self.stack_pointer_offset.expr(), value);
@@ -5,12 +5,14 @@ use halo2_proofs::plonk::Expression;
 mod abs_word;
 mod add_words;
 mod batched_is_zero;
+mod bit_range_check;
 mod byte_size;
 mod cmp_words;
 mod comparison;
 mod constant_division;
 mod is_equal;
 mod is_zero;
+mod is_zero_word;
 mod lt;
 mod lt_word;
 mod min_max;
@@ -18,20 +20,25 @@ mod modulo;
 mod mul_add_words;
 mod mul_add_words512;
 mod mul_word_u64;
+mod neg_word;
 mod pair_select;
 mod range_check;
+mod shr_word;
+mod signed_div_sign;
 #[cfg(test)]
-mod test_util;
+pub(crate) mod test_util;
 
 pub(crate) use abs_word::AbsWordGadget;
 pub(crate) use add_words::AddWordsGadget;
 pub use batched_is_zero::BatchedIsZeroGadget;
+pub(crate) use bit_range_check::BitRangeCheckGadget;
 pub(crate) use byte_size::ByteSizeGadget;
 pub(crate) use cmp_words::CmpWordsGadget;
 pub(crate) use comparison::ComparisonGadget;
 pub(crate) use constant_division::ConstantDivisionGadget;
 pub(crate) use is_equal::IsEqualGadget;
 pub(crate) use is_zero::IsZeroGadget;
+pub(crate) use is_zero_word::IsZeroWordGadget;
 pub(crate) use lt::LtGadget;
 pub(crate) use lt_word::LtWordGadget;
 pub(crate) use min_max::MinMaxGadget;
@@ -39,8 +46,11 @@ pub(crate) use modulo::ModGadget;
 pub(crate) use mul_add_words::MulAddWordsGadget;
 pub(crate) use mul_add_words512::MulAddWords512Gadget;
 pub(crate) use mul_word_u64::MulWordByU64Gadget;
+pub(crate) use neg_word::NegWordGadget;
 pub(crate) use pair_select::PairSelectGadget;
 pub(crate) use range_check::RangeCheckGadget;
+pub(crate) use shr_word::ShrWordGadget;
+pub(crate) use signed_div_sign::SignedDivSignGadget;
 
 // This function generates a Lagrange polynomial in the range [start, end) which
 // will be evaluated to 1 when `exp == value`, otherwise 0
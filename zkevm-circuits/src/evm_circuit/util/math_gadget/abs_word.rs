@@ -17,16 +17,14 @@ use halo2_proofs::plonk::Error;
 pub(crate) struct AbsWordGadget<F> {
     x: util::Word<F>,
     x_abs: util::Word<F>,
-    sum: util::Word<F>,
     is_neg: LtGadget<F, 1>,
-    add_words: AddWordsGadget<F, 2, false>,
+    neg_word: NegWordGadget<F>,
 }
 
 impl<F: Field> AbsWordGadget<F> {
     pub(crate) fn construct(cb: &mut ConstraintBuilder<F>) -> Self {
         let x = cb.query_word_rlc();
         let x_abs = cb.query_word_rlc();
-        let sum = cb.query_word_rlc();
         let x_lo = from_bytes::expr(&x.cells[0..16]);
         let x_hi = from_bytes::expr(&x.cells[16..32]);
         let x_abs_lo = from_bytes::expr(&x_abs.cells[0..16]);
@@ -42,24 +40,14 @@ impl<F: Field> AbsWordGadget<F> {
             (1.expr() - is_neg.expr()) * (x_abs_hi.expr() - x_hi.expr()),
         );
 
-        // When `is_neg`, constrain `sum == 0` and `carry == 1`. Since the final
-        // result is `1 << 256`.
-        let add_words = AddWordsGadget::construct(cb, [x.clone(), x_abs.clone()], sum.clone());
-        cb.add_constraint(
-            "sum == 0 when x < 0",
-            is_neg.expr() * add_words.sum().expr(),
-        );
-        cb.add_constraint(
-            "carry_hi == 1 when x < 0",
-            is_neg.expr() * (1.expr() - add_words.carry().as_ref().unwrap().expr()),
-        );
+        // When `is_neg`, constrain `x_abs == -x`.
+        let neg_word = cb.condition(is_neg.expr(), |cb| NegWordGadget::construct(cb, &x, &x_abs));
 
         Self {
             x,
             x_abs,
-            sum,
             is_neg,
-            add_words,
+            neg_word,
         }
     }
 
@@ -79,9 +67,7 @@ impl<F: Field> AbsWordGadget<F> {
             127.into(),
             u64::from(x.to_le_bytes()[31]).into(),
         )?;
-        let sum = x.overflowing_add(x_abs).0;
-        self.sum.assign(region, offset, Some(sum.to_le_bytes()))?;
-        self.add_words.assign(region, offset, [x, x_abs], sum)
+        self.neg_word.assign(region, offset, x, x_abs)
     }
 
     pub(crate) fn x(&self) -> &util::Word<F> {
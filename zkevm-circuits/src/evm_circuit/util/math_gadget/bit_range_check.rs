@@ -0,0 +1,165 @@
+use crate::{
+    evm_circuit::util::{
+        constraint_builder::ConstraintBuilder, pow_of_two_expr, sum, CachedRegion, Cell,
+    },
+    util::Expr,
+};
+use eth_types::Field;
+use halo2_proofs::{
+    circuit::Value,
+    plonk::{Error, Expression},
+};
+
+/// Requires that the passed in value is within `[0, 1 << N_BITS)` by
+/// decomposing it into bits. Unlike `RangeCheckGadget` or a fixed table range
+/// lookup it doesn't need any lookup, which is cheaper for tiny values like
+/// shift amounts or byte counts.
+/// `N_BITS` is required to be `< F::NUM_BITS`.
+#[derive(Clone, Debug)]
+pub(crate) struct BitRangeCheckGadget<F, const N_BITS: usize> {
+    bits: [Cell<F>; N_BITS],
+}
+
+impl<F: Field, const N_BITS: usize> BitRangeCheckGadget<F, N_BITS> {
+    pub(crate) fn construct(cb: &mut ConstraintBuilder<F>, value: Expression<F>) -> Self {
+        let bits = [(); N_BITS].map(|_| cb.query_bool());
+
+        // Require that the reconstructed value from the bits equals the
+        // original value
+        cb.require_equal(
+            "Constrain bits recomposited to value",
+            value,
+            sum::expr(
+                bits.iter()
+                    .enumerate()
+                    .map(|(idx, bit)| bit.expr() * pow_of_two_expr(idx)),
+            ),
+        );
+
+        Self { bits }
+    }
+
+    pub(crate) fn assign(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        value: F,
+    ) -> Result<(), Error> {
+        let bytes = value.to_repr();
+        for (idx, bit) in self.bits.iter().enumerate() {
+            let bit_value = (bytes[idx / 8] >> (idx % 8)) & 1;
+            bit.assign(region, offset, Value::known(F::from(bit_value as u64)))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_util::*;
+    use super::*;
+    use eth_types::*;
+    use halo2_proofs::circuit::Value;
+    use halo2_proofs::halo2curves::bn256::Fr;
+    use halo2_proofs::plonk::Error;
+
+    #[derive(Clone)]
+    /// BitRangeCheckTestContainer: require(a in [0..1<<N_BITS])
+    struct BitRangeCheckTestContainer<F, const N_BITS: usize> {
+        bit_range_check_gadget: BitRangeCheckGadget<F, N_BITS>,
+        a: Cell<F>,
+    }
+
+    impl<F: Field, const N_BITS: usize> MathGadgetContainer<F>
+        for BitRangeCheckTestContainer<F, N_BITS>
+    {
+        fn configure_gadget_container(cb: &mut ConstraintBuilder<F>) -> Self {
+            let a = cb.query_cell();
+            let bit_range_check_gadget = BitRangeCheckGadget::<F, N_BITS>::construct(cb, a.expr());
+            BitRangeCheckTestContainer {
+                bit_range_check_gadget,
+                a,
+            }
+        }
+
+        fn assign_gadget_container(
+            &self,
+            witnesses: &[Word],
+            region: &mut CachedRegion<'_, '_, F>,
+        ) -> Result<(), Error> {
+            let a = witnesses[0].to_scalar().unwrap();
+            let offset = 0;
+
+            self.a.assign(region, offset, Value::known(a))?;
+            self.bit_range_check_gadget.assign(region, offset, a)?;
+
+            Ok(())
+        }
+    }
+
+    #[derive(Clone)]
+    /// LookupRangeCheckTestContainer: require(a in [0..32]) via the Range32
+    /// fixed table
+    struct LookupRangeCheckTestContainer<F> {
+        a: Cell<F>,
+    }
+
+    impl<F: Field> MathGadgetContainer<F> for LookupRangeCheckTestContainer<F> {
+        fn configure_gadget_container(cb: &mut ConstraintBuilder<F>) -> Self {
+            let a = cb.query_cell();
+            cb.range_lookup(a.expr(), 32);
+            LookupRangeCheckTestContainer { a }
+        }
+
+        fn assign_gadget_container(
+            &self,
+            witnesses: &[Word],
+            region: &mut CachedRegion<'_, '_, F>,
+        ) -> Result<(), Error> {
+            let a = witnesses[0].to_scalar().unwrap();
+            self.a.assign(region, 0, Value::known(a))?;
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_bit_rangecheck_just_in_range() {
+        try_test!(BitRangeCheckTestContainer<Fr, 5>, vec![Word::from(0)], true);
+        try_test!(BitRangeCheckTestContainer<Fr, 5>, vec![Word::from(1)], true);
+        // max - 1
+        try_test!(BitRangeCheckTestContainer<Fr, 5>, vec![Word::from(31)], true);
+        try_test!(
+            BitRangeCheckTestContainer<Fr, 10>,
+            vec![Word::from((1u64 << 10) - 1)],
+            true,
+        );
+    }
+
+    #[test]
+    fn test_bit_rangecheck_out_of_range() {
+        try_test!(BitRangeCheckTestContainer<Fr, 5>, vec![Word::from(32)], false);
+        try_test!(
+            BitRangeCheckTestContainer<Fr, 10>,
+            vec![Word::from(1u64 << 10)],
+            false,
+        );
+    }
+
+    #[test]
+    fn test_bit_rangecheck_matches_range_lookup() {
+        for a in [0u64, 1, 16, 31, 32, 33, 63, 255] {
+            let expected_success = a < 32;
+            try_test!(
+                BitRangeCheckTestContainer<Fr, 5>,
+                vec![Word::from(a)],
+                expected_success,
+            );
+            try_test!(
+                LookupRangeCheckTestContainer<Fr>,
+                vec![Word::from(a)],
+                expected_success,
+            );
+        }
+    }
+}
@@ -88,6 +88,7 @@ mod tests {
     use super::test_util::*;
     use super::*;
     use eth_types::Word;
+    use halo2_proofs::circuit::Value;
     use halo2_proofs::halo2curves::bn256::Fr;
     use halo2_proofs::plonk::Error;
 
@@ -219,4 +220,94 @@ mod tests {
             false,
         );
     }
+
+    #[test]
+    fn test_cmpword_high_half_decides() {
+        // The high halves differ, the low halves compare the other way
+        let a = (Word::from(1) << 128) + Word::from(u128::MAX);
+        let b = Word::from(2) << 128;
+        try_test!(CmpWordGadgetTestContainer<Fr, false>, vec![a, b], true);
+        try_test!(CmpWordGadgetTestContainer<Fr, false>, vec![b, a], false);
+        try_test!(CmpWordGadgetTestContainer<Fr, true>, vec![a, b], false);
+    }
+
+    #[test]
+    fn test_cmpword_low_half_decides() {
+        // The high halves are equal
+        let a = (Word::from(0xcafe) << 128) + 1;
+        let b = (Word::from(0xcafe) << 128) + 2;
+        try_test!(CmpWordGadgetTestContainer<Fr, false>, vec![a, b], true);
+        try_test!(CmpWordGadgetTestContainer<Fr, false>, vec![b, a], false);
+        try_test!(CmpWordGadgetTestContainer<Fr, true>, vec![a, b], false);
+    }
+
+    #[derive(Clone)]
+    /// CmpWordForgedBytesTestContainer: require(a < b), with the cells of `a`
+    /// assigned as given instead of from the bytes of a word
+    struct CmpWordForgedBytesTestContainer<F> {
+        cmp_gadget: CmpWordsGadget<F>,
+        a: util::Word<F>,
+        b: util::Word<F>,
+    }
+
+    impl<F: Field> MathGadgetContainer<F> for CmpWordForgedBytesTestContainer<F> {
+        fn configure_gadget_container(cb: &mut ConstraintBuilder<F>) -> Self {
+            let a = cb.query_word_rlc();
+            let b = cb.query_word_rlc();
+            let cmp_gadget = CmpWordsGadget::<F>::construct(cb, &a, &b);
+            cb.require_equal("a < b", cmp_gadget.lt.clone(), 1.expr());
+
+            CmpWordForgedBytesTestContainer { cmp_gadget, a, b }
+        }
+
+        fn assign_gadget_container(
+            &self,
+            witnesses: &[Word],
+            region: &mut CachedRegion<'_, '_, F>,
+        ) -> Result<(), Error> {
+            let a_cells = &witnesses[0..32];
+            let b = witnesses[32];
+            let offset = 0;
+
+            for (cell, value) in self.a.cells.iter().zip(a_cells) {
+                cell.assign(region, offset, Value::known(F::from(value.as_u64())))?;
+            }
+            self.b.assign(region, offset, Some(b.to_le_bytes()))?;
+
+            let half = |cells: &[Word]| {
+                cells
+                    .iter()
+                    .rev()
+                    .fold(F::zero(), |acc, v| acc * F::from(256) + F::from(v.as_u64()))
+            };
+            self.cmp_gadget.comparison_lo.assign(
+                region,
+                offset,
+                half(&a_cells[0..16]),
+                from_bytes::value(&b.to_le_bytes()[0..16]),
+            )?;
+            self.cmp_gadget.comparison_hi.assign(
+                region,
+                offset,
+                half(&a_cells[16..32]),
+                from_bytes::value(&b.to_le_bytes()[16..32]),
+            )?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_cmpword_forged_byte() {
+        // a = 2 * 256^16 > b = 256^16. Assigning it as 512 in the top cell of
+        // the low half keeps the value of `a` but zeroes its high half, which
+        // would prove a < b without the range check on the byte cells.
+        let b = Word::from(1) << 128;
+        let mut a_cells = vec![Word::zero(); 32];
+        a_cells[15] = Word::from(512);
+        try_test!(
+            CmpWordForgedBytesTestContainer<Fr>,
+            [a_cells, vec![b]].concat(),
+            false,
+        );
+    }
 }
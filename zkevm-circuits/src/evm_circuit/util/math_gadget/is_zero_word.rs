@@ -0,0 +1,115 @@
+use crate::{
+    evm_circuit::util::{
+        self, constraint_builder::ConstraintBuilder, math_gadget::*, sum, CachedRegion,
+    },
+    util::Expr,
+};
+use eth_types::{Field, ToLittleEndian, Word};
+use halo2_proofs::plonk::{Error, Expression};
+
+/// Returns `1` when the 256-bit `word == 0`, and returns `0` otherwise.
+///
+/// The check is done on the sum of the 32 bytes of the word. Each byte cell is
+/// range checked, so the sum is at most `32 * 255` and can't wrap around to
+/// zero for a nonzero word. Checking the RLC of the word in a single phase2
+/// cell instead would only be sound with overwhelming probability over the
+/// challenge, as a nonzero word can have a zero RLC. The price is the 32 byte
+/// cells of the word: ISZERO and JUMPI query them instead of that phase2 cell,
+/// while the CALL value already has them.
+#[derive(Clone, Debug)]
+pub(crate) struct IsZeroWordGadget<F> {
+    is_zero: IsZeroGadget<F>,
+}
+
+impl<F: Field> IsZeroWordGadget<F> {
+    pub(crate) fn construct(cb: &mut ConstraintBuilder<F>, word: &util::Word<F>) -> Self {
+        let is_zero = IsZeroGadget::construct(cb, sum::expr(&word.cells));
+
+        Self { is_zero }
+    }
+
+    pub(crate) fn expr(&self) -> Expression<F> {
+        self.is_zero.expr()
+    }
+
+    pub(crate) fn assign(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        word: Word,
+    ) -> Result<F, Error> {
+        self.is_zero
+            .assign(region, offset, sum::value(&word.to_le_bytes()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_util::*;
+    use super::*;
+    use eth_types::Word;
+    use halo2_proofs::halo2curves::bn256::Fr;
+    use halo2_proofs::plonk::Error;
+
+    #[derive(Clone)]
+    /// IsZeroWordGadgetTestContainer: require(a == 0)
+    struct IsZeroWordGadgetTestContainer<F> {
+        is_zero_word_gadget: IsZeroWordGadget<F>,
+        a: util::Word<F>,
+    }
+
+    impl<F: Field> MathGadgetContainer<F> for IsZeroWordGadgetTestContainer<F> {
+        fn configure_gadget_container(cb: &mut ConstraintBuilder<F>) -> Self {
+            let a = cb.query_word_rlc();
+            let is_zero_word_gadget = IsZeroWordGadget::construct(cb, &a);
+            cb.require_equal("Input is zero", is_zero_word_gadget.expr(), 1.expr());
+            IsZeroWordGadgetTestContainer {
+                is_zero_word_gadget,
+                a,
+            }
+        }
+
+        fn assign_gadget_container(
+            &self,
+            witnesses: &[Word],
+            region: &mut CachedRegion<'_, '_, F>,
+        ) -> Result<(), Error> {
+            let a = witnesses[0];
+            let offset = 0;
+
+            self.a.assign(region, offset, Some(a.to_le_bytes()))?;
+            self.is_zero_word_gadget.assign(region, offset, a)?;
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_is_zero_word_zero() {
+        try_test!(IsZeroWordGadgetTestContainer<Fr>, vec![Word::zero()], true);
+    }
+
+    #[test]
+    fn test_is_zero_word_low_half_nonzero() {
+        try_test!(IsZeroWordGadgetTestContainer<Fr>, vec![Word::one()], false);
+        try_test!(
+            IsZeroWordGadgetTestContainer<Fr>,
+            vec![Word::from(u128::MAX)],
+            false,
+        );
+    }
+
+    #[test]
+    fn test_is_zero_word_high_half_nonzero() {
+        try_test!(
+            IsZeroWordGadgetTestContainer<Fr>,
+            vec![Word::one() << 128],
+            false,
+        );
+        try_test!(
+            IsZeroWordGadgetTestContainer<Fr>,
+            vec![Word::MAX - Word::from(u128::MAX)],
+            false,
+        );
+    }
+}
@@ -0,0 +1,181 @@
+use crate::{
+    evm_circuit::util::{
+        self, constraint_builder::ConstraintBuilder, from_bytes, pow_of_two_expr, split_u256,
+        CachedRegion, Cell,
+    },
+    util::Expr,
+};
+use eth_types::{Field, ToScalar, Word};
+use halo2_proofs::{circuit::Value, plonk::Error};
+
+/// Construction of the two's complement negation `neg = -x mod 2^256` of a
+/// 256-bit word, i.e. `!x + 1`, constrained as `x + neg == 0 mod 2^256`.
+/// Negating zero gives zero, and `-2^255` (expressed as an U256 of `2^255`)
+/// is its own negation.
+#[derive(Clone, Debug)]
+pub(crate) struct NegWordGadget<F> {
+    carry_lo: Cell<F>,
+    carry_hi: Cell<F>,
+}
+
+impl<F: Field> NegWordGadget<F> {
+    pub(crate) fn construct(
+        cb: &mut ConstraintBuilder<F>,
+        x: &util::Word<F>,
+        neg: &util::Word<F>,
+    ) -> Self {
+        let carry_lo = cb.query_cell();
+        let carry_hi = cb.query_cell();
+
+        let x_lo = from_bytes::expr(&x.cells[0..16]);
+        let x_hi = from_bytes::expr(&x.cells[16..32]);
+        let neg_lo = from_bytes::expr(&neg.cells[0..16]);
+        let neg_hi = from_bytes::expr(&neg.cells[16..32]);
+
+        // Both halves are less than `2^128`, so a boolean carry leaves a single
+        // `neg` for each `x`, including `neg == 0` when `x == 0`.
+        cb.require_equal(
+            "x_lo + neg_lo == carry_lo ⋅ 2^128",
+            x_lo + neg_lo,
+            carry_lo.expr() * pow_of_two_expr(128),
+        );
+        cb.require_equal(
+            "x_hi + neg_hi + carry_lo == carry_hi ⋅ 2^128",
+            x_hi + neg_hi + carry_lo.expr(),
+            carry_hi.expr() * pow_of_two_expr(128),
+        );
+        cb.require_boolean("carry_lo is boolean", carry_lo.expr());
+        cb.require_boolean("carry_hi is boolean", carry_hi.expr());
+
+        Self { carry_lo, carry_hi }
+    }
+
+    pub(crate) fn assign(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        x: Word,
+        neg: Word,
+    ) -> Result<(), Error> {
+        let (x_lo, x_hi) = split_u256(&x);
+        let (neg_lo, neg_hi) = split_u256(&neg);
+
+        let carry_lo = (x_lo + neg_lo) >> 128;
+        let carry_hi = (x_hi + neg_hi + carry_lo) >> 128;
+        for (cell, carry) in [(&self.carry_lo, carry_lo), (&self.carry_hi, carry_hi)] {
+            cell.assign(
+                region,
+                offset,
+                Value::known(
+                    carry
+                        .to_scalar()
+                        .expect("unexpected U256 -> Scalar conversion failure"),
+                ),
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_util::*;
+    use super::*;
+    use eth_types::{ToLittleEndian, Word};
+    use halo2_proofs::halo2curves::bn256::Fr;
+    use halo2_proofs::plonk::Error;
+
+    #[derive(Clone)]
+    /// NegWordGadgetContainer: require(neg == -x)
+    struct NegWordGadgetContainer<F> {
+        negword_gadget: NegWordGadget<F>,
+        x: util::Word<F>,
+        neg: util::Word<F>,
+    }
+
+    impl<F: Field> MathGadgetContainer<F> for NegWordGadgetContainer<F> {
+        fn configure_gadget_container(cb: &mut ConstraintBuilder<F>) -> Self {
+            let x = cb.query_word_rlc();
+            let neg = cb.query_word_rlc();
+            let negword_gadget = NegWordGadget::<F>::construct(cb, &x, &neg);
+            NegWordGadgetContainer {
+                negword_gadget,
+                x,
+                neg,
+            }
+        }
+
+        fn assign_gadget_container(
+            &self,
+            witnesses: &[Word],
+            region: &mut CachedRegion<'_, '_, F>,
+        ) -> Result<(), Error> {
+            let offset = 0;
+            let x = witnesses[0];
+            let neg = witnesses[1];
+            self.x.assign(region, offset, Some(x.to_le_bytes()))?;
+            self.neg.assign(region, offset, Some(neg.to_le_bytes()))?;
+            self.negword_gadget.assign(region, offset, x, neg)?;
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_neg_1_eq_minus1() {
+        try_test!(NegWordGadgetContainer<Fr>, [Word::from(1), Word::MAX], true);
+    }
+
+    #[test]
+    fn test_neg_minus1_eq_1() {
+        try_test!(NegWordGadgetContainer<Fr>, [Word::MAX, Word::from(1)], true);
+    }
+
+    #[test]
+    fn test_neg_0_eq_0() {
+        try_test!(
+            NegWordGadgetContainer<Fr>,
+            [Word::from(0), Word::from(0)],
+            true
+        );
+    }
+
+    // `-2^255` is the fixed point of the negation.
+    #[test]
+    fn test_neg_word_signed_min() {
+        try_test!(
+            NegWordGadgetContainer<Fr>,
+            [WORD_SIGNED_MIN, WORD_SIGNED_MIN],
+            true
+        );
+    }
+
+    #[test]
+    fn test_neg_word_low_max() {
+        let neg_low_max = WORD_HIGH_MAX + Word::from(1);
+        try_test!(
+            NegWordGadgetContainer<Fr>,
+            [WORD_LOW_MAX, neg_low_max],
+            true
+        );
+    }
+
+    #[test]
+    fn test_neg_0_neq_wordmax() {
+        try_test!(
+            NegWordGadgetContainer<Fr>,
+            [Word::from(0), Word::MAX],
+            false
+        );
+    }
+
+    #[test]
+    fn test_neg_1_neq_1() {
+        try_test!(
+            NegWordGadgetContainer<Fr>,
+            [Word::from(1), Word::from(1)],
+            false
+        );
+    }
+}
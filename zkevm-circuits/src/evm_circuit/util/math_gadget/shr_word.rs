@@ -0,0 +1,402 @@
+use crate::{
+    evm_circuit::{
+        param::N_BYTES_U64,
+        table::{FixedTableTag, Lookup},
+        util::{self, constraint_builder::ConstraintBuilder, from_bytes, sum, CachedRegion, Cell},
+    },
+    util::Expr,
+};
+use array_init::array_init;
+use eth_types::{Field, ToLittleEndian, Word};
+use halo2_proofs::{
+    circuit::Value,
+    plonk::{Error, Expression},
+};
+
+use super::{BitRangeCheckGadget, IsEqualGadget, IsZeroGadget, LtGadget};
+
+/// Construction of the right shift `b = a >> shift` of 256-bit words, where the
+/// bits shifted in from the top are all `is_neg`, so that it's the logical
+/// shift of SHR with `is_neg == 0` and the arithmetic shift of SAR with
+/// `is_neg` being the sign of `a`. A shift of 256 or more fills the whole word.
+///
+/// `shift[0]` is decomposed as `64 * shf_div64 + shf_mod64`. Each 64-bit limb
+/// of `a` is split at bit `shf_mod64`, and the limbs of `b` are merged from
+/// the high part of a limb of `a` and the low part of the next one,
+/// `shf_div64` limbs above.
+#[derive(Clone, Debug)]
+pub(crate) struct ShrWordGadget<F> {
+    // Each of the four `a64s` limbs is split into two parts (`a64s_lo` and `a64s_hi`) at position
+    // `shf_mod64`, `a64s_lo` is the lower `shf_mod64` bits.
+    a64s_lo: [Cell<F>; 4],
+    // `a64s_hi` is the higher `64 - shf_mod64` bits.
+    a64s_hi: [Cell<F>; 4],
+    // `shift[0] / 64`
+    shf_div64: Cell<F>,
+    // `shift[0] % 64`
+    shf_mod64: Cell<F>,
+    // 1 << shf_mod64
+    p_lo: Cell<F>,
+    // 1 << (64 - shf_mod64)
+    p_hi: Cell<F>,
+    // is_neg * (u64::MAX + 1 - p_hi)
+    p_top: Cell<F>,
+    // Verify `shf_mod64 < 64`.
+    shf_mod64_lt_64: BitRangeCheckGadget<F, 6>,
+    // Identify if `shift` is less than 256 or not.
+    shf_lt256: IsZeroGadget<F>,
+    // shf_div64 == 0
+    shf_lo_div64_eq0: IsZeroGadget<F>,
+    // shf_div64 == 1
+    shf_lo_div64_eq1: IsEqualGadget<F>,
+    // shf_div64 == 2
+    shf_lo_div64_eq2: IsEqualGadget<F>,
+    // shf_div64 == 3
+    shf_lo_div64_eq3: IsEqualGadget<F>,
+    // Verify `a64s_lo[idx]` should be less than `p_lo` when idx in `(0, 1, 2, 3)`.
+    a64s_lo_lt_p_lo: [LtGadget<F, 16>; 4],
+    // Verify `a64s_hi[idx]` should be less than `p_hi` when idx in `(0, 1, 2, 3)`.
+    a64s_hi_lt_p_hi: [LtGadget<F, 16>; 4],
+}
+
+impl<F: Field> ShrWordGadget<F> {
+    pub(crate) fn construct(
+        cb: &mut ConstraintBuilder<F>,
+        a: &util::Word<F>,
+        shift: &util::Word<F>,
+        b: &util::Word<F>,
+        is_neg: Expression<F>,
+    ) -> Self {
+        let a64s_lo = array_init(|_| cb.query_cell());
+        let a64s_hi = array_init(|_| cb.query_cell());
+        let shf_div64 = cb.query_cell();
+        let shf_mod64 = cb.query_cell();
+        let p_lo = cb.query_cell();
+        let p_hi = cb.query_cell();
+        let p_top = cb.query_cell();
+        let shf_lt256 = IsZeroGadget::construct(cb, sum::expr(&shift.cells[1..32]));
+
+        for idx in 0..4 {
+            cb.require_equal(
+                "a64s[idx] == a64s_lo[idx] + a64s_hi[idx] * p_lo",
+                from_bytes::expr(&a.cells[N_BYTES_U64 * idx..N_BYTES_U64 * (idx + 1)]),
+                a64s_lo[idx].expr() + a64s_hi[idx].expr() * p_lo.expr(),
+            );
+        }
+
+        // Constrain `a64s_lo[idx] < p_lo`.
+        let a64s_lo_lt_p_lo = array_init(|idx| {
+            let lt = LtGadget::construct(cb, a64s_lo[idx].expr(), p_lo.expr());
+            cb.require_zero("a64s_lo[idx] < p_lo", 1.expr() - lt.expr());
+            lt
+        });
+
+        // Constrain `a64s_hi[idx] < p_hi`.
+        let a64s_hi_lt_p_hi = array_init(|idx| {
+            let lt = LtGadget::construct(cb, a64s_hi[idx].expr(), p_hi.expr());
+            cb.require_zero("a64s_hi[idx] < p_hi", 1.expr() - lt.expr());
+            lt
+        });
+
+        // Merge contraints
+        let shf_lo_div64_eq0 = IsZeroGadget::construct(cb, shf_div64.expr());
+        let shf_lo_div64_eq1 = IsEqualGadget::construct(cb, shf_div64.expr(), 1.expr());
+        let shf_lo_div64_eq2 = IsEqualGadget::construct(cb, shf_div64.expr(), 2.expr());
+        let shf_lo_div64_eq3 = IsEqualGadget::construct(cb, shf_div64.expr(), 3.expr());
+        let shf_div64_eq0 = shf_lt256.expr() * shf_lo_div64_eq0.expr();
+        let shf_div64_eq1 = shf_lt256.expr() * shf_lo_div64_eq1.expr();
+        let shf_div64_eq2 = shf_lt256.expr() * shf_lo_div64_eq2.expr();
+        let shf_div64_eq3 = shf_lt256.expr() * shf_lo_div64_eq3.expr();
+
+        cb.require_equal(
+            "Constrain merged b64s[0] value",
+            from_bytes::expr(&b.cells[0..N_BYTES_U64]),
+            (a64s_hi[0].expr() + a64s_lo[1].expr() * p_hi.expr()) * shf_div64_eq0.expr()
+                + (a64s_hi[1].expr() + a64s_lo[2].expr() * p_hi.expr()) * shf_div64_eq1.expr()
+                + (a64s_hi[2].expr() + a64s_lo[3].expr() * p_hi.expr()) * shf_div64_eq2.expr()
+                + (a64s_hi[3].expr() + p_top.expr()) * shf_div64_eq3.expr()
+                + is_neg.expr()
+                    * u64::MAX.expr()
+                    * (1.expr()
+                        - shf_div64_eq0.expr()
+                        - shf_div64_eq1.expr()
+                        - shf_div64_eq2.expr()
+                        - shf_div64_eq3.expr()),
+        );
+        cb.require_equal(
+            "Constrain merged b64s[1] value",
+            from_bytes::expr(&b.cells[N_BYTES_U64..N_BYTES_U64 * 2]),
+            (a64s_hi[1].expr() + a64s_lo[2].expr() * p_hi.expr()) * shf_div64_eq0.expr()
+                + (a64s_hi[2].expr() + a64s_lo[3].expr() * p_hi.expr()) * shf_div64_eq1.expr()
+                + (a64s_hi[3].expr() + p_top.expr()) * shf_div64_eq2.expr()
+                + is_neg.expr()
+                    * u64::MAX.expr()
+                    * (1.expr()
+                        - shf_div64_eq0.expr()
+                        - shf_div64_eq1.expr()
+                        - shf_div64_eq2.expr()),
+        );
+        cb.require_equal(
+            "Constrain merged b64s[2] value",
+            from_bytes::expr(&b.cells[N_BYTES_U64 * 2..N_BYTES_U64 * 3]),
+            (a64s_hi[2].expr() + a64s_lo[3].expr() * p_hi.expr()) * shf_div64_eq0.expr()
+                + (a64s_hi[3].expr() + p_top.expr()) * shf_div64_eq1.expr()
+                + is_neg.expr()
+                    * u64::MAX.expr()
+                    * (1.expr() - shf_div64_eq0.expr() - shf_div64_eq1.expr()),
+        );
+        cb.require_equal(
+            "Constrain merged b64s[3] value",
+            from_bytes::expr(&b.cells[N_BYTES_U64 * 3..]),
+            (a64s_hi[3].expr() + p_top.expr()) * shf_div64_eq0.expr()
+                + is_neg.expr() * u64::MAX.expr() * (1.expr() - shf_div64_eq0.expr()),
+        );
+
+        // Shift constraint
+        let shf_mod64_lt_64 = BitRangeCheckGadget::construct(cb, shf_mod64.expr());
+        cb.require_equal(
+            "shift[0] == shf_mod64 + shf_div64 * 64",
+            shift.cells[0].expr(),
+            shf_mod64.expr() + shf_div64.expr() * 64.expr(),
+        );
+
+        // `p_top` constraint
+        cb.require_equal(
+            "p_top == is_neg * (MAX_U64 + 1 - p_hi)",
+            p_top.expr(),
+            is_neg * (u64::MAX.expr() + 1.expr() - p_hi.expr()),
+        );
+
+        // Constrain `p_lo == pow(2, shf_mod64)`.
+        cb.add_lookup(
+            "Pow2 lookup for p_lo == pow(2, shf_mod64)",
+            Lookup::Fixed {
+                tag: FixedTableTag::Pow2.expr(),
+                values: [shf_mod64.expr(), p_lo.expr(), 0.expr()],
+            },
+        );
+
+        // Constrain `p_hi == pow(2, 64 - shf_mod64)`.
+        cb.add_lookup(
+            "Pow2 lookup for p_hi == pow(2, 64 - shf_mod64)",
+            Lookup::Fixed {
+                tag: FixedTableTag::Pow2.expr(),
+                values: [64.expr() - shf_mod64.expr(), p_hi.expr(), 0.expr()],
+            },
+        );
+
+        Self {
+            a64s_lo,
+            a64s_hi,
+            shf_div64,
+            shf_mod64,
+            p_lo,
+            p_hi,
+            p_top,
+            shf_mod64_lt_64,
+            shf_lt256,
+            shf_lo_div64_eq0,
+            shf_lo_div64_eq1,
+            shf_lo_div64_eq2,
+            shf_lo_div64_eq3,
+            a64s_lo_lt_p_lo,
+            a64s_hi_lt_p_hi,
+        }
+    }
+
+    pub(crate) fn assign(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        a: Word,
+        shift: Word,
+        is_neg: bool,
+    ) -> Result<(), Error> {
+        let shf0 = u128::from(shift.to_le_bytes()[0]);
+        let shf_div64 = shf0 / 64;
+        let shf_mod64 = shf0 % 64;
+        let p_lo = 1 << shf_mod64;
+        let p_hi = 1 << (64 - shf_mod64);
+        let p_top = if is_neg {
+            u128::from(u64::MAX) + 1 - p_hi
+        } else {
+            0
+        };
+        let shf_lt256 = shift
+            .to_le_bytes()
+            .iter()
+            .fold(0, |acc, val| acc + u128::from(*val))
+            - shf0;
+        let a64s = a.0;
+        let mut a64s_lo = [0; 4];
+        let mut a64s_hi = [0; 4];
+        for idx in 0..4 {
+            a64s_hi[idx] = u128::from(a64s[idx]) / p_lo;
+            a64s_lo[idx] = u128::from(a64s[idx]) % p_lo;
+        }
+        self.a64s_lo
+            .iter()
+            .zip(a64s_lo.into_iter())
+            .map(|(c, v)| c.assign(region, offset, Value::known(F::from_u128(v))))
+            .collect::<Result<Vec<_>, _>>()?;
+        self.a64s_hi
+            .iter()
+            .zip(a64s_hi.into_iter())
+            .map(|(c, v)| c.assign(region, offset, Value::known(F::from_u128(v))))
+            .collect::<Result<Vec<_>, _>>()?;
+        self.shf_div64
+            .assign(region, offset, Value::known(F::from_u128(shf_div64)))?;
+        self.shf_mod64
+            .assign(region, offset, Value::known(F::from_u128(shf_mod64)))?;
+        self.p_lo
+            .assign(region, offset, Value::known(F::from_u128(p_lo)))?;
+        self.p_hi
+            .assign(region, offset, Value::known(F::from_u128(p_hi)))?;
+        self.p_top
+            .assign(region, offset, Value::known(F::from_u128(p_top)))?;
+        self.shf_mod64_lt_64
+            .assign(region, offset, F::from_u128(shf_mod64))?;
+        self.shf_lt256
+            .assign(region, offset, F::from_u128(shf_lt256))?;
+        self.shf_lo_div64_eq0
+            .assign(region, offset, F::from_u128(shf_div64))?;
+        self.shf_lo_div64_eq1
+            .assign(region, offset, F::from_u128(shf_div64), F::from(1))?;
+        self.shf_lo_div64_eq2
+            .assign(region, offset, F::from_u128(shf_div64), F::from(2))?;
+        self.shf_lo_div64_eq3
+            .assign(region, offset, F::from_u128(shf_div64), F::from(3))?;
+        self.a64s_lo_lt_p_lo
+            .iter()
+            .zip(a64s_lo.into_iter())
+            .map(|(l, v)| l.assign(region, offset, F::from_u128(v), F::from_u128(p_lo)))
+            .collect::<Result<Vec<_>, _>>()?;
+        self.a64s_hi_lt_p_hi
+            .iter()
+            .zip(a64s_hi.into_iter())
+            .map(|(l, v)| l.assign(region, offset, F::from_u128(v), F::from_u128(p_hi)))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_util::*;
+    use super::*;
+    use eth_types::Word;
+    use halo2_proofs::halo2curves::bn256::Fr;
+    use halo2_proofs::plonk::Error;
+
+    #[derive(Clone)]
+    /// ShrWordGadgetContainer: require(b == a >> shift), filled with is_neg
+    struct ShrWordGadgetContainer<F, const IS_NEG: bool> {
+        shrword_gadget: ShrWordGadget<F>,
+        a: util::Word<F>,
+        shift: util::Word<F>,
+        b: util::Word<F>,
+    }
+
+    impl<F: Field, const IS_NEG: bool> MathGadgetContainer<F> for ShrWordGadgetContainer<F, IS_NEG> {
+        fn configure_gadget_container(cb: &mut ConstraintBuilder<F>) -> Self {
+            let a = cb.query_word_rlc();
+            let shift = cb.query_word_rlc();
+            let b = cb.query_word_rlc();
+            let shrword_gadget = ShrWordGadget::<F>::construct(cb, &a, &shift, &b, IS_NEG.expr());
+            ShrWordGadgetContainer {
+                shrword_gadget,
+                a,
+                shift,
+                b,
+            }
+        }
+
+        fn assign_gadget_container(
+            &self,
+            witnesses: &[Word],
+            region: &mut CachedRegion<'_, '_, F>,
+        ) -> Result<(), Error> {
+            let offset = 0;
+            let [a, shift, b] = [0, 1, 2].map(|idx| witnesses[idx]);
+            self.a.assign(region, offset, Some(a.to_le_bytes()))?;
+            self.shift
+                .assign(region, offset, Some(shift.to_le_bytes()))?;
+            self.b.assign(region, offset, Some(b.to_le_bytes()))?;
+            self.shrword_gadget
+                .assign(region, offset, a, shift, IS_NEG)?;
+
+            Ok(())
+        }
+    }
+
+    /// `a >> shift` filling with `fill` bits, as expected by SHR and SAR.
+    fn shr(a: Word, shift: u64, fill: bool) -> Word {
+        if shift >= 256 {
+            return if fill { Word::MAX } else { Word::zero() };
+        }
+        let shifted = a >> shift;
+        if fill && shift > 0 {
+            shifted | (Word::MAX << (256 - shift))
+        } else {
+            shifted
+        }
+    }
+
+    #[test]
+    fn test_shr_word_logical() {
+        let a = Word::from_big_endian(&(1..33).collect::<Vec<_>>());
+        for shift in [0, 7, 8, 9, 63, 64, 65, 255] {
+            try_test!(
+                ShrWordGadgetContainer<Fr, false>,
+                [a, Word::from(shift), shr(a, shift, false)],
+                true,
+            );
+        }
+    }
+
+    #[test]
+    fn test_shr_word_arithmetic() {
+        let a = Word::MAX - Word::from(0x1234);
+        for shift in [0, 7, 8, 9, 63, 64, 65, 255] {
+            try_test!(
+                ShrWordGadgetContainer<Fr, true>,
+                [a, Word::from(shift), shr(a, shift, true)],
+                true,
+            );
+        }
+    }
+
+    #[test]
+    fn test_shr_word_overflow() {
+        let a = Word::MAX;
+        for shift in [Word::from(256), Word::from(256 + 8), Word::MAX] {
+            try_test!(
+                ShrWordGadgetContainer<Fr, false>,
+                [a, shift, Word::zero()],
+                true,
+            );
+            try_test!(
+                ShrWordGadgetContainer<Fr, true>,
+                [a, shift, Word::MAX],
+                true,
+            );
+        }
+    }
+
+    #[test]
+    fn test_shr_word_unexpected() {
+        let a = Word::MAX;
+        // The logical shift doesn't fill the top bits.
+        try_test!(
+            ShrWordGadgetContainer<Fr, false>,
+            [a, Word::from(8), Word::MAX],
+            false,
+        );
+        try_test!(
+            ShrWordGadgetContainer<Fr, true>,
+            [a, Word::from(8), Word::MAX >> 8],
+            false,
+        );
+    }
+}
@@ -0,0 +1,305 @@
+use crate::{
+    evm_circuit::util::{constraint_builder::ConstraintBuilder, math_gadget::*, sum, CachedRegion},
+    util::Expr,
+};
+use eth_types::{Field, ToLittleEndian, Word};
+use halo2_proofs::plonk::{Error, Expression};
+
+/// Constrains the signs of the results of the signed division
+/// `dividend = quotient * divisor + remainder`, with all four words given as
+/// `AbsWordGadget`:
+/// - `sign(quotient) == sign(dividend) ^ sign(divisor)` (result of `SDIV`),
+/// - `sign(remainder) == sign(dividend)` (result of `SMOD`).
+///
+/// A zero result has no sign, so the matching constraint is skipped when the
+/// quotient or the remainder is zero, as well as both when the divisor is zero.
+/// The quotient constraint is also skipped for `dividend = -2^255`: since
+/// `-2^255 / -1 = 2^255` can't be expressed as a signed word, the quotient
+/// stays `-2^255`.
+#[derive(Clone, Debug)]
+pub(crate) struct SignedDivSignGadget<F> {
+    quotient_is_zero: IsZeroGadget<F>,
+    remainder_is_zero: IsZeroGadget<F>,
+    dividend_is_signed_overflow: LtGadget<F, 1>,
+}
+
+impl<F: Field> SignedDivSignGadget<F> {
+    pub(crate) fn construct(
+        cb: &mut ConstraintBuilder<F>,
+        quotient: &AbsWordGadget<F>,
+        divisor: &AbsWordGadget<F>,
+        remainder: &AbsWordGadget<F>,
+        dividend: &AbsWordGadget<F>,
+        divisor_is_zero: Expression<F>,
+    ) -> Self {
+        let quotient_is_zero = IsZeroGadget::construct(cb, sum::expr(&quotient.x().cells));
+        let remainder_is_zero = IsZeroGadget::construct(cb, sum::expr(&remainder.x().cells));
+
+        // The remainder keeps the sign of the dividend even when the quotient
+        // is zero, i.e. when `|dividend| < |divisor|`.
+        cb.condition(
+            (1.expr() - divisor_is_zero.expr()) * (1.expr() - remainder_is_zero.expr()),
+            |cb| {
+                cb.add_constraint(
+                    "sign(dividend) == sign(remainder) when divisor and remainder are non-zero",
+                    dividend.is_neg().expr() - remainder.is_neg().expr(),
+                )
+            },
+        );
+
+        let dividend_is_signed_overflow =
+            LtGadget::construct(cb, 127.expr(), dividend.x_abs().cells[31].expr());
+
+        cb.condition(
+            (1.expr() - quotient_is_zero.expr())
+                * (1.expr() - divisor_is_zero)
+                * (1.expr() - dividend_is_signed_overflow.expr()),
+            |cb| {
+                cb.add_constraint(
+                    "sign(dividend) == sign(divisor) ^ sign(quotient)",
+                    quotient.is_neg().expr() + divisor.is_neg().expr()
+                        - dividend.is_neg().expr()
+                        - 2.expr() * quotient.is_neg().expr() * divisor.is_neg().expr(),
+                )
+            },
+        );
+
+        Self {
+            quotient_is_zero,
+            remainder_is_zero,
+            dividend_is_signed_overflow,
+        }
+    }
+
+    pub(crate) fn assign(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        quotient: Word,
+        remainder: Word,
+        dividend_abs: Word,
+    ) -> Result<(), Error> {
+        let byte_sum = |x: Word| (0..32).fold(0, |acc, idx| acc + x.byte(idx) as u64);
+        self.quotient_is_zero
+            .assign(region, offset, F::from(byte_sum(quotient)))?;
+        self.remainder_is_zero
+            .assign(region, offset, F::from(byte_sum(remainder)))?;
+        self.dividend_is_signed_overflow.assign(
+            region,
+            offset,
+            127.into(),
+            u64::from(dividend_abs.to_le_bytes()[31]).into(),
+        )?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_util::*;
+    use super::*;
+    use eth_types::Word;
+    use halo2_proofs::halo2curves::bn256::Fr;
+    use halo2_proofs::plonk::Error;
+
+    fn neg(x: Word) -> Word {
+        (!x).overflowing_add(Word::one()).0
+    }
+
+    fn abs(x: Word) -> Word {
+        if x.bit(255) {
+            neg(x)
+        } else {
+            x
+        }
+    }
+
+    #[derive(Clone)]
+    /// SignedDivSignTestContainer: require(sign(quotient) == sign(dividend) ^
+    /// sign(divisor) && sign(remainder) == sign(dividend))
+    struct SignedDivSignTestContainer<F> {
+        signed_div_sign_gadget: SignedDivSignGadget<F>,
+        quotient: AbsWordGadget<F>,
+        divisor: AbsWordGadget<F>,
+        remainder: AbsWordGadget<F>,
+        dividend: AbsWordGadget<F>,
+        divisor_is_zero: IsZeroGadget<F>,
+    }
+
+    impl<F: Field> MathGadgetContainer<F> for SignedDivSignTestContainer<F> {
+        fn configure_gadget_container(cb: &mut ConstraintBuilder<F>) -> Self {
+            let quotient = AbsWordGadget::construct(cb);
+            let divisor = AbsWordGadget::construct(cb);
+            let remainder = AbsWordGadget::construct(cb);
+            let dividend = AbsWordGadget::construct(cb);
+            let divisor_is_zero = IsZeroGadget::construct(cb, sum::expr(&divisor.x().cells));
+            let signed_div_sign_gadget = SignedDivSignGadget::construct(
+                cb,
+                &quotient,
+                &divisor,
+                &remainder,
+                &dividend,
+                divisor_is_zero.expr(),
+            );
+            SignedDivSignTestContainer {
+                signed_div_sign_gadget,
+                quotient,
+                divisor,
+                remainder,
+                dividend,
+                divisor_is_zero,
+            }
+        }
+
+        fn assign_gadget_container(
+            &self,
+            witnesses: &[Word],
+            region: &mut CachedRegion<'_, '_, F>,
+        ) -> Result<(), Error> {
+            let offset = 0;
+            let [quotient, divisor, remainder, dividend] = [0, 1, 2, 3].map(|idx| witnesses[idx]);
+            self.quotient
+                .assign(region, offset, quotient, abs(quotient))?;
+            self.divisor.assign(region, offset, divisor, abs(divisor))?;
+            self.remainder
+                .assign(region, offset, remainder, abs(remainder))?;
+            self.dividend
+                .assign(region, offset, dividend, abs(dividend))?;
+            let divisor_sum = (0..32).fold(0, |acc, idx| acc + divisor.byte(idx) as u64);
+            self.divisor_is_zero
+                .assign(region, offset, F::from(divisor_sum))?;
+            self.signed_div_sign_gadget.assign(
+                region,
+                offset,
+                quotient,
+                remainder,
+                abs(dividend),
+            )?;
+
+            Ok(())
+        }
+    }
+
+    /// Witnesses `[quotient, divisor, remainder, dividend]` of
+    /// `dividend = quotient * divisor + remainder` from signed `i64` values.
+    fn witnesses(quotient: i64, divisor: i64, remainder: i64, dividend: i64) -> Vec<Word> {
+        [quotient, divisor, remainder, dividend]
+            .iter()
+            .map(|x| {
+                let x_abs = Word::from(x.unsigned_abs());
+                if *x < 0 {
+                    neg(x_abs)
+                } else {
+                    x_abs
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_signed_div_sign_quadrants() {
+        // 7 / 2 = 3, 7 % 2 = 1
+        try_test!(SignedDivSignTestContainer<Fr>, witnesses(3, 2, 1, 7), true);
+        // -7 / 2 = -3, -7 % 2 = -1
+        try_test!(
+            SignedDivSignTestContainer<Fr>,
+            witnesses(-3, 2, -1, -7),
+            true
+        );
+        // 7 / -2 = -3, 7 % -2 = 1
+        try_test!(
+            SignedDivSignTestContainer<Fr>,
+            witnesses(-3, -2, 1, 7),
+            true
+        );
+        // -7 / -2 = 3, -7 % -2 = -1
+        try_test!(
+            SignedDivSignTestContainer<Fr>,
+            witnesses(3, -2, -1, -7),
+            true
+        );
+    }
+
+    #[test]
+    fn test_signed_div_sign_wrong_quotient_sign() {
+        try_test!(
+            SignedDivSignTestContainer<Fr>,
+            witnesses(3, 2, -1, -7),
+            false
+        );
+        try_test!(
+            SignedDivSignTestContainer<Fr>,
+            witnesses(3, -2, 1, 7),
+            false
+        );
+        try_test!(
+            SignedDivSignTestContainer<Fr>,
+            witnesses(-3, -2, -1, -7),
+            false
+        );
+    }
+
+    #[test]
+    fn test_signed_div_sign_wrong_remainder_sign() {
+        try_test!(
+            SignedDivSignTestContainer<Fr>,
+            witnesses(-3, 2, 1, -7),
+            false
+        );
+        try_test!(
+            SignedDivSignTestContainer<Fr>,
+            witnesses(-3, -2, -1, 7),
+            false
+        );
+    }
+
+    #[test]
+    fn test_signed_div_sign_zero_results() {
+        // 1 / 2 = 0, -1 / 2 = 0 and -1 / -2 = 0, the remainder still follows
+        // the dividend.
+        try_test!(SignedDivSignTestContainer<Fr>, witnesses(0, 2, 1, 1), true);
+        try_test!(
+            SignedDivSignTestContainer<Fr>,
+            witnesses(0, 2, -1, -1),
+            true
+        );
+        try_test!(
+            SignedDivSignTestContainer<Fr>,
+            witnesses(0, -2, -1, -1),
+            true
+        );
+        try_test!(
+            SignedDivSignTestContainer<Fr>,
+            witnesses(0, -2, 1, -1),
+            false
+        );
+        // -6 % 3 = 0
+        try_test!(
+            SignedDivSignTestContainer<Fr>,
+            witnesses(-2, 3, 0, -6),
+            true
+        );
+        try_test!(
+            SignedDivSignTestContainer<Fr>,
+            witnesses(2, 3, 0, -6),
+            false
+        );
+    }
+
+    #[test]
+    fn test_signed_div_sign_min_int() {
+        // -2^255 / -1 = -2^255 overflows, so the quotient keeps the sign of the
+        // dividend.
+        try_test!(
+            SignedDivSignTestContainer<Fr>,
+            vec![WORD_SIGNED_MIN, Word::MAX, Word::zero(), WORD_SIGNED_MIN],
+            true,
+        );
+        // -2^255 / 1 = -2^255
+        try_test!(
+            SignedDivSignTestContainer<Fr>,
+            vec![WORD_SIGNED_MIN, Word::one(), Word::zero(), WORD_SIGNED_MIN],
+            true,
+        );
+    }
+}
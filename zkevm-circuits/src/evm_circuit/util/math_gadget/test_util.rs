@@ -157,6 +157,23 @@ impl<F: Field, G: MathGadgetContainer<F>> Circuit<F> for UnitTestMathGadgetBaseC
                     });
                 }
             }
+            if let CellType::LookupByte = column.cell_type {
+                meta.lookup_any("Byte lookup", |meta| {
+                    let table_expressions = fixed_table.table_exprs(meta);
+                    vec![(
+                        rlc::expr(
+                            &[
+                                FixedTableTag::Range256.expr(),
+                                column.expr(),
+                                0.expr(),
+                                0.expr(),
+                            ],
+                            challenges_exprs.lookup_input(),
+                        ),
+                        rlc::expr(&table_expressions, challenges_exprs.lookup_input()),
+                    )]
+                });
+            }
         }
 
         (
@@ -208,8 +225,9 @@ impl<F: Field, G: MathGadgetContainer<F>> Circuit<F> for UnitTestMathGadgetBaseC
             },
         )?;
 
-        // assign fixed range tables only as they are the only tables referred by a
-        // specfic math gadget -- ConstantDivisionGadget.
+        // assign fixed range tables and the pow2 table only as they are the only
+        // tables referred by specific math gadgets -- ConstantDivisionGadget and
+        // ShrWordGadget -- and by the byte cells.
         layouter.assign_region(
             || "fixed table",
             |mut region| {
@@ -226,6 +244,7 @@ impl<F: Field, G: MathGadgetContainer<F>> Circuit<F> for UnitTestMathGadgetBaseC
                                         | FixedTableTag::Range256
                                         | FixedTableTag::Range512
                                         | FixedTableTag::Range1024
+                                        | FixedTableTag::Pow2
                                 )
                             })
                             .flat_map(|tag| tag.build()),
@@ -535,3 +535,247 @@ impl<F: Field, const MAX_BYTES: usize, const ADDR_SIZE_IN_BYTES: usize>
         sum::expr(&self.selectors)
     }
 }
+
+/// Splits a copy of `copy_length` bytes from `src_offset` in a source of
+/// `src_length` bytes into the bytes read from the source and the zero padding
+/// written past its end, i.e. `min(max(src_length - src_offset, 0),
+/// copy_length)` bytes come from the source and the rest are zeros.
+#[derive(Clone, Debug)]
+pub(crate) struct CopyPaddingGadget<F, const N_BYTES: usize> {
+    /// `min(src_offset, src_length)`, so that reading from past the end of the
+    /// source leaves no source bytes.
+    src_start: MinMaxGadget<F, N_BYTES>,
+    /// `min(src_length - src_start, copy_length)`
+    n_src_bytes: MinMaxGadget<F, N_BYTES>,
+    copy_length: Expression<F>,
+}
+
+impl<F: Field, const N_BYTES: usize> CopyPaddingGadget<F, N_BYTES> {
+    pub(crate) fn construct(
+        cb: &mut ConstraintBuilder<F>,
+        src_offset: Expression<F>,
+        src_length: Expression<F>,
+        copy_length: Expression<F>,
+    ) -> Self {
+        let src_start = MinMaxGadget::construct(cb, src_offset, src_length.clone());
+        let n_src_bytes =
+            MinMaxGadget::construct(cb, src_length - src_start.min(), copy_length.clone());
+
+        Self {
+            src_start,
+            n_src_bytes,
+            copy_length,
+        }
+    }
+
+    /// The number of bytes copied from the source.
+    pub(crate) fn n_src_bytes(&self) -> Expression<F> {
+        self.n_src_bytes.min()
+    }
+
+    /// The number of zero bytes padded after the source bytes.
+    pub(crate) fn n_padding_bytes(&self) -> Expression<F> {
+        self.copy_length.clone() - self.n_src_bytes()
+    }
+
+    /// Returns the number of source bytes and padding bytes.
+    pub(crate) fn assign(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        src_offset: u64,
+        src_length: u64,
+        copy_length: u64,
+    ) -> Result<(u64, u64), Error> {
+        let src_start = src_offset.min(src_length);
+        self.src_start
+            .assign(region, offset, F::from(src_offset), F::from(src_length))?;
+        let n_src_bytes = (src_length - src_start).min(copy_length);
+        self.n_src_bytes.assign(
+            region,
+            offset,
+            F::from(src_length - src_start),
+            F::from(copy_length),
+        )?;
+
+        Ok((n_src_bytes, copy_length - n_src_bytes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::evm_circuit::util::math_gadget::test_util::*;
+    use eth_types::Word;
+    use halo2_proofs::halo2curves::bn256::Fr;
+
+    #[derive(Clone)]
+    /// MemoryWordSizeTestContainer: require(word_size == ceil(len / 32))
+    struct MemoryWordSizeTestContainer<F> {
+        memory_word_size_gadget: MemoryWordSizeGadget<F>,
+        len: Cell<F>,
+        word_size: Cell<F>,
+    }
+
+    impl<F: Field> MathGadgetContainer<F> for MemoryWordSizeTestContainer<F> {
+        fn configure_gadget_container(cb: &mut ConstraintBuilder<F>) -> Self {
+            let len = cb.query_cell();
+            let word_size = cb.query_cell();
+            let memory_word_size_gadget = MemoryWordSizeGadget::construct(cb, len.expr());
+            cb.require_equal(
+                "word_size == ceil(len / 32)",
+                memory_word_size_gadget.expr(),
+                word_size.expr(),
+            );
+            MemoryWordSizeTestContainer {
+                memory_word_size_gadget,
+                len,
+                word_size,
+            }
+        }
+
+        fn assign_gadget_container(
+            &self,
+            witnesses: &[Word],
+            region: &mut CachedRegion<'_, '_, F>,
+        ) -> Result<(), Error> {
+            let [len, word_size] = [0, 1].map(|idx| witnesses[idx].as_u64());
+            let offset = 0;
+
+            self.len
+                .assign(region, offset, Value::known(F::from(len)))?;
+            self.word_size
+                .assign(region, offset, Value::known(F::from(word_size)))?;
+            self.memory_word_size_gadget.assign(region, offset, len)?;
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_memory_word_size() {
+        for (len, word_size) in [(0u64, 0u64), (31, 1), (32, 1), (33, 2)] {
+            try_test!(
+                MemoryWordSizeTestContainer<Fr>,
+                vec![Word::from(len), Word::from(word_size)],
+                true,
+            );
+        }
+    }
+
+    #[test]
+    fn test_memory_word_size_wrong_rounding() {
+        for (len, word_size) in [(0u64, 1u64), (31, 0), (32, 2), (33, 1)] {
+            try_test!(
+                MemoryWordSizeTestContainer<Fr>,
+                vec![Word::from(len), Word::from(word_size)],
+                false,
+            );
+        }
+    }
+
+    #[derive(Clone)]
+    /// CopyPaddingTestContainer: require(n_src_bytes, n_padding_bytes) to match
+    /// a copy of copy_length bytes from src_offset in src_length bytes
+    struct CopyPaddingTestContainer<F> {
+        copy_padding_gadget: CopyPaddingGadget<F, 8>,
+        src_offset: Cell<F>,
+        src_length: Cell<F>,
+        copy_length: Cell<F>,
+        n_src_bytes: Cell<F>,
+        n_padding_bytes: Cell<F>,
+    }
+
+    impl<F: Field> MathGadgetContainer<F> for CopyPaddingTestContainer<F> {
+        fn configure_gadget_container(cb: &mut ConstraintBuilder<F>) -> Self {
+            let [src_offset, src_length, copy_length, n_src_bytes, n_padding_bytes] =
+                [(); 5].map(|_| cb.query_cell());
+            let copy_padding_gadget = CopyPaddingGadget::construct(
+                cb,
+                src_offset.expr(),
+                src_length.expr(),
+                copy_length.expr(),
+            );
+            cb.require_equal(
+                "n_src_bytes is correct",
+                copy_padding_gadget.n_src_bytes(),
+                n_src_bytes.expr(),
+            );
+            cb.require_equal(
+                "n_padding_bytes is correct",
+                copy_padding_gadget.n_padding_bytes(),
+                n_padding_bytes.expr(),
+            );
+            CopyPaddingTestContainer {
+                copy_padding_gadget,
+                src_offset,
+                src_length,
+                copy_length,
+                n_src_bytes,
+                n_padding_bytes,
+            }
+        }
+
+        fn assign_gadget_container(
+            &self,
+            witnesses: &[Word],
+            region: &mut CachedRegion<'_, '_, F>,
+        ) -> Result<(), Error> {
+            let [src_offset, src_length, copy_length, n_src_bytes, n_padding_bytes] =
+                [0, 1, 2, 3, 4].map(|idx| witnesses[idx].as_u64());
+            let offset = 0;
+
+            for (cell, value) in [
+                (&self.src_offset, src_offset),
+                (&self.src_length, src_length),
+                (&self.copy_length, copy_length),
+                (&self.n_src_bytes, n_src_bytes),
+                (&self.n_padding_bytes, n_padding_bytes),
+            ] {
+                cell.assign(region, offset, Value::known(F::from(value)))?;
+            }
+            self.copy_padding_gadget
+                .assign(region, offset, src_offset, src_length, copy_length)?;
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_copy_padding_in_bounds() {
+        try_test!(
+            CopyPaddingTestContainer<Fr>,
+            [2u64, 10, 4, 4, 0].map(Word::from),
+            true,
+        );
+    }
+
+    #[test]
+    fn test_copy_padding_partially_padded() {
+        try_test!(
+            CopyPaddingTestContainer<Fr>,
+            [8u64, 10, 4, 2, 2].map(Word::from),
+            true,
+        );
+    }
+
+    #[test]
+    fn test_copy_padding_fully_padded() {
+        for src_offset in [10u64, 12, u64::MAX >> 8] {
+            try_test!(
+                CopyPaddingTestContainer<Fr>,
+                [src_offset, 10, 4, 0, 4].map(Word::from),
+                true,
+            );
+        }
+    }
+
+    #[test]
+    fn test_copy_padding_unexpected() {
+        try_test!(
+            CopyPaddingTestContainer<Fr>,
+            [8u64, 10, 4, 4, 0].map(Word::from),
+            false,
+        );
+    }
+}
@@ -0,0 +1,149 @@
+use crate::{
+    evm_circuit::{
+        param::{N_BYTES_ACCOUNT_ADDRESS, N_BYTES_GAS},
+        util::{
+            constraint_builder::ConstraintBuilder,
+            math_gadget::{IsZeroGadget, LtGadget},
+            memory_gadget::MemoryWordSizeGadget,
+            not, select, sum, CachedRegion, Cell,
+        },
+    },
+    util::Expr,
+};
+use eth_types::{
+    evm_types::{GasCost, PRECOMPILE_COUNT, PRECOMPILE_IDENTITY},
+    Field, ToLittleEndian, U256,
+};
+use halo2_proofs::plonk::{Error, Expression};
+
+/// Gadget for the calls to precompiled contracts, deployed at the addresses
+/// `0x01..=0x09`. They run without entering a new call frame, so this gadget
+/// constrains the result of the call and the gas it consumes: all the gas
+/// given to the precompiled when it fails, or its cost otherwise.
+///
+/// Only the identity precompiled contract is supported so far. Calls to ecAdd,
+/// ecMul, ecPairing and blake2f can't be proven until there are circuits for
+/// the BN254 curve arithmetic, its pairing and the blake2b compression
+/// function.
+#[derive(Clone, Debug)]
+pub(crate) struct PrecompileGadget<F> {
+    is_callee_address_high_zero: IsZeroGadget<F>,
+    is_callee_address_zero: IsZeroGadget<F>,
+    is_callee_address_lt_precompile_end: LtGadget<F, 1>,
+    is_identity: IsZeroGadget<F>,
+    identity_input_word_size: MemoryWordSizeGadget<F>,
+    is_oog: LtGadget<F, N_BYTES_GAS>,
+    is_precompile: Expression<F>,
+    is_success: Expression<F>,
+    gas_used: Expression<F>,
+}
+
+impl<F: Field> PrecompileGadget<F> {
+    /// `callee_address_bytes` are the little-endian bytes of the callee
+    /// address, `input_length` is the length of the input given to the
+    /// precompiled and `gas` the gas given to it.
+    pub(crate) fn construct(
+        cb: &mut ConstraintBuilder<F>,
+        callee_address_bytes: &[Cell<F>],
+        input_length: Expression<F>,
+        gas: Expression<F>,
+    ) -> Self {
+        let is_callee_address_high_zero =
+            IsZeroGadget::construct(cb, sum::expr(&callee_address_bytes[1..]));
+        let is_callee_address_zero = IsZeroGadget::construct(cb, callee_address_bytes[0].expr());
+        let is_callee_address_lt_precompile_end = LtGadget::construct(
+            cb,
+            callee_address_bytes[0].expr(),
+            (PRECOMPILE_COUNT + 1).expr(),
+        );
+        let is_precompile = is_callee_address_high_zero.expr()
+            * not::expr(is_callee_address_zero.expr())
+            * is_callee_address_lt_precompile_end.expr();
+
+        let is_identity = IsZeroGadget::construct(
+            cb,
+            callee_address_bytes[0].expr() - PRECOMPILE_IDENTITY.expr(),
+        );
+        cb.condition(is_precompile.clone(), |cb| {
+            cb.require_equal(
+                "Only the identity precompiled is supported",
+                is_identity.expr(),
+                1.expr(),
+            );
+        });
+
+        let identity_input_word_size = MemoryWordSizeGadget::construct(cb, input_length);
+
+        let gas_cost = GasCost::PRECOMPILE_IDENTITY_BASE.expr()
+            + identity_input_word_size.expr() * GasCost::PRECOMPILE_IDENTITY_PER_WORD.expr();
+        let is_oog = LtGadget::construct(cb, gas.clone(), gas_cost.clone());
+        let is_success = not::expr(is_oog.expr());
+        let gas_used = is_precompile.clone() * select::expr(is_success.clone(), gas_cost, gas);
+
+        Self {
+            is_callee_address_high_zero,
+            is_callee_address_zero,
+            is_callee_address_lt_precompile_end,
+            is_identity,
+            identity_input_word_size,
+            is_oog,
+            is_precompile,
+            is_success,
+            gas_used,
+        }
+    }
+
+    pub(crate) fn is_precompile(&self) -> Expression<F> {
+        self.is_precompile.clone()
+    }
+
+    /// Expected result of the call when it's made to a precompiled.
+    pub(crate) fn is_success(&self) -> Expression<F> {
+        self.is_success.clone()
+    }
+
+    /// Gas consumed by the precompiled, zero when the callee isn't one.
+    pub(crate) fn gas_used(&self) -> Expression<F> {
+        self.gas_used.clone()
+    }
+
+    pub(crate) fn assign(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        callee_address: U256,
+        cd_length: u64,
+        gas: u64,
+    ) -> Result<(), Error> {
+        let callee_address_bytes = callee_address.to_le_bytes();
+        let callee_address_low = F::from(callee_address_bytes[0] as u64);
+        self.is_callee_address_high_zero.assign(
+            region,
+            offset,
+            sum::value(&callee_address_bytes[1..N_BYTES_ACCOUNT_ADDRESS]),
+        )?;
+        self.is_callee_address_zero
+            .assign(region, offset, callee_address_low)?;
+        self.is_callee_address_lt_precompile_end.assign(
+            region,
+            offset,
+            callee_address_low,
+            F::from(PRECOMPILE_COUNT + 1),
+        )?;
+        self.is_identity.assign(
+            region,
+            offset,
+            callee_address_low - F::from(PRECOMPILE_IDENTITY),
+        )?;
+
+        let identity_input_word_size = self
+            .identity_input_word_size
+            .assign(region, offset, cd_length)?;
+        let gas_cost = GasCost::PRECOMPILE_IDENTITY_BASE.as_u64()
+            + identity_input_word_size * GasCost::PRECOMPILE_IDENTITY_PER_WORD.as_u64();
+        self.is_oog
+            .assign(region, offset, F::from(gas), F::from(gas_cost))?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,199 @@
+use crate::{
+    evm_circuit::util::{constraint_builder::ConstraintBuilder, CachedRegion, Cell},
+    util::Expr,
+};
+use eth_types::{Field, Word};
+use halo2_proofs::{
+    circuit::Value,
+    plonk::{Error, Expression},
+};
+
+/// A 256-bit word compressed into a single phase 2 cell as the RLC of its
+/// little-endian bytes with the `evm_word` challenge, the same encoding the
+/// rw table uses for stack values. Use it when a gadget only moves or
+/// compares a word; to access the bytes use `Word` instead.
+#[derive(Clone, Debug)]
+pub(crate) struct WordRlc<F> {
+    cell: Cell<F>,
+}
+
+impl<F: Field> WordRlc<F> {
+    pub(crate) fn construct(cb: &mut ConstraintBuilder<F>) -> Self {
+        Self {
+            cell: cb.query_cell_phase2(),
+        }
+    }
+
+    pub(crate) fn assign(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        word: Word,
+    ) -> Result<Value<F>, Error> {
+        let rlc = region.word_rlc(word);
+        self.cell.assign(region, offset, rlc)?;
+        Ok(rlc)
+    }
+}
+
+impl<F: Field> Expr<F> for WordRlc<F> {
+    fn expr(&self) -> Expression<F> {
+        self.cell.expr()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::math_gadget::test_util::*;
+    use super::*;
+    use crate::{evm_circuit::util, witness::Rw};
+    use eth_types::{ToLittleEndian, Word};
+    use halo2_proofs::halo2curves::bn256::Fr;
+    use halo2_proofs::plonk::Error;
+
+    #[derive(Clone)]
+    /// WordRlcTestContainer: require(rlc(a) == rw_table_value(b) &&
+    /// rlc(a) == rlc(bytes(a)))
+    struct WordRlcTestContainer<F> {
+        word_rlc: WordRlc<F>,
+        word: util::Word<F>,
+        table_value: Cell<F>,
+    }
+
+    impl<F: Field> MathGadgetContainer<F> for WordRlcTestContainer<F> {
+        fn configure_gadget_container(cb: &mut ConstraintBuilder<F>) -> Self {
+            let word_rlc = WordRlc::construct(cb);
+            let word = cb.query_word_rlc();
+            let table_value = cb.query_cell_phase2();
+            cb.require_equal(
+                "word RLC matches the RLC of its bytes",
+                word_rlc.expr(),
+                word.expr(),
+            );
+            cb.require_equal(
+                "word RLC matches the rw table encoding",
+                word_rlc.expr(),
+                table_value.expr(),
+            );
+            WordRlcTestContainer {
+                word_rlc,
+                word,
+                table_value,
+            }
+        }
+
+        fn assign_gadget_container(
+            &self,
+            witnesses: &[Word],
+            region: &mut CachedRegion<'_, '_, F>,
+        ) -> Result<(), Error> {
+            let a = witnesses[0];
+            let b = witnesses[1];
+            let offset = 0;
+
+            self.word_rlc.assign(region, offset, a)?;
+            self.word.assign(region, offset, Some(a.to_le_bytes()))?;
+            let table_value = Rw::Stack {
+                rw_counter: 1,
+                is_write: true,
+                call_id: 1,
+                stack_pointer: 1023,
+                value: b,
+            }
+            .table_assignment(region.challenges().evm_word())
+            .value;
+            self.table_value.assign(region, offset, table_value)?;
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_word_rlc_matches_table() {
+        for word in [
+            Word::zero(),
+            Word::one(),
+            WORD_LOW_MAX,
+            WORD_HIGH_MAX,
+            Word::MAX,
+        ] {
+            try_test!(WordRlcTestContainer<Fr>, vec![word, word], true);
+        }
+    }
+
+    #[test]
+    fn test_word_rlc_unexpected() {
+        try_test!(
+            WordRlcTestContainer<Fr>,
+            vec![Word::one(), Word::zero()],
+            false
+        );
+        try_test!(
+            WordRlcTestContainer<Fr>,
+            vec![WORD_LOW_MAX, WORD_HIGH_MAX],
+            false
+        );
+    }
+
+    #[derive(Clone)]
+    /// ForgedWordTestContainer: require(rlc(bytes(a)) == table_value) with the
+    /// most significant byte of `a` forged
+    struct ForgedWordTestContainer<F> {
+        word: util::Word<F>,
+        table_value: Cell<F>,
+    }
+
+    impl<F: Field> MathGadgetContainer<F> for ForgedWordTestContainer<F> {
+        fn configure_gadget_container(cb: &mut ConstraintBuilder<F>) -> Self {
+            let word = cb.query_word_rlc();
+            let table_value = cb.query_cell_phase2();
+            cb.require_equal(
+                "pushed word matches the rw table value",
+                word.expr(),
+                table_value.expr(),
+            );
+            ForgedWordTestContainer { word, table_value }
+        }
+
+        fn assign_gadget_container(
+            &self,
+            witnesses: &[Word],
+            region: &mut CachedRegion<'_, '_, F>,
+        ) -> Result<(), Error> {
+            let a = witnesses[0];
+            let msb = F::from(witnesses[1].as_u64());
+            let offset = 0;
+
+            // `a` is below 2^248, so overwriting its most significant byte with
+            // `msb` adds `msb ⋅ r^31` to its RLC.
+            self.word.assign(region, offset, Some(a.to_le_bytes()))?;
+            self.word.cells[31].assign(region, offset, Value::known(msb))?;
+            let table_value = region
+                .word_rlc(a)
+                .zip(region.challenges().evm_word())
+                .map(|(rlc, r)| rlc + msb * r.pow(&[31, 0, 0, 0]));
+            self.table_value.assign(region, offset, table_value)?;
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_word_rlc_byte_range() {
+        try_test!(
+            ForgedWordTestContainer<Fr>,
+            vec![WORD_LOW_MAX, Word::from(0xff)],
+            true
+        );
+    }
+
+    // A word with a byte of 256 would encode a value over 256 bits.
+    #[test]
+    fn test_word_rlc_forged_over_range() {
+        try_test!(
+            ForgedWordTestContainer<Fr>,
+            vec![WORD_LOW_MAX, Word::from(0x100)],
+            false
+        );
+    }
+}
@@ -72,13 +72,25 @@ use bus_mapping::circuit_input_builder::{CircuitInputBuilder, CircuitsParams};
 use bus_mapping::mock::BlockData;
 use eth_types::geth_types::GethData;
 use eth_types::Field;
+#[cfg(any(feature = "rlp-block", test))]
+use eth_types::{
+    evm_types::HardFork,
+    geth_types::{self, decode_rlp_block, Account, BlockConstants},
+    Word,
+};
+#[cfg(any(feature = "rlp-block", test))]
+use external_tracer::{trace, LoggerConfig, TraceConfig};
 use halo2_proofs::{
     circuit::{Layouter, SimpleFloorPlanner, Value},
+    halo2curves::bn256::Bn256,
     plonk::{Circuit, ConstraintSystem, Error, Expression},
+    poly::{commitment::Params, kzg::commitment::ParamsKZG},
 };
 
 use std::array;
 
+pub mod assigned_witness;
+
 /// Configuration of the Super Circuit
 #[derive(Clone)]
 pub struct SuperCircuitConfig<F: Field> {
@@ -257,6 +269,26 @@ impl<F: Field, const MAX_TXS: usize, const MAX_CALLDATA: usize, const MOCK_RANDO
             TxCircuitConfig::<F>::get_num_rows_required(block.circuits_params.max_txs);
         num_rows_evm_circuit.max(num_rows_tx_circuit)
     }
+
+    /// Return the minimum SRS degree required to verify a given block
+    pub fn min_k(block: &Block<F>) -> u32 {
+        const NUM_BLINDING_ROWS: usize = 64;
+        let (_, rows_needed) = Self::min_num_rows_block(block);
+        log2_ceil(NUM_BLINDING_ROWS + rows_needed)
+    }
+
+    /// Panic with a descriptive message if the SRS degree of `params` is
+    /// below the minimum degree `min_k` of the block, as returned by
+    /// [`Self::build`], instead of failing later in the key generation with
+    /// `NotEnoughRowsAvailable`.
+    pub fn assert_params_k(params: &ParamsKZG<Bn256>, min_k: u32) {
+        assert!(
+            params.k() >= min_k,
+            "params with k = {} are too small for the SuperCircuit, the block requires k >= {}",
+            params.k(),
+            min_k
+        );
+    }
 }
 
 // Eventhough the SuperCircuit is not a subcircuit we implement the SubCircuit
@@ -425,6 +457,59 @@ impl<F: Field, const MAX_TXS: usize, const MAX_CALLDATA: usize, const MOCK_RANDO
         Ok((ret.0, ret.1, ret.2, builder))
     }
 
+    /// From a raw RLP encoded block, trace it on the state made of `accounts`
+    /// and generate a SuperCircuit instance like [`Self::build`], without the
+    /// need of a geth node.
+    ///
+    /// `accounts` are the state witnesses of the block: every account it
+    /// accesses, with its code and the storage slots it reads. The block is
+    /// traced with the EVM rules of `hardfork`, which must be London for the
+    /// block to be converted to a witness.
+    ///
+    /// Tracing the block needs the external tracer, enabled by the
+    /// `rlp-block` feature.
+    #[cfg(any(feature = "rlp-block", test))]
+    #[allow(clippy::type_complexity)]
+    pub fn build_from_rlp_block(
+        block_rlp: &[u8],
+        chain_id: Word,
+        history_hashes: Vec<Word>,
+        accounts: Vec<Account>,
+        circuits_params: CircuitsParams,
+        hardfork: HardFork,
+    ) -> Result<(u32, Self, Vec<Vec<F>>, CircuitInputBuilder), bus_mapping::Error> {
+        let eth_block = decode_rlp_block(block_rlp)?;
+        let trace_config = TraceConfig {
+            chain_id,
+            history_hashes: history_hashes.clone(),
+            block_constants: BlockConstants::try_from_block(&eth_block, hardfork)?,
+            accounts: accounts
+                .iter()
+                .map(|account| (account.address, account.clone()))
+                .collect(),
+            transactions: eth_block
+                .transactions
+                .iter()
+                .map(geth_types::Transaction::from)
+                .collect(),
+            logger_config: LoggerConfig::default(),
+            hardfork,
+        };
+        let geth_traces = trace(&trace_config)?;
+
+        Self::build(
+            GethData {
+                chain_id,
+                history_hashes,
+                eth_block,
+                geth_traces,
+                accounts,
+                hardfork,
+            },
+            circuits_params,
+        )
+    }
+
     /// From CircuitInputBuilder, generate a SuperCircuit instance with all of
     /// the sub-circuits filled with their corresponding witnesses.
     ///
@@ -438,9 +523,7 @@ impl<F: Field, const MAX_TXS: usize, const MAX_CALLDATA: usize, const MOCK_RANDO
         assert_eq!(block.circuits_params.max_txs, MAX_TXS);
         assert_eq!(block.circuits_params.max_calldata, MAX_CALLDATA);
 
-        const NUM_BLINDING_ROWS: usize = 64;
-        let (_, rows_needed) = Self::min_num_rows_block(&block);
-        let k = log2_ceil(NUM_BLINDING_ROWS + rows_needed);
+        let k = Self::min_k(&block);
         log::debug!("super circuit uses k = {}", k);
 
         let circuit =
@@ -452,7 +535,7 @@ impl<F: Field, const MAX_TXS: usize, const MAX_CALLDATA: usize, const MOCK_RANDO
 }
 
 #[cfg(test)]
-mod super_circuit_tests {
+pub(crate) mod super_circuit_tests {
     use super::*;
     use ethers_signers::{LocalWallet, Signer};
     use halo2_proofs::dev::MockProver;
@@ -496,7 +579,108 @@ mod super_circuit_tests {
         }
     }
 
-    fn block_1tx() -> GethData {
+    #[test]
+    fn super_circuit_min_k() {
+        let circuits_params = CircuitsParams {
+            max_txs: 1,
+            max_calldata: 32,
+            max_rws: 256,
+            max_copy_rows: 256,
+            max_bytecode: 512,
+            keccak_padding: None,
+        };
+        let (k, _, _, builder) =
+            SuperCircuit::<Fr, 1, 32, 0x100>::build(block_1tx(), circuits_params).unwrap();
+        let block = block_convert::<Fr>(&builder.block, &builder.code_db).unwrap();
+        assert_eq!(SuperCircuit::<Fr, 1, 32, 0x100>::min_k(&block), k);
+        let params = ParamsKZG::<Bn256>::setup(k, ChaCha20Rng::seed_from_u64(2));
+        SuperCircuit::<Fr, 1, 32, 0x100>::assert_params_k(&params, k);
+    }
+
+    #[test]
+    #[should_panic(expected = "are too small for the SuperCircuit")]
+    fn super_circuit_undersized_params() {
+        let circuits_params = CircuitsParams {
+            max_txs: 1,
+            max_calldata: 32,
+            max_rws: 256,
+            max_copy_rows: 256,
+            max_bytecode: 512,
+            keccak_padding: None,
+        };
+        let (k, _, _, _) =
+            SuperCircuit::<Fr, 1, 32, 0x100>::build(block_1tx(), circuits_params).unwrap();
+        let params = ParamsKZG::<Bn256>::setup(k - 1, ChaCha20Rng::seed_from_u64(2));
+        SuperCircuit::<Fr, 1, 32, 0x100>::assert_params_k(&params, k);
+    }
+
+    /// RLP of block 1 holding one legacy transaction, signed for
+    /// `MOCK_CHAIN_ID` by `0x2c7536E3605D9C16a7a3D7b1898e529396a65c23`, which
+    /// calls the code `PUSH1(0x2a) PUSH1(0) SSTORE STOP` at `0xcafe`.
+    const BLOCK_1TX_RLP: &str = include_str!("testdata/block_1tx.rlp.hex");
+
+    #[test]
+    fn super_circuit_rlp_block() {
+        let block_rlp = hex::decode(BLOCK_1TX_RLP.trim()).unwrap();
+        let sender = address!("0x2c7536E3605D9C16a7a3D7b1898e529396a65c23");
+        let callee = address!("0x000000000000000000000000000000000000cafe");
+
+        let eth_block = decode_rlp_block(&block_rlp).unwrap();
+        assert_eq!(eth_block.number, Some(1.into()));
+        assert_eq!(eth_block.transactions.len(), 1);
+        assert_eq!(eth_block.transactions[0].from, sender);
+        assert_eq!(eth_block.transactions[0].to, Some(callee));
+
+        let accounts = vec![
+            Account {
+                address: sender,
+                balance: Word::from(1u64 << 20),
+                ..Default::default()
+            },
+            Account {
+                address: callee,
+                code: bytecode! {
+                    PUSH1(0x2a)
+                    PUSH1(0)
+                    SSTORE
+                    STOP
+                }
+                .into(),
+                ..Default::default()
+            },
+        ];
+        let circuits_params = CircuitsParams {
+            max_txs: 1,
+            max_calldata: 32,
+            max_rws: 256,
+            max_copy_rows: 256,
+            max_bytecode: 512,
+            keccak_padding: None,
+        };
+        let (k, circuit, instance, builder) =
+            SuperCircuit::<Fr, 1, 32, 0x100>::build_from_rlp_block(
+                &block_rlp,
+                *MOCK_CHAIN_ID,
+                Vec::new(),
+                accounts,
+                circuits_params,
+                HardFork::default(),
+            )
+            .unwrap();
+        assert_eq!(
+            builder.sdb.get_storage(&callee, &Word::zero()).1,
+            &Word::from(0x2a)
+        );
+
+        let prover = MockProver::run(k, &circuit, instance).unwrap();
+        let res = prover.verify_par();
+        if let Err(err) = res {
+            error!("Verification failures: {:#?}", err);
+            panic!("Failed verification");
+        }
+    }
+
+    pub(crate) fn block_1tx() -> GethData {
         let mut rng = ChaCha20Rng::seed_from_u64(2);
 
         let chain_id = (*MOCK_CHAIN_ID).as_u64();
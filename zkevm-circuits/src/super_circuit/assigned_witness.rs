@@ -0,0 +1,710 @@
+//! Export of the values assigned to the columns of the [`SuperCircuit`], so
+//! that the witness can be generated on one host and proven on another one
+//! without running the bus-mapping again.
+//!
+//! The SuperCircuit uses mock challenges derived from the block randomness,
+//! so its assigned values don't depend on the proving transcript and can be
+//! computed ahead of time, to be loaded by the prover as an
+//! [`AssignedWitnessCircuit`].
+
+use super::{SuperCircuit, SuperCircuitConfig};
+use crate::util::SubCircuit;
+use eth_types::Field;
+use halo2_proofs::{
+    circuit::{Cell, Layouter, SimpleFloorPlanner, Value},
+    plonk::{
+        Advice, Any, Assigned, Assignment, Challenge, Circuit, Column, ConstraintSystem, Error,
+        FirstPhase, Fixed, FloorPlanner, Instance, SecondPhase, Selector, ThirdPhase,
+    },
+};
+use std::{
+    collections::HashMap,
+    io::{self, Read, Write},
+};
+
+/// Type of a column of the circuit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum ColumnKind {
+    Advice,
+    Fixed,
+    Instance,
+}
+
+impl ColumnKind {
+    fn from_column(column: Column<Any>) -> Self {
+        match column.column_type() {
+            Any::Advice(_) => Self::Advice,
+            Any::Fixed => Self::Fixed,
+            Any::Instance => Self::Instance,
+        }
+    }
+}
+
+/// A cell identified by the type and index of its column and its row.
+type CellPosition = (ColumnKind, usize, usize);
+
+/// Values assigned to the columns of a [`SuperCircuit`], along with its
+/// instance and copy constraints.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AssignedWitness<F> {
+    k: u32,
+    instance: Vec<Vec<F>>,
+    // phase of every advice column
+    advice_phases: Vec<u8>,
+    advice: Vec<Vec<F>>,
+    fixed: Vec<Vec<F>>,
+    selectors: Vec<Vec<bool>>,
+    copies: Vec<(CellPosition, CellPosition)>,
+}
+
+impl<F: Field> AssignedWitness<F> {
+    /// Assigns the columns of `circuit` for the degree `k`.
+    pub fn new<const MAX_TXS: usize, const MAX_CALLDATA: usize, const MOCK_RANDOMNESS: u64>(
+        k: u32,
+        circuit: &SuperCircuit<F, MAX_TXS, MAX_CALLDATA, MOCK_RANDOMNESS>,
+    ) -> Result<Self, Error> {
+        let mut cs = ConstraintSystem::default();
+        let config = SuperCircuit::<F, MAX_TXS, MAX_CALLDATA, MOCK_RANDOMNESS>::configure(&mut cs);
+        let mut witness = Self {
+            k,
+            instance: circuit.instance(),
+            advice_phases: vec![0; cs.num_advice_columns()],
+            advice: vec![vec![]; cs.num_advice_columns()],
+            fixed: vec![vec![]; cs.num_fixed_columns()],
+            selectors: vec![vec![]; cs.num_selectors()],
+            copies: vec![],
+        };
+        let mut collection = WitnessCollection {
+            usable_rows: (1 << k) - (cs.blinding_factors() + 1),
+            witness: &mut witness,
+        };
+        SimpleFloorPlanner::synthesize(&mut collection, circuit, config, cs.constants().clone())?;
+
+        Ok(witness)
+    }
+
+    /// Degree of the circuit the witness was assigned for.
+    pub fn k(&self) -> u32 {
+        self.k
+    }
+
+    /// Public inputs of the circuit.
+    pub fn instance(&self) -> Vec<Vec<F>> {
+        self.instance.clone()
+    }
+
+    /// Writes the witness in a raw format, with the field elements in their
+    /// canonical representation and the lengths as little-endian `u64`.
+    pub fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        write_u64(writer, self.k as u64)?;
+        for columns in [&self.instance, &self.advice, &self.fixed] {
+            write_u64(writer, columns.len() as u64)?;
+            for values in columns {
+                write_u64(writer, values.len() as u64)?;
+                for value in values {
+                    writer.write_all(&value.to_repr())?;
+                }
+            }
+        }
+        writer.write_all(&self.advice_phases)?;
+        write_u64(writer, self.selectors.len() as u64)?;
+        for enabled in &self.selectors {
+            write_u64(writer, enabled.len() as u64)?;
+            writer.write_all(
+                &enabled
+                    .iter()
+                    .map(|enabled| *enabled as u8)
+                    .collect::<Vec<_>>(),
+            )?;
+        }
+        write_u64(writer, self.copies.len() as u64)?;
+        for (left, right) in &self.copies {
+            for (kind, index, row) in [left, right] {
+                writer.write_all(&[*kind as u8])?;
+                write_u64(writer, *index as u64)?;
+                write_u64(writer, *row as u64)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads a witness written by [`Self::write`]. Fails with
+    /// `UnexpectedEof` if the input is truncated, which includes the lengths
+    /// that exceed the remaining input, and with `InvalidData` if the input
+    /// doesn't hold a consistent witness, such as copies of unassigned cells.
+    pub fn read<R: Read>(reader: &mut R) -> io::Result<Self> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+        let mut input = Input(&bytes);
+
+        let k = input.read_u64()?;
+        if k > MAX_K as u64 {
+            return Err(invalid_data("degree too large"));
+        }
+        let k = k as u32;
+        let max_rows = 1usize << k;
+        let instance = input.read_columns(max_rows)?;
+        let advice = input.read_columns(max_rows)?;
+        let fixed = input.read_columns(max_rows)?;
+        let mut advice_phases = vec![0; advice.len()];
+        input.read_exact(&mut advice_phases)?;
+        if advice_phases.iter().any(|phase| *phase > 2) {
+            return Err(invalid_data("invalid advice phase"));
+        }
+        let selectors = (0..input.read_len(8)?)
+            .map(|_| {
+                let mut enabled = vec![0; input.read_len(1)?];
+                if enabled.len() > max_rows {
+                    return Err(invalid_data("selector longer than the circuit"));
+                }
+                input.read_exact(&mut enabled)?;
+                Ok(enabled.into_iter().map(|enabled| enabled != 0).collect())
+            })
+            .collect::<io::Result<_>>()?;
+        let copies: Vec<_> = (0..input.read_len(2 * CELL_POSITION_SIZE)?)
+            .map(|_| Ok((input.read_cell()?, input.read_cell()?)))
+            .collect::<io::Result<_>>()?;
+        if !input.0.is_empty() {
+            return Err(invalid_data("trailing bytes after the witness"));
+        }
+
+        // Every copied cell has been assigned, the instance ones are the
+        // public inputs given to the prover.
+        let is_assigned = |(kind, index, row): &CellPosition| match kind {
+            ColumnKind::Advice => advice
+                .get(*index)
+                .map_or(false, |values| *row < values.len()),
+            ColumnKind::Fixed => fixed
+                .get(*index)
+                .map_or(false, |values| *row < values.len()),
+            ColumnKind::Instance => *index < instance.len() && *row < max_rows,
+        };
+        if !copies
+            .iter()
+            .all(|(left, right)| is_assigned(left) && is_assigned(right))
+        {
+            return Err(invalid_data("copy of an unassigned cell"));
+        }
+
+        Ok(Self {
+            k,
+            instance,
+            advice_phases,
+            advice,
+            fixed,
+            selectors,
+            copies,
+        })
+    }
+}
+
+/// Largest degree of a witness read by [`AssignedWitness::read`].
+const MAX_K: u32 = 32;
+
+/// Size of a serialized [`CellPosition`]: the column type, index and row.
+const CELL_POSITION_SIZE: usize = 1 + 8 + 8;
+
+fn write_u64<W: Write>(writer: &mut W, value: u64) -> io::Result<()> {
+    writer.write_all(&value.to_le_bytes())
+}
+
+/// Remaining input of [`AssignedWitness::read`].
+struct Input<'a>(&'a [u8]);
+
+impl<'a> Input<'a> {
+    fn read_exact(&mut self, buf: &mut [u8]) -> io::Result<()> {
+        self.0.read_exact(buf)
+    }
+
+    fn read_u64(&mut self) -> io::Result<u64> {
+        let mut bytes = [0u8; 8];
+        self.read_exact(&mut bytes)?;
+        Ok(u64::from_le_bytes(bytes))
+    }
+
+    /// Reads the length of a list of items taking at least `item_size` bytes
+    /// each, which can't exceed the remaining input.
+    fn read_len(&mut self, item_size: usize) -> io::Result<usize> {
+        let len = self.read_u64()?;
+        if len > (self.0.len() / item_size) as u64 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "length exceeds the remaining input",
+            ));
+        }
+        Ok(len as usize)
+    }
+
+    /// Reads columns of at most `max_rows` field elements.
+    fn read_columns<F: Field>(&mut self, max_rows: usize) -> io::Result<Vec<Vec<F>>> {
+        (0..self.read_len(8)?)
+            .map(|_| {
+                let len = self.read_len(32)?;
+                if len > max_rows {
+                    return Err(invalid_data("column longer than the circuit"));
+                }
+                (0..len)
+                    .map(|_| {
+                        let mut repr = [0u8; 32];
+                        self.read_exact(&mut repr)?;
+                        Option::from(F::from_repr(repr))
+                            .ok_or_else(|| invalid_data("invalid field element"))
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn read_cell(&mut self) -> io::Result<CellPosition> {
+        let mut kind = [0u8];
+        self.read_exact(&mut kind)?;
+        let kind = match kind[0] {
+            0 => ColumnKind::Advice,
+            1 => ColumnKind::Fixed,
+            2 => ColumnKind::Instance,
+            _ => return Err(invalid_data("invalid column type")),
+        };
+        Ok((kind, self.read_u64()? as usize, self.read_u64()? as usize))
+    }
+}
+
+fn invalid_data(error: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error)
+}
+
+fn evaluate<F: Field>(value: Value<Assigned<F>>) -> F {
+    let mut evaluated = F::zero();
+    value.map(|value| evaluated = value.evaluate());
+    evaluated
+}
+
+fn assign<F: Field>(
+    columns: &mut [Vec<F>],
+    index: usize,
+    row: usize,
+    value: F,
+    usable_rows: usize,
+    k: u32,
+) -> Result<(), Error> {
+    if row >= usable_rows {
+        return Err(Error::NotEnoughRowsAvailable { current_k: k });
+    }
+    let values = &mut columns[index];
+    if values.len() <= row {
+        values.resize(row + 1, F::zero());
+    }
+    values[row] = value;
+    Ok(())
+}
+
+/// Records the assignments made by the floor planner to an
+/// [`AssignedWitness`].
+struct WitnessCollection<'a, F> {
+    usable_rows: usize,
+    witness: &'a mut AssignedWitness<F>,
+}
+
+impl<'a, F: Field> Assignment<F> for WitnessCollection<'a, F> {
+    fn enter_region<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+    }
+
+    fn exit_region(&mut self) {}
+
+    fn enable_selector<A, AR>(&mut self, _: A, selector: &Selector, row: usize) -> Result<(), Error>
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        if row >= self.usable_rows {
+            return Err(Error::NotEnoughRowsAvailable {
+                current_k: self.witness.k,
+            });
+        }
+        let enabled = &mut self.witness.selectors[selector.index()];
+        if enabled.len() <= row {
+            enabled.resize(row + 1, false);
+        }
+        enabled[row] = true;
+        Ok(())
+    }
+
+    fn annotate_column<A, AR>(&mut self, _: A, _: Column<Any>)
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+    }
+
+    fn query_instance(&self, column: Column<Instance>, row: usize) -> Result<Value<F>, Error> {
+        Ok(Value::known(
+            self.witness.instance[column.index()]
+                .get(row)
+                .copied()
+                .unwrap_or_else(F::zero),
+        ))
+    }
+
+    fn assign_advice<V, VR, A, AR>(
+        &mut self,
+        _: A,
+        column: Column<Advice>,
+        row: usize,
+        to: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.witness.advice_phases[column.index()] = column.column_type().phase();
+        assign(
+            &mut self.witness.advice,
+            column.index(),
+            row,
+            evaluate(to().into_field()),
+            self.usable_rows,
+            self.witness.k,
+        )
+    }
+
+    fn assign_fixed<V, VR, A, AR>(
+        &mut self,
+        _: A,
+        column: Column<Fixed>,
+        row: usize,
+        to: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        assign(
+            &mut self.witness.fixed,
+            column.index(),
+            row,
+            evaluate(to().into_field()),
+            self.usable_rows,
+            self.witness.k,
+        )
+    }
+
+    fn copy(
+        &mut self,
+        left_column: Column<Any>,
+        left_row: usize,
+        right_column: Column<Any>,
+        right_row: usize,
+    ) -> Result<(), Error> {
+        self.witness.copies.push((
+            (
+                ColumnKind::from_column(left_column),
+                left_column.index(),
+                left_row,
+            ),
+            (
+                ColumnKind::from_column(right_column),
+                right_column.index(),
+                right_row,
+            ),
+        ));
+        Ok(())
+    }
+
+    fn fill_from_row(
+        &mut self,
+        column: Column<Fixed>,
+        from_row: usize,
+        to: Value<Assigned<F>>,
+    ) -> Result<(), Error> {
+        let value = evaluate(to);
+        for row in from_row..self.usable_rows {
+            assign(
+                &mut self.witness.fixed,
+                column.index(),
+                row,
+                value,
+                self.usable_rows,
+                self.witness.k,
+            )?;
+        }
+        Ok(())
+    }
+
+    fn get_challenge(&self, _: Challenge) -> Value<F> {
+        // The SuperCircuit only uses mock challenges.
+        Value::unknown()
+    }
+
+    fn push_namespace<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+    }
+
+    fn pop_namespace(&mut self, _: Option<String>) {}
+}
+
+/// Circuit with the configuration of the [`SuperCircuit`], whose columns are
+/// assigned from an [`AssignedWitness`].
+#[derive(Clone, Debug, Default)]
+pub struct AssignedWitnessCircuit<
+    F: Field,
+    const MAX_TXS: usize,
+    const MAX_CALLDATA: usize,
+    const MOCK_RANDOMNESS: u64,
+> {
+    witness: AssignedWitness<F>,
+}
+
+impl<F: Field, const MAX_TXS: usize, const MAX_CALLDATA: usize, const MOCK_RANDOMNESS: u64>
+    AssignedWitnessCircuit<F, MAX_TXS, MAX_CALLDATA, MOCK_RANDOMNESS>
+{
+    /// Creates the circuit assigned with `witness`.
+    pub fn new(witness: AssignedWitness<F>) -> Self {
+        Self { witness }
+    }
+}
+
+impl<F: Field, const MAX_TXS: usize, const MAX_CALLDATA: usize, const MOCK_RANDOMNESS: u64>
+    Circuit<F> for AssignedWitnessCircuit<F, MAX_TXS, MAX_CALLDATA, MOCK_RANDOMNESS>
+{
+    type Config = SuperCircuitConfig<F>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        SuperCircuit::<F, MAX_TXS, MAX_CALLDATA, MOCK_RANDOMNESS>::configure(meta)
+    }
+
+    fn synthesize(&self, _: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        let witness = &self.witness;
+        // The witness must have been assigned for the same configuration.
+        let mut config_cs = ConstraintSystem::<F>::default();
+        SuperCircuit::<F, MAX_TXS, MAX_CALLDATA, MOCK_RANDOMNESS>::configure(&mut config_cs);
+        if witness.advice.len() != config_cs.num_advice_columns()
+            || witness.fixed.len() != config_cs.num_fixed_columns()
+            || witness.instance.len() != config_cs.num_instance_columns()
+            || witness.selectors.len() != config_cs.num_selectors()
+        {
+            return Err(Error::Synthesis);
+        }
+
+        // Columns are only identified by their type and index, so the ones of
+        // the configuration are recreated in the same order.
+        let mut cs = ConstraintSystem::<F>::default();
+        let advice_columns = witness
+            .advice_phases
+            .iter()
+            .map(|phase| match phase {
+                0 => cs.advice_column_in(FirstPhase),
+                1 => cs.advice_column_in(SecondPhase),
+                _ => cs.advice_column_in(ThirdPhase),
+            })
+            .collect::<Vec<_>>();
+        let fixed_columns = witness
+            .fixed
+            .iter()
+            .map(|_| cs.fixed_column())
+            .collect::<Vec<_>>();
+        let instance_columns = witness
+            .instance
+            .iter()
+            .map(|_| cs.instance_column())
+            .collect::<Vec<_>>();
+        let selectors = witness
+            .selectors
+            .iter()
+            .map(|_| cs.selector())
+            .collect::<Vec<_>>();
+
+        let cells = layouter.assign_region(
+            || "assigned witness",
+            |mut region| {
+                let mut cells = HashMap::new();
+                for (index, (column, values)) in
+                    fixed_columns.iter().zip(&witness.fixed).enumerate()
+                {
+                    for (row, value) in values.iter().enumerate() {
+                        let cell = region.assign_fixed(
+                            || "fixed",
+                            *column,
+                            row,
+                            || Value::known(*value),
+                        )?;
+                        cells.insert((ColumnKind::Fixed, index, row), cell.cell());
+                    }
+                }
+                for (index, (column, values)) in
+                    advice_columns.iter().zip(&witness.advice).enumerate()
+                {
+                    for (row, value) in values.iter().enumerate() {
+                        let cell = region.assign_advice(
+                            || "advice",
+                            *column,
+                            row,
+                            || Value::known(*value),
+                        )?;
+                        cells.insert((ColumnKind::Advice, index, row), cell.cell());
+                    }
+                }
+                for (selector, enabled) in selectors.iter().zip(&witness.selectors) {
+                    for (row, _) in enabled.iter().enumerate().filter(|(_, enabled)| **enabled) {
+                        selector.enable(&mut region, row)?;
+                    }
+                }
+                for (left, right) in &witness.copies {
+                    if left.0 == ColumnKind::Instance || right.0 == ColumnKind::Instance {
+                        continue;
+                    }
+                    match (cells.get(left), cells.get(right)) {
+                        (Some(left), Some(right)) => region.constrain_equal(*left, *right)?,
+                        _ => return Err(Error::Synthesis),
+                    }
+                }
+                Ok(cells)
+            },
+        )?;
+
+        let cell = |position: &CellPosition| -> Result<Cell, Error> {
+            cells.get(position).copied().ok_or(Error::Synthesis)
+        };
+        for (left, right) in &witness.copies {
+            match (left, right) {
+                ((ColumnKind::Instance, index, row), other)
+                | (other, (ColumnKind::Instance, index, row)) => {
+                    layouter.constrain_instance(cell(other)?, instance_columns[*index], *row)?;
+                }
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::super_circuit::super_circuit_tests::block_1tx;
+    use bus_mapping::circuit_input_builder::CircuitsParams;
+    use halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr};
+
+    /// Witness of a single advice cell copied to the public input.
+    fn small_witness() -> AssignedWitness<Fr> {
+        AssignedWitness {
+            k: 4,
+            instance: vec![vec![Fr::one()]],
+            advice_phases: vec![0],
+            advice: vec![vec![Fr::zero(), Fr::one()]],
+            fixed: vec![vec![Fr::one()]],
+            selectors: vec![vec![false, true]],
+            copies: vec![((ColumnKind::Advice, 0, 1), (ColumnKind::Instance, 0, 0))],
+        }
+    }
+
+    fn to_bytes(witness: &AssignedWitness<Fr>) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        witness.write(&mut bytes).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn assigned_witness_read_written() {
+        let witness = small_witness();
+        let bytes = to_bytes(&witness);
+        assert_eq!(
+            AssignedWitness::<Fr>::read(&mut bytes.as_slice()).unwrap(),
+            witness
+        );
+    }
+
+    #[test]
+    fn assigned_witness_read_truncated() {
+        let bytes = to_bytes(&small_witness());
+        for len in 0..bytes.len() {
+            assert_eq!(
+                AssignedWitness::<Fr>::read(&mut &bytes[..len])
+                    .unwrap_err()
+                    .kind(),
+                io::ErrorKind::UnexpectedEof,
+                "{} bytes",
+                len
+            );
+        }
+    }
+
+    #[test]
+    fn assigned_witness_read_length_beyond_input() {
+        // A degree, then a number of instance columns that the input can't
+        // hold, which is rejected before allocating them.
+        let mut bytes = Vec::new();
+        write_u64(&mut bytes, 4).unwrap();
+        write_u64(&mut bytes, u64::MAX).unwrap();
+        assert_eq!(
+            AssignedWitness::<Fr>::read(&mut bytes.as_slice())
+                .unwrap_err()
+                .kind(),
+            io::ErrorKind::UnexpectedEof
+        );
+    }
+
+    #[test]
+    fn assigned_witness_read_mismatched() {
+        let mut trailing = to_bytes(&small_witness());
+        trailing.push(0);
+
+        let mut unassigned_copy = small_witness();
+        unassigned_copy.copies[0].0 = (ColumnKind::Advice, 0, 2);
+
+        let mut long_column = small_witness();
+        long_column.fixed[0] = vec![Fr::one(); 1 << 5];
+
+        for bytes in [trailing, to_bytes(&unassigned_copy), to_bytes(&long_column)] {
+            assert_eq!(
+                AssignedWitness::<Fr>::read(&mut bytes.as_slice())
+                    .unwrap_err()
+                    .kind(),
+                io::ErrorKind::InvalidData
+            );
+        }
+    }
+
+    #[ignore]
+    #[test]
+    fn serial_test_assigned_witness_round_trip() {
+        let circuits_params = CircuitsParams {
+            max_txs: 1,
+            max_calldata: 32,
+            max_rws: 256,
+            max_copy_rows: 256,
+            max_bytecode: 512,
+            keccak_padding: None,
+        };
+        let (k, circuit, instance, _) =
+            SuperCircuit::<Fr, 1, 32, 0x100>::build(block_1tx(), circuits_params).unwrap();
+        let witness = AssignedWitness::new(k, &circuit).unwrap();
+
+        let mut bytes = Vec::new();
+        witness.write(&mut bytes).unwrap();
+        let loaded = AssignedWitness::<Fr>::read(&mut bytes.as_slice()).unwrap();
+        assert_eq!(loaded, witness);
+        assert_eq!(loaded.k(), k);
+        assert_eq!(loaded.instance(), instance);
+
+        // The loaded witness satisfies the constraints of the SuperCircuit.
+        let circuit = AssignedWitnessCircuit::<Fr, 1, 32, 0x100>::new(loaded);
+        let prover = MockProver::run(k, &circuit, instance).unwrap();
+        prover.assert_satisfied_par();
+    }
+}
@@ -666,6 +666,17 @@ pub enum BlockContextFieldTag {
 }
 impl_expr!(BlockContextFieldTag);
 
+impl BlockContextFieldTag {
+    /// Returns true if the field is stored in the block table as the RLC of
+    /// its 32 bytes instead of as a scalar.
+    pub fn is_word(&self) -> bool {
+        matches!(
+            self,
+            Self::Difficulty | Self::BaseFee | Self::BlockHash | Self::ChainId
+        )
+    }
+}
+
 /// Table with Block header fields
 #[derive(Clone, Debug)]
 pub struct BlockTable {
@@ -1,17 +1,24 @@
 //! Testing utilities
 
 use crate::{
-    evm_circuit::EvmCircuit,
+    evm_circuit::{step::ExecutionState, EvmCircuit},
     state_circuit::StateCircuit,
     util::SubCircuit,
-    witness::{Block, Rw},
+    witness::{block_convert, Block, Rw},
 };
-use bus_mapping::{circuit_input_builder::CircuitsParams, mock::BlockData};
-use eth_types::geth_types::GethData;
+use bus_mapping::{
+    circuit_input_builder::{CircuitInputBuilder, CircuitsParams},
+    mock::BlockData,
+};
+use eth_types::{evm_types::OpcodeId, geth_types::GethData, Bytecode, Word};
 
 use halo2_proofs::dev::MockProver;
 use halo2_proofs::halo2curves::bn256::Fr;
 use mock::TestContext;
+use std::{
+    fmt::Write,
+    panic::{catch_unwind, resume_unwind, AssertUnwindSafe},
+};
 
 #[cfg(test)]
 #[ctor::ctor]
@@ -78,6 +85,8 @@ pub struct CircuitTestBuilder<const NACC: usize, const NTX: usize> {
     evm_checks: Box<dyn Fn(MockProver<Fr>, &Vec<usize>, &Vec<usize>)>,
     state_checks: Box<dyn Fn(MockProver<Fr>, &Vec<usize>, &Vec<usize>)>,
     block_modifiers: Vec<Box<dyn Fn(&mut Block<Fr>)>>,
+    allowed_opcodes: Option<Vec<OpcodeId>>,
+    max_evm_rows: Option<usize>,
 }
 
 impl<const NACC: usize, const NTX: usize> CircuitTestBuilder<NACC, NTX> {
@@ -100,6 +109,8 @@ impl<const NACC: usize, const NTX: usize> CircuitTestBuilder<NACC, NTX> {
                 )
             }),
             block_modifiers: vec![],
+            allowed_opcodes: None,
+            max_evm_rows: None,
         }
     }
 
@@ -171,6 +182,23 @@ impl<const NACC: usize, const NTX: usize> CircuitTestBuilder<NACC, NTX> {
         self.block_modifiers.push(modifier);
         self
     }
+
+    /// Allows to restrict the opcodes the block is expected to execute, to
+    /// test a partial opcode support. The run panics before proving if any
+    /// step executes an opcode out of `opcodes`, instead of reporting
+    /// constraint failures of the missing gadget.
+    pub fn allowed_opcodes(mut self, opcodes: Vec<OpcodeId>) -> Self {
+        self.allowed_opcodes = Some(opcodes);
+        self
+    }
+
+    /// Allows to set a budget for the rows used by the EVM circuit steps, to
+    /// catch gadget changes which bloat the circuit. The run panics before
+    /// proving if the block needs more than `max_rows` rows.
+    pub fn max_evm_rows(mut self, max_rows: usize) -> Self {
+        self.max_evm_rows = Some(max_rows);
+        self
+    }
 }
 
 impl<const NACC: usize, const NTX: usize> CircuitTestBuilder<NACC, NTX> {
@@ -187,15 +215,7 @@ impl<const NACC: usize, const NTX: usize> CircuitTestBuilder<NACC, NTX> {
         let block: Block<Fr> = if self.block.is_some() {
             self.block.unwrap()
         } else if self.test_ctx.is_some() {
-            let block: GethData = self.test_ctx.unwrap().into();
-            let mut builder = BlockData::new_from_geth_data_with_params(block.clone(), params)
-                .new_circuit_input_builder();
-            builder
-                .handle_block(&block.eth_block, &block.geth_traces)
-                .unwrap();
-            // Build a witness block from trace result.
-            let mut block =
-                crate::witness::block_convert(&builder.block, &builder.code_db).unwrap();
+            let mut block = block_from_test_ctx_with_params(self.test_ctx.unwrap(), params);
 
             for modifier_fn in self.block_modifiers {
                 modifier_fn.as_ref()(&mut block);
@@ -205,6 +225,33 @@ impl<const NACC: usize, const NTX: usize> CircuitTestBuilder<NACC, NTX> {
             panic!("No attribute to build a block was passed to the CircuitTestBuilder")
         };
 
+        if let Some(allowed_opcodes) = self.allowed_opcodes.as_ref() {
+            for (tx_idx, tx) in block.txs.iter().enumerate() {
+                for step in tx.steps.iter() {
+                    if let Some(opcode) = step.opcode {
+                        assert!(
+                            allowed_opcodes.contains(&opcode),
+                            "tx {} executes opcode {:?} at pc {}, which is not in the allowed opcodes {:?}",
+                            tx_idx,
+                            opcode,
+                            step.program_counter,
+                            allowed_opcodes
+                        );
+                    }
+                }
+            }
+        }
+
+        if let Some(max_evm_rows) = self.max_evm_rows {
+            let num_rows = EvmCircuit::<Fr>::get_num_rows_required(&block);
+            assert!(
+                num_rows <= max_evm_rows,
+                "EVM circuit uses {} rows, which is over the budget of {} rows",
+                num_rows,
+                max_evm_rows
+            );
+        }
+
         // Run evm circuit test
         {
             let k = block.get_test_degree();
@@ -214,7 +261,14 @@ impl<const NACC: usize, const NTX: usize> CircuitTestBuilder<NACC, NTX> {
             let circuit = EvmCircuit::<Fr>::get_test_cicuit_from_block(block.clone());
             let prover = MockProver::<Fr>::run(k, &circuit, vec![]).unwrap();
 
-            self.evm_checks.as_ref()(prover, &active_gate_rows, &active_lookup_rows)
+            // Print the steps on failure, to correlate the failing rows with
+            // the opcodes they belong to.
+            if let Err(err) = catch_unwind(AssertUnwindSafe(|| {
+                self.evm_checks.as_ref()(prover, &active_gate_rows, &active_lookup_rows)
+            })) {
+                eprintln!("EVM circuit steps:\n{}", format_exec_steps(&block));
+                resume_unwind(err);
+            }
         }
 
         // Run state circuit test
@@ -238,3 +292,250 @@ impl<const NACC: usize, const NTX: usize> CircuitTestBuilder<NACC, NTX> {
         }
     }
 }
+
+/// Handles the block of the [`TestContext`] `ctx` with the given circuit
+/// `params`, returning the [`CircuitInputBuilder`] with its traces processed.
+pub fn builder_from_test_ctx<const NACC: usize, const NTX: usize>(
+    ctx: TestContext<NACC, NTX>,
+    params: CircuitsParams,
+) -> CircuitInputBuilder {
+    let block: GethData = ctx.into();
+    let mut builder = BlockData::new_from_geth_data_with_params(block.clone(), params)
+        .new_circuit_input_builder();
+    builder
+        .handle_block(&block.eth_block, &block.geth_traces)
+        .unwrap();
+    builder
+}
+
+/// Builds the witness block of the [`TestContext`] `ctx` with the default
+/// circuit params.
+pub fn block_from_test_ctx<const NACC: usize, const NTX: usize>(
+    ctx: TestContext<NACC, NTX>,
+) -> Block<Fr> {
+    block_from_test_ctx_with_params(ctx, CircuitsParams::default())
+}
+
+/// Builds the witness block of the [`TestContext`] `ctx` with the given
+/// circuit `params`.
+pub fn block_from_test_ctx_with_params<const NACC: usize, const NTX: usize>(
+    ctx: TestContext<NACC, NTX>,
+    params: CircuitsParams,
+) -> Block<Fr> {
+    let builder = builder_from_test_ctx(ctx, params);
+    block_convert(&builder.block, &builder.code_db).unwrap()
+}
+
+/// Runs `opcode` on a stack of `stack_len` items, checking it's executed as
+/// `execution_state` before running the block with the
+/// [`CircuitTestBuilder`].
+pub(crate) fn test_stack_depth(
+    opcode: OpcodeId,
+    stack_len: usize,
+    execution_state: ExecutionState,
+) {
+    let mut bytecode = Bytecode::default();
+    for _ in 0..stack_len {
+        bytecode.push(1, Word::one());
+    }
+    bytecode.write_op(opcode);
+    bytecode.write_op(OpcodeId::STOP);
+
+    let block =
+        block_from_test_ctx(TestContext::<2, 1>::simple_ctx_with_bytecode(bytecode).unwrap());
+    let step = block.txs[0]
+        .steps
+        .iter()
+        .find(|step| step.opcode == Some(opcode))
+        .unwrap();
+    assert_eq!(step.execution_state, execution_state);
+
+    CircuitTestBuilder::<2, 1>::new_from_block(block).run();
+}
+
+/// Runs the `snippets`, each exercising one or a few opcodes, one after
+/// another in the code of a single tx ending with a `STOP`, to test the
+/// interplay of their gadgets in one block.
+///
+/// The composed code must run straight through, i.e. a taken jump lands on
+/// the instruction right after it: the run panics if the tx doesn't execute
+/// every instruction of the code in order, before running the EVM and State
+/// circuits on the block with the [`CircuitTestBuilder`].
+pub fn run_opcode_sequence(snippets: &[Bytecode]) {
+    let mut code = Bytecode::default();
+    for snippet in snippets {
+        code.append(snippet);
+    }
+    code.write_op(OpcodeId::STOP);
+    let opcodes: Vec<_> = code.iter().map(|op| op.opcode()).collect();
+
+    let block = block_from_test_ctx(TestContext::<2, 1>::simple_ctx_with_bytecode(code).unwrap());
+
+    let executed_opcodes: Vec<_> = block.txs[0]
+        .steps
+        .iter()
+        .filter_map(|step| step.opcode)
+        .collect();
+    assert_eq!(
+        executed_opcodes, opcodes,
+        "the tx doesn't execute the opcode sequence in order"
+    );
+
+    CircuitTestBuilder::<2, 1>::new_from_block(block).run();
+}
+
+/// Formats the steps of the witness `block`, one line per step with its
+/// execution state, opcode, program counter, gas left and the stack top it
+/// pops (if any), followed by the trailing `EndBlock`.
+///
+/// It's printed by [`CircuitTestBuilder::run`] when the EVM circuit checks
+/// fail, e.g. for a block of a single tx running `PUSH1 0x01 PUSH1 0x02 ADD
+/// STOP`:
+/// ```text
+/// tx 0 step 0: BeginTx pc=0 gas=1000000
+/// tx 0 step 1: PUSH PUSH1 pc=0 gas=979000
+/// tx 0 step 2: PUSH PUSH1 pc=2 gas=978997
+/// tx 0 step 3: ADD_SUB ADD pc=4 gas=978994 stack_top=0x2
+/// tx 0 step 4: STOP STOP pc=5 gas=978991
+/// tx 0 step 5: EndTx pc=0 gas=978991
+/// EndBlock
+/// ```
+pub fn format_exec_steps<F>(block: &Block<F>) -> String {
+    let mut out = String::new();
+    for (tx_idx, tx) in block.txs.iter().enumerate() {
+        for (step_idx, step) in tx.steps.iter().enumerate() {
+            write!(
+                out,
+                "tx {} step {}: {:?}",
+                tx_idx, step_idx, step.execution_state
+            )
+            .unwrap();
+            if let Some(opcode) = step.opcode {
+                write!(out, " {:?}", opcode).unwrap();
+            }
+            write!(out, " pc={} gas={}", step.program_counter, step.gas_left).unwrap();
+            let stack_top = step
+                .rw_indices
+                .iter()
+                .find_map(|idx| match block.rws[*idx] {
+                    Rw::Stack {
+                        is_write: false,
+                        stack_pointer,
+                        value,
+                        ..
+                    } if stack_pointer == step.stack_pointer => Some(value),
+                    _ => None,
+                });
+            if let Some(stack_top) = stack_top {
+                write!(out, " stack_top={:#x}", stack_top).unwrap();
+            }
+            out.push('\n');
+        }
+    }
+    out.push_str("EndBlock\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use eth_types::bytecode;
+
+    fn add_ctx() -> TestContext<2, 1> {
+        TestContext::<2, 1>::simple_ctx_with_bytecode(bytecode! {
+            PUSH1(0x01)
+            PUSH1(0x02)
+            ADD
+            STOP
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn format_exec_steps_add() {
+        let block = block_from_test_ctx(add_ctx());
+
+        let steps = format_exec_steps(&block);
+        let lines: Vec<_> = steps.lines().collect();
+        assert_eq!(lines.len(), 7);
+        assert!(lines[0].starts_with("tx 0 step 0: BeginTx"));
+        assert!(lines[1].starts_with("tx 0 step 1: PUSH PUSH1 pc=0"));
+        assert!(lines[2].starts_with("tx 0 step 2: PUSH PUSH1 pc=2"));
+        assert!(lines[3].starts_with("tx 0 step 3: ADD_SUB ADD pc=4"));
+        assert!(lines[3].ends_with("stack_top=0x2"));
+        assert!(lines[4].starts_with("tx 0 step 4: STOP STOP pc=5"));
+        assert!(!lines[4].contains("stack_top"));
+        assert!(lines[5].starts_with("tx 0 step 5: EndTx"));
+        assert_eq!(lines[6], "EndBlock");
+    }
+
+    // The steps above are printed to stderr before the circuit failure is
+    // reported.
+    #[test]
+    #[should_panic]
+    fn circuit_test_builder_prints_steps_of_broken_block() {
+        CircuitTestBuilder::new_from_test_ctx(add_ctx())
+            .block_modifier(Box::new(|block| {
+                let step = block.txs[0]
+                    .steps
+                    .iter_mut()
+                    .find(|step| step.execution_state == ExecutionState::ADD_SUB)
+                    .unwrap();
+                step.gas_left += 1;
+            }))
+            .run();
+    }
+
+    #[test]
+    fn opcode_sequence_push_slt_iszero_jumpi() {
+        // 2 < 1 is false, so ISZERO turns it into a taken jump to the
+        // JUMPDEST right after the JUMPI, at offset 9.
+        run_opcode_sequence(&[
+            bytecode! {
+                PUSH1(1)
+                PUSH1(2)
+            },
+            bytecode! {
+                SLT
+            },
+            bytecode! {
+                ISZERO
+            },
+            bytecode! {
+                PUSH1(9)
+                JUMPI
+                JUMPDEST
+            },
+        ]);
+    }
+
+    #[test]
+    #[should_panic(expected = "the tx doesn't execute the opcode sequence in order")]
+    fn opcode_sequence_skipped_opcode() {
+        // The jump skips the PUSH1 in between.
+        run_opcode_sequence(&[bytecode! {
+            PUSH1(1)
+            PUSH1(7)
+            JUMPI
+            PUSH1(0)
+            JUMPDEST
+        }]);
+    }
+
+    #[test]
+    fn circuit_test_builder_allowed_opcodes() {
+        CircuitTestBuilder::new_from_test_ctx(add_ctx())
+            .allowed_opcodes(vec![OpcodeId::PUSH1, OpcodeId::ADD, OpcodeId::STOP])
+            .run();
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "tx 0 executes opcode ADD at pc 4, which is not in the allowed opcodes"
+    )]
+    fn circuit_test_builder_disallowed_opcode() {
+        CircuitTestBuilder::new_from_test_ctx(add_ctx())
+            .allowed_opcodes(vec![OpcodeId::PUSH1, OpcodeId::STOP])
+            .run();
+    }
+}
@@ -146,7 +146,9 @@ impl<F: Field> TxCircuitConfig<F> {
 pub struct TxCircuit<F: Field> {
     /// Max number of supported transactions
     pub max_txs: usize,
-    /// Max number of supported calldata bytes
+    /// Max number of supported calldata bytes, shared by all the transactions:
+    /// the calldata rows of the txs follow each other, keyed by the tx id and
+    /// the byte index, and the remaining rows are padding.
     pub max_calldata: usize,
     /// SignVerify chip
     pub sign_verify: SignVerifyChip<F>,
@@ -435,11 +437,12 @@ mod tx_circuit_tests {
     use super::*;
     use crate::util::log2_ceil;
     use eth_types::address;
+    use eth_types::{Bytes, Word};
     use halo2_proofs::{
         dev::{MockProver, VerifyFailure},
         halo2curves::bn256::Fr,
     };
-    use mock::AddrOrWallet;
+    use mock::{AddrOrWallet, MockTransaction};
     use pretty_assertions::assert_eq;
 
     const NUM_BLINDING_ROWS: usize = 64;
@@ -505,6 +508,59 @@ mod tx_circuit_tests {
         assert_eq!(run::<Fr>(vec![tx], chain_id, MAX_TXS, MAX_CALLDATA), Ok(()));
     }
 
+    #[test]
+    fn tx_circuit_invalid_signature() {
+        const MAX_TXS: usize = 3;
+        const MAX_CALLDATA: usize = 32;
+
+        let mut txs = mock::CORRECT_MOCK_TXS[..MAX_TXS]
+            .iter()
+            .map(|tx| Transaction::from(tx.clone()))
+            .collect_vec();
+        assert_eq!(
+            run::<Fr>(
+                txs[..2].to_vec(),
+                mock::MOCK_CHAIN_ID.as_u64(),
+                MAX_TXS,
+                MAX_CALLDATA
+            ),
+            Ok(())
+        );
+
+        // The tampered signature recovers another public key than the one of
+        // the tx sender.
+        txs[2].s = txs[2].s + 1;
+        assert!(run::<Fr>(txs, mock::MOCK_CHAIN_ID.as_u64(), MAX_TXS, MAX_CALLDATA).is_err());
+    }
+
+    #[test]
+    fn tx_circuit_fixed_aux_generator() {
+        let circuit_a = TxCircuit::<Fr>::new(
+            1,
+            32,
+            mock::MOCK_CHAIN_ID.as_u64(),
+            vec![mock::CORRECT_MOCK_TXS[0].clone().into()],
+        );
+        let circuit_b = TxCircuit::<Fr>::new(
+            2,
+            32,
+            mock::MOCK_CHAIN_ID.as_u64(),
+            mock::CORRECT_MOCK_TXS[1..3]
+                .iter()
+                .map(|tx| Transaction::from(tx.clone()))
+                .collect_vec(),
+        );
+
+        assert_eq!(
+            circuit_a.sign_verify.aux_generator,
+            circuit_b.sign_verify.aux_generator
+        );
+        assert_eq!(
+            circuit_a.sign_verify.aux_generator,
+            SignVerifyChip::<Fr>::fixed_aux_generator()
+        );
+    }
+
     #[test]
     fn tx_circuit_bad_address() {
         const MAX_TXS: usize = 1;
@@ -522,4 +578,34 @@ mod tx_circuit_tests {
         )
         .is_err(),);
     }
+
+    #[test]
+    fn tx_circuit_shared_calldata_pool() {
+        const MAX_TXS: usize = 4;
+        const MAX_CALLDATA: usize = 32;
+
+        // The first tx takes the whole calldata budget, which no other tx
+        // needs.
+        let txs = (0..MAX_TXS)
+            .map(|i| {
+                let calldata = if i == 0 {
+                    vec![0xab; MAX_CALLDATA]
+                } else {
+                    vec![]
+                };
+                MockTransaction::default()
+                    .from(mock::MOCK_WALLETS[i % mock::MOCK_WALLETS.len()].clone())
+                    .to(mock::MOCK_ACCOUNTS[i])
+                    .nonce(Word::from(i))
+                    .input(Bytes::from(calldata))
+                    .build()
+                    .into()
+            })
+            .collect_vec();
+
+        assert_eq!(
+            run::<Fr>(txs, mock::MOCK_CHAIN_ID.as_u64(), MAX_TXS, MAX_CALLDATA),
+            Ok(())
+        );
+    }
 }
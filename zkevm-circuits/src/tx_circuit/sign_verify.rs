@@ -55,13 +55,13 @@ pub struct SignVerifyChip<F: Field> {
 impl<F: Field> SignVerifyChip<F> {
     /// Return a new SignVerifyChip
     pub fn new(max_verif: usize) -> Self {
-        // TODO: Investigate if it is safe to use a random point as aux generator that
-        // is choosen by the prover.  If this is unsafe, we will need to update the
-        // EccChip to calculate an aux generator using the challange API.
-        // https://github.com/privacy-scaling-explorations/halo2wrong/issues/53
-        let mut rng = ChaCha20Rng::seed_from_u64(0);
-        let aux_generator =
-            <Secp256k1Affine as CurveAffine>::CurveExt::random(&mut rng).to_affine();
+        Self::with_aux_generator(max_verif, Self::fixed_aux_generator())
+    }
+
+    /// Return a new SignVerifyChip using a custom aux generator.  Note that the
+    /// aux generator is part of the circuit, so proving keys are only valid for
+    /// chips using the same one.
+    pub fn with_aux_generator(max_verif: usize, aux_generator: Secp256k1Affine) -> Self {
         Self {
             aux_generator,
             window_size: 2,
@@ -70,6 +70,17 @@ impl<F: Field> SignVerifyChip<F> {
         }
     }
 
+    /// Return the aux generator used by default, which is derived from a fixed
+    /// seed so that it's the same for every circuit instance.
+    pub fn fixed_aux_generator() -> Secp256k1Affine {
+        // TODO: Investigate if it is safe to use a random point as aux generator that
+        // is choosen by the prover.  If this is unsafe, we will need to update the
+        // EccChip to calculate an aux generator using the challange API.
+        // https://github.com/privacy-scaling-explorations/halo2wrong/issues/53
+        let mut rng = ChaCha20Rng::seed_from_u64(0);
+        <Secp256k1Affine as CurveAffine>::CurveExt::random(&mut rng).to_affine()
+    }
+
     /// Return the minimum number of rows required to prove an input of a
     /// particular size.
     pub fn min_num_rows(num_verif: usize) -> usize {
@@ -3,7 +3,7 @@
 //! used to generate witnesses for circuits.
 
 mod block;
-pub use block::{block_convert, Block, BlockContext};
+pub use block::{block_convert, block_from_geth_json, Block, BlockContext};
 mod bytecode;
 pub use bytecode::Bytecode;
 mod call;
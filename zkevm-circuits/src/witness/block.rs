@@ -1,17 +1,25 @@
 use std::collections::HashMap;
 
 use crate::{
-    evm_circuit::{detect_fixed_table_tags, util::rlc, EvmCircuit},
-    table::BlockContextFieldTag,
+    evm_circuit::{detect_fixed_table_tags, param::STACK_CAPACITY, util::rlc, EvmCircuit},
+    table::{BlockContextFieldTag, RwTableTag},
 };
 use bus_mapping::{
-    circuit_input_builder::{self, CircuitsParams, CopyEvent, ExpEvent},
+    circuit_input_builder::{self, CircuitsParams, CodeSource, CopyEvent, ExpEvent, StateOverride},
+    precompile::{is_precompiled, PrecompileCall},
     Error,
 };
-use eth_types::{Address, Field, ToLittleEndian, ToScalar, Word};
+use eth_types::{
+    evm_types::{HardFork, OpcodeId},
+    Address, Field, ToLittleEndian, ToScalar, ToWord, Word,
+};
 use halo2_proofs::circuit::Value;
 
-use super::{step::step_convert, tx::tx_convert, Bytecode, ExecStep, RwMap, Transaction};
+use super::{
+    step::{opcode_execution_state, step_convert},
+    tx::tx_convert,
+    Bytecode, ExecStep, Rw, RwMap, Transaction,
+};
 
 // TODO: Remove fields that are duplicated in`eth_block`
 /// Block is the struct used by all circuits, which contains all the needed
@@ -159,49 +167,48 @@ pub struct BlockContext {
 }
 
 impl BlockContext {
+    /// Value of the block field identified by `tag`, as looked up by the
+    /// gadgets in the block table. Returns `None` for `BlockHash`, whose rows
+    /// are indexed by block number, use `history_hashes` for them instead.
+    pub fn value(&self, tag: BlockContextFieldTag) -> Option<Word> {
+        match tag {
+            BlockContextFieldTag::Coinbase => Some(self.coinbase.to_word()),
+            BlockContextFieldTag::Timestamp => Some(self.timestamp),
+            BlockContextFieldTag::Number => Some(self.number),
+            BlockContextFieldTag::Difficulty => Some(self.difficulty),
+            BlockContextFieldTag::GasLimit => Some(Word::from(self.gas_limit)),
+            BlockContextFieldTag::BaseFee => Some(self.base_fee),
+            BlockContextFieldTag::ChainId => Some(self.chain_id),
+            BlockContextFieldTag::BlockHash => None,
+        }
+    }
+
     /// Assignments for block table
     pub fn table_assignments<F: Field>(&self, randomness: Value<F>) -> Vec<[Value<F>; 3]> {
         [
-            vec![
-                [
-                    Value::known(F::from(BlockContextFieldTag::Coinbase as u64)),
-                    Value::known(F::zero()),
-                    Value::known(self.coinbase.to_scalar().unwrap()),
-                ],
-                [
-                    Value::known(F::from(BlockContextFieldTag::Timestamp as u64)),
-                    Value::known(F::zero()),
-                    Value::known(self.timestamp.to_scalar().unwrap()),
-                ],
-                [
-                    Value::known(F::from(BlockContextFieldTag::Number as u64)),
-                    Value::known(F::zero()),
-                    Value::known(self.number.to_scalar().unwrap()),
-                ],
-                [
-                    Value::known(F::from(BlockContextFieldTag::Difficulty as u64)),
-                    Value::known(F::zero()),
-                    randomness
-                        .map(|randomness| rlc::value(&self.difficulty.to_le_bytes(), randomness)),
-                ],
-                [
-                    Value::known(F::from(BlockContextFieldTag::GasLimit as u64)),
-                    Value::known(F::zero()),
-                    Value::known(F::from(self.gas_limit)),
-                ],
-                [
-                    Value::known(F::from(BlockContextFieldTag::BaseFee as u64)),
-                    Value::known(F::zero()),
-                    randomness
-                        .map(|randomness| rlc::value(&self.base_fee.to_le_bytes(), randomness)),
-                ],
+            [
+                BlockContextFieldTag::Coinbase,
+                BlockContextFieldTag::Timestamp,
+                BlockContextFieldTag::Number,
+                BlockContextFieldTag::Difficulty,
+                BlockContextFieldTag::GasLimit,
+                BlockContextFieldTag::BaseFee,
+                BlockContextFieldTag::ChainId,
+            ]
+            .iter()
+            .filter_map(|tag| self.value(*tag).map(|value| (tag, value)))
+            .map(|(tag, value)| {
                 [
-                    Value::known(F::from(BlockContextFieldTag::ChainId as u64)),
+                    Value::known(F::from(*tag as u64)),
                     Value::known(F::zero()),
-                    randomness
-                        .map(|randomness| rlc::value(&self.chain_id.to_le_bytes(), randomness)),
-                ],
-            ],
+                    if tag.is_word() {
+                        randomness.map(|randomness| rlc::value(&value.to_le_bytes(), randomness))
+                    } else {
+                        Value::known(value.to_scalar().unwrap())
+                    },
+                ]
+            })
+            .collect::<Vec<_>>(),
             {
                 let len_history = self.history_hashes.len();
                 self.history_hashes
@@ -237,12 +244,31 @@ impl From<&circuit_input_builder::Block> for BlockContext {
     }
 }
 
-/// Convert a block struct in bus-mapping to a witness block used in circuits
+/// Convert a block struct in bus-mapping to a witness block used in circuits.
+/// Fails with [`Error::UnsupportedHardFork`] if the block isn't executed with
+/// the EVM rules of London, the only ones the EVM circuit constrains: the hard
+/// fork only drives the witness generation in bus-mapping. Fails with
+/// [`Error::UnsupportedOpcodes`] if the block executes opcodes that the EVM
+/// circuit has no gadget for, with
+/// [`Error::UnsupportedPrecompiles`] if it calls precompiled contracts other
+/// than the identity, and with [`Error::InconsistentStackOp`] if the stack
+/// operations don't match the stack of the steps they belong to.
 pub fn block_convert<F: Field>(
     block: &circuit_input_builder::Block,
     code_db: &bus_mapping::state_db::CodeDB,
 ) -> Result<Block<F>, Error> {
-    Ok(Block {
+    if block.hardfork != HardFork::London {
+        return Err(Error::UnsupportedHardFork(block.hardfork));
+    }
+    let unsupported_opcodes = unsupported_opcodes(block);
+    if !unsupported_opcodes.is_empty() {
+        return Err(Error::UnsupportedOpcodes(unsupported_opcodes));
+    }
+    let unsupported_precompiles = unsupported_precompiles(block);
+    if !unsupported_precompiles.is_empty() {
+        return Err(Error::UnsupportedPrecompiles(unsupported_precompiles));
+    }
+    let block = Block {
         // randomness: F::from(0x100), // Special value to reveal elements after RLC
         randomness: F::from(0xcafeu64),
         context: block.into(),
@@ -272,5 +298,477 @@ pub fn block_convert<F: Field>(
         prev_state_root: block.prev_state_root,
         keccak_inputs: circuit_input_builder::keccak_inputs(block, code_db)?,
         eth_block: block.eth_block.clone(),
-    })
+    };
+    check_stack_rws(&block)?;
+    Ok(block)
+}
+
+/// Check the stack rws of each step against the stack of the step, which the
+/// gadgets trust when assigning the stack values: the rws belong to the call
+/// of the step, a read is at or above the stack pointer and a write at most
+/// one slot below it (no opcode pushes more items than it pops plus one), and
+/// a read returns the value last written at its address.
+fn check_stack_rws<F>(block: &Block<F>) -> Result<(), Error> {
+    let mut stack_values = HashMap::new();
+    for tx in block.txs.iter() {
+        for step in tx.steps.iter() {
+            let call_id = tx.calls[step.call_index].id;
+            for (tag, idx) in step.rw_indices.iter() {
+                if *tag != RwTableTag::Stack {
+                    continue;
+                }
+                if let Rw::Stack {
+                    rw_counter,
+                    is_write,
+                    call_id: rw_call_id,
+                    stack_pointer,
+                    value,
+                } = block.rws[(*tag, *idx)]
+                {
+                    if rw_call_id != call_id {
+                        return Err(Error::InconsistentStackOp(
+                            "stack op of another call",
+                            rw_counter,
+                        ));
+                    }
+                    let lowest_stack_pointer = step.stack_pointer.saturating_sub(is_write as usize);
+                    if stack_pointer < lowest_stack_pointer || stack_pointer >= STACK_CAPACITY {
+                        return Err(Error::InconsistentStackOp(
+                            "stack op out of the stack of the step",
+                            rw_counter,
+                        ));
+                    }
+                    if is_write {
+                        stack_values.insert((call_id, stack_pointer), value);
+                    } else if stack_values
+                        .get(&(call_id, stack_pointer))
+                        .map_or(false, |written| *written != value)
+                    {
+                        return Err(Error::InconsistentStackOp(
+                            "stack read of a value not written",
+                            rw_counter,
+                        ));
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The opcodes executed successfully in `block` that the EVM circuit has no
+/// gadget for, in order of first appearance.
+fn unsupported_opcodes(block: &circuit_input_builder::Block) -> Vec<OpcodeId> {
+    let mut opcodes = Vec::new();
+    for step in block.txs().iter().flat_map(|tx| tx.steps()) {
+        if let circuit_input_builder::ExecState::Op(op) = step.exec_state {
+            if step.error.is_none()
+                && opcode_execution_state(op).is_none()
+                && !opcodes.contains(&op)
+            {
+                opcodes.push(op);
+            }
+        }
+    }
+    opcodes
+}
+
+/// The precompiled contracts called in `block` that the EVM circuit can't
+/// prove, i.e. all but the identity, in order of first appearance.
+fn unsupported_precompiles(block: &circuit_input_builder::Block) -> Vec<Address> {
+    let mut addresses = Vec::new();
+    for call in block.txs().iter().flat_map(|tx| tx.calls()) {
+        if let CodeSource::Address(address) = call.code_source {
+            if is_precompiled(&address)
+                && PrecompileCall::from_address(&address) != Some(PrecompileCall::Identity)
+                && !addresses.contains(&address)
+            {
+                addresses.push(address);
+            }
+        }
+    }
+    addresses
+}
+
+/// Build the witness block straight from the raw JSON response of a
+/// `debug_traceBlock*` call, the block and the state proofs of the accessed
+/// accounts, bypassing `GethClient`. `state_override` is applied on the state
+/// from the proofs, and the block is executed with the EVM rules of
+/// `hardfork`.
+#[allow(clippy::too_many_arguments)]
+pub fn block_from_geth_json<F: Field>(
+    chain_id: Word,
+    circuits_params: CircuitsParams,
+    hardfork: HardFork,
+    eth_block: &eth_types::Block<eth_types::Transaction>,
+    geth_traces_json: &str,
+    proofs: Vec<eth_types::EIP1186ProofResponse>,
+    codes: HashMap<Address, Vec<u8>>,
+    state_override: &StateOverride,
+    history_hashes: Vec<Word>,
+    prev_state_root: Word,
+) -> Result<Block<F>, Error> {
+    let builder = circuit_input_builder::gen_inputs_from_geth_json(
+        chain_id,
+        circuits_params,
+        hardfork,
+        eth_block,
+        geth_traces_json,
+        proofs,
+        codes,
+        state_override,
+        history_hashes,
+        prev_state_root,
+    )?;
+    block_convert(&builder.block, &builder.code_db)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::evm_circuit::step::ExecutionState;
+    use crate::test_util::{builder_from_test_ctx, CircuitTestBuilder};
+    use bus_mapping::{
+        circuit_input_builder::{
+            build_state_code_db_with_override, geth_traces_from_json, AccountOverride,
+            CircuitInputBuilder, ExecState,
+        },
+        mock::{BlockData, TRANSFER_TRACE_JSON},
+        operation::StackOp,
+    };
+    use eth_types::{
+        bytecode,
+        evm_types::{
+            StackAddress, PRECOMPILE_BLAKE2F, PRECOMPILE_BN256_ADD, PRECOMPILE_BN256_PAIRING,
+            PRECOMPILE_BN256_SCALAR_MUL,
+        },
+        geth_types::Account,
+        StorageProof, H256,
+    };
+    use ethers_core::utils::keccak256;
+    use halo2_proofs::halo2curves::bn256::Fr;
+    use mock::test_ctx::{
+        helpers::{account_0_code_account_1_no_code, tx_from_1_to_0},
+        TestContext,
+    };
+
+    /// The `eth_getProof` responses and the codes of `accounts`.
+    fn proofs_and_codes(
+        accounts: &[Account],
+    ) -> (
+        Vec<eth_types::EIP1186ProofResponse>,
+        HashMap<Address, Vec<u8>>,
+    ) {
+        let proofs = accounts
+            .iter()
+            .map(|account| eth_types::EIP1186ProofResponse {
+                address: account.address,
+                balance: account.balance,
+                nonce: account.nonce,
+                code_hash: H256(keccak256(&account.code)),
+                storage_proof: account
+                    .storage
+                    .iter()
+                    .map(|(key, value)| StorageProof {
+                        key: *key,
+                        value: *value,
+                        ..Default::default()
+                    })
+                    .collect(),
+                ..Default::default()
+            })
+            .collect();
+        let codes = accounts
+            .iter()
+            .map(|account| (account.address, account.code.to_vec()))
+            .collect();
+        (proofs, codes)
+    }
+
+    #[test]
+    fn block_from_geth_json_transfer() {
+        let ctx = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(bytecode! {}),
+            tx_from_1_to_0,
+            |block, _tx| block,
+        )
+        .unwrap();
+
+        // The committed fixture must match what geth traces for the same block
+        assert_eq!(
+            geth_traces_from_json(TRANSFER_TRACE_JSON).unwrap(),
+            ctx.geth_traces.to_vec()
+        );
+
+        let (proofs, codes) = proofs_and_codes(&ctx.accounts);
+
+        let block: Block<Fr> = block_from_geth_json(
+            ctx.chain_id,
+            CircuitsParams::default(),
+            HardFork::default(),
+            &ctx.eth_block,
+            TRANSFER_TRACE_JSON,
+            proofs,
+            codes,
+            &StateOverride::new(),
+            ctx.history_hashes.clone(),
+            Word::zero(),
+        )
+        .unwrap();
+
+        assert_eq!(block.txs.len(), 1);
+        let states: Vec<_> = block.txs[0]
+            .steps
+            .iter()
+            .map(|step| step.execution_state)
+            .collect();
+        assert_eq!(states, vec![ExecutionState::BeginTx, ExecutionState::EndTx]);
+    }
+
+    #[test]
+    fn block_convert_short_history_hashes() {
+        let history_hashes =
+            |len: u64| -> Vec<Word> { (0..len).map(|i| Word::from(0xbeefcafeu64 + i)).collect() };
+        let ctx = TestContext::<2, 1>::new(
+            Some(history_hashes(10)),
+            account_0_code_account_1_no_code(bytecode! {}),
+            tx_from_1_to_0,
+            |block, _tx| block.number(10),
+        )
+        .unwrap();
+
+        let builder = BlockData::new_from_geth_data(ctx.into()).new_circuit_input_builder();
+        assert_eq!(builder.block.history_hashes.len(), 10);
+        let block: Block<Fr> = block_convert(&builder.block, &builder.code_db).unwrap();
+        assert_eq!(block.context.history_hashes, history_hashes(10));
+
+        // Block 10 has no 11th ancestor to take a hash from
+        assert!(circuit_input_builder::Block::new(
+            builder.block.chain_id,
+            history_hashes(11),
+            Word::zero(),
+            &builder.block.eth_block,
+            CircuitsParams::default(),
+            builder.block.hardfork,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn block_convert_unsupported_opcodes() {
+        let ctx = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(bytecode! {
+                PUSH1(1)
+                PUSH1(2)
+                ADD
+                STOP
+            }),
+            tx_from_1_to_0,
+            |block, _tx| block,
+        )
+        .unwrap();
+        let mut builder = builder_from_test_ctx(ctx, CircuitsParams::default());
+
+        // Pretend the ADD is an opcode without a gadget
+        let step = builder.block.txs_mut()[0]
+            .steps_mut()
+            .iter_mut()
+            .find(|step| step.exec_state == ExecState::Op(OpcodeId::ADD))
+            .unwrap();
+        step.exec_state = ExecState::Op(OpcodeId::INVALID(0x0c));
+
+        match block_convert::<Fr>(&builder.block, &builder.code_db).unwrap_err() {
+            Error::UnsupportedOpcodes(opcodes) => {
+                assert_eq!(opcodes, vec![OpcodeId::INVALID(0x0c)])
+            }
+            err => panic!("unexpected error {:?}", err),
+        }
+    }
+
+    #[test]
+    fn block_convert_unsupported_precompiles() {
+        for precompile in [
+            PRECOMPILE_BN256_ADD,
+            PRECOMPILE_BN256_SCALAR_MUL,
+            PRECOMPILE_BN256_PAIRING,
+            PRECOMPILE_BLAKE2F,
+        ] {
+            let address = Address::from_low_u64_be(precompile);
+            let ctx = TestContext::<2, 1>::new(
+                None,
+                account_0_code_account_1_no_code(bytecode! {
+                    PUSH1(0) // retLength
+                    PUSH1(0) // retOffset
+                    PUSH1(0) // argsLength
+                    PUSH1(0) // argsOffset
+                    PUSH1(0) // value
+                    PUSH1(precompile) // addr
+                    PUSH32(0x1000) // gas
+                    CALL
+                    STOP
+                }),
+                tx_from_1_to_0,
+                |block, _tx| block,
+            )
+            .unwrap();
+            let builder = builder_from_test_ctx(ctx, CircuitsParams::default());
+
+            match block_convert::<Fr>(&builder.block, &builder.code_db).unwrap_err() {
+                Error::UnsupportedPrecompiles(addresses) => assert_eq!(addresses, vec![address]),
+                err => panic!("unexpected error {:?}", err),
+            }
+        }
+    }
+
+    #[test]
+    fn block_convert_inconsistent_stack_op() {
+        let ctx = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(bytecode! {
+                PUSH1(1)
+                PUSH1(2)
+                ADD
+                STOP
+            }),
+            tx_from_1_to_0,
+            |block, _tx| block,
+        )
+        .unwrap();
+        let mut builder = builder_from_test_ctx(ctx, CircuitsParams::default());
+        assert!(block_convert::<Fr>(&builder.block, &builder.code_db).is_ok());
+
+        // The stack ops are the writes of both PUSH1, and the two reads and the
+        // write of ADD.
+        assert_eq!(builder.block.container.stack.len(), 5);
+        let (read_of_add, write_of_push) = (2, 1);
+        let cases: [(usize, fn(&mut StackOp), &str); 3] = [
+            (
+                read_of_add,
+                |op: &mut StackOp| op.value = Word::from(3),
+                "stack read of a value not written",
+            ),
+            (
+                write_of_push,
+                |op: &mut StackOp| op.address = StackAddress(1020),
+                "stack op out of the stack of the step",
+            ),
+            (
+                write_of_push,
+                |op: &mut StackOp| op.call_id += 1,
+                "stack op of another call",
+            ),
+        ];
+        for (idx, tampered, expected) in cases {
+            let stack_op = &mut builder.block.container.stack[idx];
+            let rw_counter = stack_op.rwc().0;
+            let original = stack_op.op().clone();
+            tampered(stack_op.op_mut());
+
+            match block_convert::<Fr>(&builder.block, &builder.code_db).unwrap_err() {
+                Error::InconsistentStackOp(reason, counter) => {
+                    assert_eq!(reason, expected);
+                    assert_eq!(counter, rw_counter);
+                }
+                err => panic!("unexpected error {:?}", err),
+            }
+            *builder.block.container.stack[idx].op_mut() = original;
+        }
+    }
+
+    #[test]
+    fn block_context_values() {
+        let coinbase = *mock::MOCK_COINBASE;
+        let ctx = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(bytecode! {}),
+            tx_from_1_to_0,
+            |block, _tx| {
+                block
+                    .author(coinbase)
+                    .timestamp(Word::from(0x1234u64))
+                    .number(0xcafeu64)
+                    .difficulty(Word::from(0x5678u64))
+                    .gas_limit(Word::from(30_000_000u64))
+                    .base_fee_per_gas(Word::from(0xdefu64))
+            },
+        )
+        .unwrap();
+        let chain_id = ctx.chain_id;
+
+        let builder = BlockData::new_from_geth_data(ctx.into()).new_circuit_input_builder();
+        let block: Block<Fr> = block_convert(&builder.block, &builder.code_db).unwrap();
+
+        for (tag, expected) in [
+            (BlockContextFieldTag::Coinbase, coinbase.to_word()),
+            (BlockContextFieldTag::Timestamp, Word::from(0x1234u64)),
+            (BlockContextFieldTag::Number, Word::from(0xcafeu64)),
+            (BlockContextFieldTag::Difficulty, Word::from(0x5678u64)),
+            (BlockContextFieldTag::GasLimit, Word::from(30_000_000u64)),
+            (BlockContextFieldTag::BaseFee, Word::from(0xdefu64)),
+            (BlockContextFieldTag::ChainId, chain_id),
+        ] {
+            assert_eq!(block.context.value(tag), Some(expected), "{:?}", tag);
+        }
+        assert_eq!(block.context.value(BlockContextFieldTag::BlockHash), None);
+    }
+
+    #[test]
+    fn block_state_override_code() {
+        let code = bytecode! {
+            PUSH1(0x2a)
+            PUSH1(0)
+            SSTORE
+            STOP
+        };
+        // The block is traced on the state with the overridden code.
+        let ctx = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code.clone()),
+            tx_from_1_to_0,
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap();
+        let address = ctx.accounts[0].address;
+
+        // While the proofs hold the original code, which doesn't store
+        // anything.
+        let mut accounts = ctx.accounts.to_vec();
+        accounts[0].code = bytecode! { STOP }.into();
+        let (proofs, codes) = proofs_and_codes(&accounts);
+        let state_override = StateOverride::from([(
+            address,
+            AccountOverride {
+                code: Some(code.to_vec()),
+                ..Default::default()
+            },
+        )]);
+        let (sdb, code_db) = build_state_code_db_with_override(proofs, codes, &state_override);
+        assert_eq!(
+            sdb.get_account(&address).1.code_hash,
+            H256(keccak256(code.to_vec()))
+        );
+
+        let block = circuit_input_builder::Block::new(
+            ctx.chain_id,
+            ctx.history_hashes.clone(),
+            Word::zero(),
+            &ctx.eth_block,
+            CircuitsParams::default(),
+            ctx.hardfork,
+        )
+        .unwrap();
+        let mut builder = CircuitInputBuilder::new(sdb, code_db, block);
+        builder
+            .handle_block(&ctx.eth_block, &ctx.geth_traces)
+            .unwrap();
+        assert_eq!(
+            builder.sdb.get_storage(&address, &Word::zero()).1,
+            &Word::from(0x2a)
+        );
+
+        let block: Block<Fr> = block_convert(&builder.block, &builder.code_db).unwrap();
+        CircuitTestBuilder::<2, 1>::new_from_block(block).run();
+    }
 }
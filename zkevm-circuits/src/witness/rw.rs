@@ -67,6 +67,39 @@ impl RwMap {
         let padding = (1..=padding_length).map(|rw_counter| Rw::Start { rw_counter });
         (padding.chain(rows.into_iter()).collect(), padding_length)
     }
+    /// Serialize the rws to CSV, one line per rw in the order of the rw
+    /// counter, under the header
+    /// `rw_counter,is_write,tag,id,address,field_tag,storage_key,value,
+    /// value_prev`. The values are written as is instead of their RLC, so
+    /// that the rws can be diffed against the accesses of a reference EVM,
+    /// and the keys that a tag doesn't have are left empty.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from(
+            "rw_counter,is_write,tag,id,address,field_tag,storage_key,value,value_prev\n",
+        );
+        let optional = |value: Option<String>| value.unwrap_or_default();
+        for rw in self
+            .0
+            .values()
+            .flatten()
+            .filter(|rw| !matches!(rw, Rw::Start { .. }))
+            .sorted_by_key(|rw| rw.rw_counter())
+        {
+            csv.push_str(&format!(
+                "{},{},{:?},{},{},{},{},{:#x},{}\n",
+                rw.rw_counter(),
+                rw.is_write() as u64,
+                rw.tag(),
+                optional(rw.id().map(|id| id.to_string())),
+                optional(rw.address().map(|address| format!("{:?}", address))),
+                optional(rw.field_tag().map(|field_tag| field_tag.to_string())),
+                optional(rw.storage_key().map(|key| format!("{:#x}", key))),
+                rw.raw_value(),
+                optional(rw.raw_value_prev().map(|value| format!("{:#x}", value))),
+            ));
+        }
+        csv
+    }
     /// Build Rws for assignment
     pub fn table_assignments(&self) -> Vec<Rw> {
         let mut rows: Vec<Rw> = self.0.values().flatten().cloned().collect();
@@ -519,6 +552,46 @@ impl Rw {
         }
     }
 
+    /// The value of the rw as is, i.e. before its RLC in the rw table.
+    fn raw_value(&self) -> Word {
+        match self {
+            Self::Start { .. } => Word::zero(),
+            Self::TxAccessListAccount { is_warm, .. }
+            | Self::TxAccessListAccountStorage { is_warm, .. } => Word::from(*is_warm as u64),
+            Self::AccountDestructed { is_destructed, .. } => Word::from(*is_destructed as u64),
+            Self::TxRefund { value, .. } | Self::TxReceipt { value, .. } => Word::from(*value),
+            Self::Account { value, .. }
+            | Self::AccountStorage { value, .. }
+            | Self::CallContext { value, .. }
+            | Self::Stack { value, .. }
+            | Self::TxLog { value, .. } => *value,
+            Self::Memory { byte, .. } => Word::from(*byte),
+        }
+    }
+
+    /// The previous value of the rw as is, for the tags which have one.
+    fn raw_value_prev(&self) -> Option<Word> {
+        match self {
+            Self::TxAccessListAccount { is_warm_prev, .. }
+            | Self::TxAccessListAccountStorage { is_warm_prev, .. } => {
+                Some(Word::from(*is_warm_prev as u64))
+            }
+            Self::AccountDestructed {
+                is_destructed_prev, ..
+            } => Some(Word::from(*is_destructed_prev as u64)),
+            Self::TxRefund { value_prev, .. } => Some(Word::from(*value_prev)),
+            Self::Account { value_prev, .. } | Self::AccountStorage { value_prev, .. } => {
+                Some(*value_prev)
+            }
+            Self::Start { .. }
+            | Self::Stack { .. }
+            | Self::Memory { .. }
+            | Self::CallContext { .. }
+            | Self::TxLog { .. }
+            | Self::TxReceipt { .. } => None,
+        }
+    }
+
     pub(crate) fn value_assignment<F: Field>(&self, randomness: F) -> F {
         match self {
             Self::Start { .. } => F::zero(),
@@ -835,3 +908,61 @@ impl From<&operation::OperationContainer> for RwMap {
         Self(rws)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::block_from_test_ctx;
+    use eth_types::bytecode;
+    use mock::test_ctx::{
+        helpers::{account_0_code_account_1_no_code, tx_from_1_to_0},
+        TestContext,
+    };
+
+    #[test]
+    fn rw_map_to_csv_sstore() {
+        let ctx = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(bytecode! {
+                PUSH1(0x2a)
+                PUSH1(0x01)
+                SSTORE
+                STOP
+            }),
+            tx_from_1_to_0,
+            |block, _tx| block,
+        )
+        .unwrap();
+        let address = ctx.accounts[0].address;
+        let block = block_from_test_ctx(ctx);
+
+        let csv = block.rws.to_csv();
+        let lines: Vec<_> = csv.lines().collect();
+        assert_eq!(
+            lines[0],
+            "rw_counter,is_write,tag,id,address,field_tag,storage_key,value,value_prev"
+        );
+        // One line per rw, in the order of the rw counter.
+        let num_rws = block
+            .rws
+            .0
+            .values()
+            .flatten()
+            .filter(|rw| !matches!(rw, Rw::Start { .. }))
+            .count();
+        assert_eq!(lines.len(), 1 + num_rws);
+        for (idx, line) in lines[1..].iter().enumerate() {
+            assert!(line.starts_with(&format!("{},", idx + 1)), "{}", line);
+        }
+
+        // The write of 0x2a at the slot 1 of the account, in tx 1.
+        let rw_counter = block.rws.0[&RwTableTag::AccountStorage][0].rw_counter();
+        assert!(lines.contains(
+            &format!(
+                "{},1,AccountStorage,1,{:?},,0x1,0x2a,0x0",
+                rw_counter, address
+            )
+            .as_str()
+        ));
+    }
+}
@@ -39,6 +39,8 @@ pub struct ExecStep {
     pub reversible_write_counter: usize,
     /// The counter for log index within tx
     pub log_id: usize,
+    /// The accumulated gas refund of the tx before the step
+    pub gas_refund: u64,
     /// The opcode corresponds to the step
     pub opcode: Option<OpcodeId>,
 }
@@ -63,6 +65,7 @@ impl From<&ExecError> for ExecutionState {
             ExecError::Depth => ExecutionState::ErrorDepth,
             ExecError::InsufficientBalance => ExecutionState::ErrorInsufficientBalance,
             ExecError::ContractAddressCollision => ExecutionState::ErrorContractAddressCollision,
+            ExecError::NonceUintOverflow => ExecutionState::ErrorNonceUintOverflow,
             ExecError::InvalidCreationCode => ExecutionState::ErrorInvalidCreationCode,
             ExecError::InvalidJump => ExecutionState::ErrorInvalidJump,
             ExecError::ReturnDataOutOfBounds => ExecutionState::ErrorReturnDataOutOfBound,
@@ -103,102 +106,111 @@ impl From<&circuit_input_builder::ExecStep> for ExecutionState {
         }
         match step.exec_state {
             circuit_input_builder::ExecState::Op(op) => {
-                if op.is_dup() {
-                    return ExecutionState::DUP;
-                }
-                if op.is_push() {
-                    return ExecutionState::PUSH;
-                }
-                if op.is_swap() {
-                    return ExecutionState::SWAP;
-                }
-                if op.is_log() {
-                    return ExecutionState::LOG;
-                }
-
-                macro_rules! dummy {
-                    ($name:expr) => {{
-                        evm_unimplemented!("{:?} is implemented with DummyGadget", $name);
-                        $name
-                    }};
-                }
-
-                match op {
-                    OpcodeId::ADD | OpcodeId::SUB => ExecutionState::ADD_SUB,
-                    OpcodeId::ADDMOD => ExecutionState::ADDMOD,
-                    OpcodeId::ADDRESS => ExecutionState::ADDRESS,
-                    OpcodeId::BALANCE => ExecutionState::BALANCE,
-                    OpcodeId::MUL | OpcodeId::DIV | OpcodeId::MOD => ExecutionState::MUL_DIV_MOD,
-                    OpcodeId::MULMOD => ExecutionState::MULMOD,
-                    OpcodeId::SDIV | OpcodeId::SMOD => ExecutionState::SDIV_SMOD,
-                    OpcodeId::EQ | OpcodeId::LT | OpcodeId::GT => ExecutionState::CMP,
-                    OpcodeId::SLT | OpcodeId::SGT => ExecutionState::SCMP,
-                    OpcodeId::SIGNEXTEND => ExecutionState::SIGNEXTEND,
-                    OpcodeId::STOP => ExecutionState::STOP,
-                    OpcodeId::AND => ExecutionState::BITWISE,
-                    OpcodeId::XOR => ExecutionState::BITWISE,
-                    OpcodeId::OR => ExecutionState::BITWISE,
-                    OpcodeId::NOT => ExecutionState::NOT,
-                    OpcodeId::EXP => ExecutionState::EXP,
-                    OpcodeId::POP => ExecutionState::POP,
-                    OpcodeId::PUSH32 => ExecutionState::PUSH,
-                    OpcodeId::BYTE => ExecutionState::BYTE,
-                    OpcodeId::MLOAD => ExecutionState::MEMORY,
-                    OpcodeId::MSTORE => ExecutionState::MEMORY,
-                    OpcodeId::MSTORE8 => ExecutionState::MEMORY,
-                    OpcodeId::JUMPDEST => ExecutionState::JUMPDEST,
-                    OpcodeId::JUMP => ExecutionState::JUMP,
-                    OpcodeId::JUMPI => ExecutionState::JUMPI,
-                    OpcodeId::GASPRICE => ExecutionState::GASPRICE,
-                    OpcodeId::PC => ExecutionState::PC,
-                    OpcodeId::MSIZE => ExecutionState::MSIZE,
-                    OpcodeId::CALLER => ExecutionState::CALLER,
-                    OpcodeId::CALLVALUE => ExecutionState::CALLVALUE,
-                    OpcodeId::EXTCODEHASH => ExecutionState::EXTCODEHASH,
-                    OpcodeId::EXTCODESIZE => ExecutionState::EXTCODESIZE,
-                    OpcodeId::BLOCKHASH => ExecutionState::BLOCKHASH,
-                    OpcodeId::TIMESTAMP | OpcodeId::NUMBER | OpcodeId::GASLIMIT => {
-                        ExecutionState::BLOCKCTXU64
-                    }
-                    OpcodeId::COINBASE => ExecutionState::BLOCKCTXU160,
-                    OpcodeId::DIFFICULTY | OpcodeId::BASEFEE => ExecutionState::BLOCKCTXU256,
-                    OpcodeId::GAS => ExecutionState::GAS,
-                    OpcodeId::SAR => ExecutionState::SAR,
-                    OpcodeId::SELFBALANCE => ExecutionState::SELFBALANCE,
-                    OpcodeId::SHA3 => ExecutionState::SHA3,
-                    OpcodeId::SHL | OpcodeId::SHR => ExecutionState::SHL_SHR,
-                    OpcodeId::SLOAD => ExecutionState::SLOAD,
-                    OpcodeId::SSTORE => ExecutionState::SSTORE,
-                    OpcodeId::CALLDATASIZE => ExecutionState::CALLDATASIZE,
-                    OpcodeId::CALLDATACOPY => ExecutionState::CALLDATACOPY,
-                    OpcodeId::CHAINID => ExecutionState::CHAINID,
-                    OpcodeId::ISZERO => ExecutionState::ISZERO,
-                    OpcodeId::CALL
-                    | OpcodeId::CALLCODE
-                    | OpcodeId::DELEGATECALL
-                    | OpcodeId::STATICCALL => ExecutionState::CALL_OP,
-                    OpcodeId::ORIGIN => ExecutionState::ORIGIN,
-                    OpcodeId::CODECOPY => ExecutionState::CODECOPY,
-                    OpcodeId::CALLDATALOAD => ExecutionState::CALLDATALOAD,
-                    OpcodeId::CODESIZE => ExecutionState::CODESIZE,
-                    OpcodeId::RETURN | OpcodeId::REVERT => ExecutionState::RETURN_REVERT,
-                    OpcodeId::RETURNDATASIZE => ExecutionState::RETURNDATASIZE,
-                    OpcodeId::RETURNDATACOPY => ExecutionState::RETURNDATACOPY,
-                    // dummy ops
-                    OpcodeId::EXTCODECOPY => dummy!(ExecutionState::EXTCODECOPY),
-                    OpcodeId::CREATE => dummy!(ExecutionState::CREATE),
-                    OpcodeId::CREATE2 => dummy!(ExecutionState::CREATE2),
-                    OpcodeId::SELFDESTRUCT => dummy!(ExecutionState::SELFDESTRUCT),
-                    _ => unimplemented!("unimplemented opcode {:?}", op),
+                let execution_state = opcode_execution_state(op)
+                    .unwrap_or_else(|| unimplemented!("unimplemented opcode {:?}", op));
+                if matches!(
+                    execution_state,
+                    ExecutionState::EXTCODECOPY
+                        | ExecutionState::CREATE
+                        | ExecutionState::CREATE2
+                        | ExecutionState::SELFDESTRUCT
+                ) {
+                    evm_unimplemented!("{:?} is implemented with DummyGadget", execution_state);
                 }
+                execution_state
             }
             circuit_input_builder::ExecState::BeginTx => ExecutionState::BeginTx,
             circuit_input_builder::ExecState::EndTx => ExecutionState::EndTx,
+            circuit_input_builder::ExecState::InvalidTx => ExecutionState::InvalidTx,
             circuit_input_builder::ExecState::EndBlock => ExecutionState::EndBlock,
         }
     }
 }
 
+/// The execution state of a successful step of `op`, or `None` if the EVM
+/// circuit has no gadget for the opcode.
+pub(crate) fn opcode_execution_state(op: OpcodeId) -> Option<ExecutionState> {
+    if op.is_dup() {
+        return Some(ExecutionState::DUP);
+    }
+    if op.is_push() {
+        return Some(ExecutionState::PUSH);
+    }
+    if op.is_swap() {
+        return Some(ExecutionState::SWAP);
+    }
+    if op.is_log() {
+        return Some(ExecutionState::LOG);
+    }
+
+    let execution_state = match op {
+        OpcodeId::ADD | OpcodeId::SUB => ExecutionState::ADD_SUB,
+        OpcodeId::ADDMOD => ExecutionState::ADDMOD,
+        OpcodeId::ADDRESS => ExecutionState::ADDRESS,
+        OpcodeId::BALANCE => ExecutionState::BALANCE,
+        OpcodeId::MUL | OpcodeId::DIV | OpcodeId::MOD => ExecutionState::MUL_DIV_MOD,
+        OpcodeId::MULMOD => ExecutionState::MULMOD,
+        OpcodeId::SDIV | OpcodeId::SMOD => ExecutionState::SDIV_SMOD,
+        OpcodeId::EQ | OpcodeId::LT | OpcodeId::GT => ExecutionState::CMP,
+        OpcodeId::SLT | OpcodeId::SGT => ExecutionState::SCMP,
+        OpcodeId::SIGNEXTEND => ExecutionState::SIGNEXTEND,
+        OpcodeId::STOP => ExecutionState::STOP,
+        OpcodeId::AND => ExecutionState::BITWISE,
+        OpcodeId::XOR => ExecutionState::BITWISE,
+        OpcodeId::OR => ExecutionState::BITWISE,
+        OpcodeId::NOT => ExecutionState::NOT,
+        OpcodeId::EXP => ExecutionState::EXP,
+        OpcodeId::POP => ExecutionState::POP,
+        OpcodeId::PUSH32 => ExecutionState::PUSH,
+        OpcodeId::BYTE => ExecutionState::BYTE,
+        OpcodeId::MLOAD => ExecutionState::MEMORY,
+        OpcodeId::MSTORE => ExecutionState::MEMORY,
+        OpcodeId::MSTORE8 => ExecutionState::MEMORY,
+        OpcodeId::JUMPDEST => ExecutionState::JUMPDEST,
+        OpcodeId::JUMP => ExecutionState::JUMP,
+        OpcodeId::JUMPI => ExecutionState::JUMPI,
+        OpcodeId::GASPRICE => ExecutionState::GASPRICE,
+        OpcodeId::PC => ExecutionState::PC,
+        OpcodeId::MSIZE => ExecutionState::MSIZE,
+        OpcodeId::CALLER => ExecutionState::CALLER,
+        OpcodeId::CALLVALUE => ExecutionState::CALLVALUE,
+        OpcodeId::EXTCODEHASH => ExecutionState::EXTCODEHASH,
+        OpcodeId::EXTCODESIZE => ExecutionState::EXTCODESIZE,
+        OpcodeId::BLOCKHASH => ExecutionState::BLOCKHASH,
+        OpcodeId::TIMESTAMP | OpcodeId::NUMBER | OpcodeId::GASLIMIT => ExecutionState::BLOCKCTXU64,
+        OpcodeId::COINBASE => ExecutionState::BLOCKCTXU160,
+        OpcodeId::DIFFICULTY | OpcodeId::BASEFEE => ExecutionState::BLOCKCTXU256,
+        OpcodeId::GAS => ExecutionState::GAS,
+        OpcodeId::SAR => ExecutionState::SAR,
+        OpcodeId::SELFBALANCE => ExecutionState::SELFBALANCE,
+        OpcodeId::SHA3 => ExecutionState::SHA3,
+        OpcodeId::SHL | OpcodeId::SHR => ExecutionState::SHL_SHR,
+        OpcodeId::SLOAD => ExecutionState::SLOAD,
+        OpcodeId::SSTORE => ExecutionState::SSTORE,
+        OpcodeId::CALLDATASIZE => ExecutionState::CALLDATASIZE,
+        OpcodeId::CALLDATACOPY => ExecutionState::CALLDATACOPY,
+        OpcodeId::CHAINID => ExecutionState::CHAINID,
+        OpcodeId::ISZERO => ExecutionState::ISZERO,
+        OpcodeId::CALL | OpcodeId::CALLCODE | OpcodeId::DELEGATECALL | OpcodeId::STATICCALL => {
+            ExecutionState::CALL_OP
+        }
+        OpcodeId::ORIGIN => ExecutionState::ORIGIN,
+        OpcodeId::CODECOPY => ExecutionState::CODECOPY,
+        OpcodeId::CALLDATALOAD => ExecutionState::CALLDATALOAD,
+        OpcodeId::CODESIZE => ExecutionState::CODESIZE,
+        OpcodeId::RETURN | OpcodeId::REVERT => ExecutionState::RETURN_REVERT,
+        OpcodeId::RETURNDATASIZE => ExecutionState::RETURNDATASIZE,
+        OpcodeId::RETURNDATACOPY => ExecutionState::RETURNDATACOPY,
+        // dummy ops
+        OpcodeId::EXTCODECOPY => ExecutionState::EXTCODECOPY,
+        OpcodeId::CREATE => ExecutionState::CREATE,
+        OpcodeId::CREATE2 => ExecutionState::CREATE2,
+        OpcodeId::SELFDESTRUCT => ExecutionState::SELFDESTRUCT,
+        _ => return None,
+    };
+    Some(execution_state)
+}
+
 pub(super) fn step_convert(step: &circuit_input_builder::ExecStep) -> ExecStep {
     ExecStep {
         call_index: step.call_index,
@@ -238,5 +250,6 @@ pub(super) fn step_convert(step: &circuit_input_builder::ExecStep) -> ExecStep {
         memory_size: step.memory_size as u64,
         reversible_write_counter: step.reversible_write_counter,
         log_id: step.log_id,
+        gas_refund: step.gas_refund.0,
     }
 }